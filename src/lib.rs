@@ -151,8 +151,40 @@
 //! assert_eq!(val.negative(), 1);
 //! ```
 //!
-//! The macro generates three accessor functions for each field.
+//! The macro generates three accessor functions for each field, and with `try_setters = true` two fallible variants of the setters.
+//! Fields that are readable and writable also get `update_` and `map_` functions, which modify the value with a closure, and `replace_`, which returns the previous value like `mem::replace`.
+//! For `bool` fields, `toggle_` and `with_<field>_toggled` additionally invert the flag.
+//! The functions returning a modified copy, like `with_<field>`, are `#[must_use]`, so discarding their result is a warning; `must_use_getters = true` does the same for the getters.
+//! The `inline` argument adds `#[inline]` (`inline = true`), `#[inline(always)]` (`inline = always`), or `#[inline(never)]` (`inline = never`) to the accessors and the raw conversions, which have no inline attribute by default.
+//! With `try_getters = true`, readable fields of custom types get a fallible `try_<field>` getter, which returns the raw bits as `Err` if they do not survive the round trip through `from_bits` and `into_bits`, like reserved enum patterns mapped to a fallback.
 //! Each accessor also inherits the documentation of its field.
+//! Field documentation may contain the placeholders `{bits}`, `{offset}`, `{mask}`, and `{default}`, which are replaced with the actual values in the accessor documentation (literal defaults like `0x10` as their value `16`).
+//!
+//! ```compile_fail
+//! #![deny(unused_must_use)]
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Flags {
+//!     #[bits(8)]
+//!     value: u8,
+//! }
+//!
+//! let flags = Flags::new();
+//! flags.with_value(1); // the modified copy is discarded
+//! ```
+//!
+//! ```compile_fail
+//! #![deny(unused_must_use)]
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, must_use_getters = true)]
+//! struct Flags {
+//!     #[bits(8)]
+//!     value: u8,
+//! }
+//!
+//! let flags = Flags::new();
+//! flags.value(); // the value is discarded
+//! ```
 //!
 //! The signatures for `int` are:
 //!
@@ -161,13 +193,35 @@
 //! struct MyBitfield(u64);
 //! impl MyBitfield {
 //!     const fn new() -> Self { Self(0) }
+//!     const fn new_zeroed() -> Self { Self(0) } // ignores field defaults
+//!     const fn is_default(&self) -> bool { /* ... */ } // and `is_zero`
+//!     const fn new_with(int: u16, /* other fields ... */) -> Self { /* ... */ }
+//!     const fn from_bits(bits: u64) -> Self { Self(bits) }
+//!     const fn into_bits(self) -> u64 { self.0 }
+//!     const fn raw(&self) -> u64 { self.0 } // and `with_raw`, `set_raw`, unless a field is named `raw`
+//!     fn modify(&mut self, f: impl FnOnce(Self) -> Self) { /* ... */ } // unless a field is named `modify`
+//!     const fn from_be_bytes(bytes: [u8; 8]) -> Self { /* ... */ } // and `_le_`, `_ne_`
+//!     const fn to_be_bytes(self) -> [u8; 8] { /* ... */ }
+//!     const FIELDS: &'static [(&'static str, usize, usize, bool)] = &[/* ... */];
+//!     const OCCUPIED_MASK: u64 = /* ... */; // bits covered by fields, unless a field is named `occupied`
+//!     const PADDING_MASK: u64 = /* ... */; // all other bits, unless a field is named `padding`
+//!     const BITS: usize = 64;
+//!     const DEFAULT: Self = /* ... */; // like `new()`
+//!     const LAYOUT: (&'static str, usize, &'static [/* ... */]) = ("MyBitfield", 64, Self::FIELDS); // or a custom struct with `layout`
 //!
 //!     const INT_BITS: usize = 16;
 //!     const INT_OFFSET: usize = 0;
+//!     const INT_MASK: u64 = 0xffff; // `INT_BITS` at `INT_OFFSET`
+//!     const INT_MAX: u16 = u16::MAX; // and `_MIN` for signed integers
 //!
 //!     const fn with_int(self, value: u16) -> Self { /* ... */ }
 //!     const fn int(&self) -> u16 { /* ... */ }
 //!     fn set_int(&mut self, value: u16) { /* ... */ }
+//!     const fn try_with_int(self, value: u16) -> Result<Self, MyBitfieldError> { /* ... */ } // with `try_setters = true`
+//!     fn try_set_int(&mut self, value: u16) -> Result<(), MyBitfieldError> { /* ... */ }
+//!     fn update_int(&mut self, f: impl FnOnce(u16) -> u16) { /* ... */ }
+//!     fn map_int(self, f: impl FnOnce(u16) -> u16) -> Self { /* ... */ }
+//!     fn replace_int(&mut self, value: u16) -> u16 { /* ... */ }
 //!
 //!     // other field ...
 //! }
@@ -238,11 +292,38 @@
 //! assert!(my_byte_msb.0 == 0b1010_0_10_1);
 //! ```
 //!
+//! Individual fields can override the order with `#[bits(4, order = Msb)]`.
+//! They are placed from the other end, following the previous fields with the same order, as if two bitfields were merged.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Header {
+//!     #[bits(4)]
+//!     flags: u8,
+//!     #[bits(4, order = Msb)]
+//!     version: u8,
+//!     #[bits(8)]
+//!     length: u8,
+//! }
+//!
+//! let header = Header::new().with_flags(1).with_version(4).with_length(0x20);
+//! assert_eq!(header.into_bits(), 0x4201);
+//! ```
+//!
+//! The offsets in the constants and the documentation count from the least significant bit.
+//! For datasheets that number the bits from the most significant bit, like many network protocols, `msb0 = true` additionally generates `<FIELD>_MSB_OFFSET` constants and shows these numbers in the documentation of the accessors.
+//!
 //! ## `fmt::Debug` and `Default`
 //!
 //! This macro automatically creates a suitable `fmt::Debug` and `Default` implementations
 //! similar to the ones created for normal structs by `#[derive(Debug, Default)]`.
 //! You can disable these with the extra `debug` and `default` arguments.
+//! The `debug_order = offset` argument lists the fields by their bit position (least significant first) instead of their declaration order.
+//! Fields marked with `redact` (e.g. `#[bits(32, redact)]`) are printed as `***`, keeping secrets out of logs.
+//! The format of a value can be chosen with `fmt = hex`, `binary`, `octal`, or a format string like `#[bits(12, fmt = "{:#05x}")]`.
+//! The alternate format (`{:#?}`) additionally shows the bit ranges and raw bits of each field, like `level: 2 (bits 5..7 = 0b10)`.
+//! Default values that do not fit into their field, like `#[bits(3, default = 12)]`, are rejected at compile time.
 //!
 //! ```
 //! # use std::fmt;
@@ -268,333 +349,6096 @@
 //! println!("{val:?}")
 //! ```
 //!
-
-#![warn(clippy::unwrap_used)]
-
-use proc_macro as pc;
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::{format_ident, quote, ToTokens};
-use std::stringify;
-use syn::parse::{Parse, ParseStream};
-use syn::spanned::Spanned;
-use syn::Token;
-
-/// Creates a bitfield for this struct.
-///
-/// The arguments first, have to begin with the underlying type of the bitfield:
-/// For example: `#[bitfield(u64)]`.
-///
-/// It can contain an extra `debug` argument for disabling the `Debug` trait
-/// generation (`#[bitfield(u64, debug = false)]`).
-#[proc_macro_attribute]
-pub fn bitfield(args: pc::TokenStream, input: pc::TokenStream) -> pc::TokenStream {
-    match bitfield_inner(args.into(), input.into()) {
-        Ok(result) => result.into(),
-        Err(e) => e.into_compile_error().into(),
-    }
-}
-
-fn bitfield_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
-    let input = syn::parse2::<syn::ItemStruct>(input)?;
-    let Params {
-        ty,
-        bits,
-        debug,
-        default,
-        order,
-    } = syn::parse2::<Params>(args)?;
-
-    let span = input.fields.span();
-    let name = input.ident;
-    let name_str = name.to_string();
-    let vis = input.vis;
-    let attrs: TokenStream = input.attrs.iter().map(ToTokens::to_token_stream).collect();
-
-    let syn::Fields::Named(fields) = input.fields else {
-        return Err(syn::Error::new(span, "only named fields are supported"));
-    };
-
-    let mut offset = 0;
-    let mut members = Vec::with_capacity(fields.named.len());
-    for field in fields.named {
-        let f = Member::new(ty.clone(), bits, field, offset, order)?;
-        offset += f.bits;
-        members.push(f);
-    }
-
-    if offset < bits {
-        return Err(syn::Error::new(
-            span,
-            format!(
-                "The bitfiled size ({bits} bits) has to be equal to the sum of its members ({offset} bits)!. \
-                You might have to add padding (a {} bits large member prefixed with \"_\").",
-                bits - offset
-            ),
-        ));
-    }
-    if offset > bits {
-        return Err(syn::Error::new(
-            span,
-            format!(
-                "The size of the members ({offset} bits) is larger than the type ({bits} bits)!."
-            ),
-        ));
-    }
-
-    let debug_impl = if debug {
-        let debug_fields = members.iter().map(Member::debug);
-        quote! {
-            impl core::fmt::Debug for #name {
-                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                    f.debug_struct(#name_str)
-                        #( #debug_fields )*
-                        .finish()
-                }
-            }
-        }
-    } else {
-        TokenStream::default()
-    };
-
-    let defaults = members.iter().map(Member::default);
-
-    let default_impl = if default {
-        quote! {
-            impl Default for #name {
-                fn default() -> Self {
-                    Self::new()
-                }
-            }
-        }
-    } else {
-        TokenStream::new()
-    };
-
-    Ok(quote! {
-        #attrs
-        #[derive(Copy, Clone)]
-        #[repr(transparent)]
-        #vis struct #name(#ty);
-
-        impl #name {
-            /// Creates a new default initialized bitfield.
-            #vis const fn new() -> Self {
-                let mut this = Self(0);
-                #( #defaults )*
-                this
-            }
-
-            #( #members )*
-        }
-
-        #default_impl
-
-        impl From<#ty> for #name {
-            fn from(v: #ty) -> Self {
-                Self(v)
-            }
-        }
-        impl From<#name> for #ty {
-            fn from(v: #name) -> #ty {
-                v.0
-            }
-        }
-
-        #debug_impl
-    })
-}
-
-/// Represents a member where accessor functions should be generated for.
-struct Member {
-    offset: usize,
-    bits: usize,
-    base_ty: syn::Type,
-    default: TokenStream,
-    inner: Option<MemberInner>,
-}
-
-struct MemberInner {
-    ident: syn::Ident,
-    ty: syn::Type,
-    attrs: Vec<syn::Attribute>,
-    vis: syn::Visibility,
-    into: TokenStream,
-    from: TokenStream,
-}
-
-impl Member {
-    fn new(
-        base_ty: syn::Type,
-        base_bits: usize,
-        f: syn::Field,
-        offset: usize,
-        order: Order,
-    ) -> syn::Result<Self> {
-        let span = f.span();
-
-        let syn::Field {
-            mut attrs,
-            vis,
-            ident,
-            ty,
-            ..
-        } = f;
-
-        let ident = ident.ok_or_else(|| syn::Error::new(span, "Not supported"))?;
-        let ignore = ident.to_string().starts_with('_');
-
-        let Field {
-            bits,
-            ty,
-            mut default,
-            into,
-            from,
-        } = parse_field(&attrs, &ty, ignore)?;
-
-        if bits > 0 && !ignore {
-            if offset + bits > base_bits {
-                return Err(syn::Error::new(
-                    ty.span(),
-                    "The total size of the members is too large!",
-                ));
-            };
-
-            // compute the offset
-            let offset = if order == Order::Lsb {
-                offset
-            } else {
-                base_bits - offset - bits
-            };
-
-            if into.is_empty() || from.is_empty() {
-                return Err(syn::Error::new(
-                    ty.span(),
-                    "Custom types require 'into', and 'from' in the #[bits] attribute",
-                ));
-            }
-
-            if default.is_empty() {
-                default = quote!(#ty::from_bits(0));
-            }
-
-            // remove our attribute
-            attrs.retain(|a| !a.path().is_ident("bits"));
-
-            Ok(Self {
-                offset,
-                bits,
-                base_ty,
-                default,
-                inner: Some(MemberInner {
-                    ident,
-                    ty,
-                    attrs,
-                    vis,
-                    into,
-                    from,
-                }),
-            })
-        } else {
-            if default.is_empty() {
-                default = quote!(0);
-            }
-
-            Ok(Self {
-                offset,
-                bits,
-                base_ty,
-                default,
-                inner: None,
-            })
-        }
-    }
-
-    fn debug(&self) -> TokenStream {
-        if let Some(inner) = &self.inner {
-            let ident_str = inner.ident.to_string();
-            let ident = &inner.ident;
-            quote!(.field(#ident_str, &self.#ident()))
-        } else {
-            quote!()
-        }
-    }
-
-    fn default(&self) -> TokenStream {
-        let default = &self.default;
-        if let Some(inner) = &self.inner {
-            let ident = &inner.ident;
-            let with_ident = format_ident!("with_{ident}");
-            quote!(this = this.#with_ident(#default);)
-        } else {
-            let offset = self.offset;
-            let base_ty = &self.base_ty;
-            quote!(this.0 |= (#default as #base_ty) << #offset;)
-        }
-    }
-}
-
-impl ToTokens for Member {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let Self {
-            offset,
-            bits,
-            base_ty,
-            default: _,
-            inner: Some(MemberInner { ident, ty, attrs, vis, into, from }),
-        } = self else {
-            return Default::default();
-        };
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct TooLarge {
+//!     #[bits(3, default = 12)]
+//!     level: u8,
+//!     #[bits(5)]
+//!     __: u8,
+//! }
+//! ```
+//!
+//! ## Encodings
+//!
+//! Fields can also be stored in a different representation than their binary value.
+//! With `encoding = bcd`, unsigned integers are stored as packed binary-coded decimals (one digit per nibble),
+//! as commonly found in RTC registers:
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Time {
+//!     #[bits(7, encoding = bcd)]
+//!     minutes: u8,
+//!     #[bits(6, encoding = bcd)]
+//!     hours: u8,
+//!     #[bits(3)]
+//!     __: u8,
+//! }
+//!
+//! let time = Time::new().with_minutes(59).with_hours(23);
+//! assert_eq!(time.minutes(), 59);
+//! assert_eq!(time.0, 0x23 << 7 | 0x59);
+//! ```
+//!
+//! With `reverse = true`, the bits of a field are stored in reverse order, like the data of LSB-first shift registers.
+//! This is independent of the conversion of the field value.
+//! Similarly, `endian = big` swaps the bytes of a field, for big endian protocol fields embedded in a little endian register.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Shift {
+//!     #[bits(4, reverse = true)]
+//!     data: u8,
+//!     #[bits(4)]
+//!     __: u8,
+//! }
+//!
+//! let shift = Shift::new().with_data(0b0001);
+//! assert_eq!(shift.data(), 0b0001);
+//! assert_eq!(shift.into_bits(), 0b1000);
+//! ```
+//!
+//! ## Parity
+//!
+//! A single 1 bit `bool` field can be declared as parity over a range of the underlying bits.
+//! The macro then generates `with_valid_parity()`, which returns a copy with the parity bit set accordingly,
+//! and `is_parity_ok()`, which checks the stored parity.
+//! The range refers to the raw bit positions and must not contain the parity bit itself.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32)]
+//! struct Frame {
+//!     #[bits(31)]
+//!     data: u32,
+//!     #[bits(1, parity = even, over = 0..31)]
+//!     parity: bool,
+//! }
+//!
+//! let frame = Frame::new().with_data(0b1011).with_valid_parity();
+//! assert!(frame.parity());
+//! assert!(frame.is_parity_ok());
+//! assert!(!frame.with_data(0b1001).is_parity_ok());
+//! ```
+//!
+//! ## `NonZero` Backing
+//!
+//! The bitfield can also be backed by a `NonZero` unsigned integer, which allows the compiler to use the zero value as niche (`Option<MyHandle>` has the same size as `MyHandle`).
+//! Because the value must never become zero, the accessors panic if they would produce a zero value.
+//! The defaults have to contain a nonzero bit, which is checked at compile time, unless `new = false, default = false` disables the default constructors.
+//! Raw values can be checked with `new_checked`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use std::num::NonZeroU64;
+//!
+//! #[bitfield(NonZeroU64)]
+//! struct Handle {
+//!     #[bits(default = true)]
+//!     valid: bool,
+//!     #[bits(63)]
+//!     index: u64,
+//! }
+//!
+//! assert_eq!(std::mem::size_of::<Option<Handle>>(), 8);
+//!
+//! let handle = Handle::new().with_index(42);
+//! assert_eq!(handle.index(), 42);
+//! assert!(Handle::new_checked(0).is_none());
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! # use std::num::NonZeroU8;
+//! #[bitfield(NonZeroU8)]
+//! struct Zeroed {
+//!     value: u8, // error: the default would be zero
+//! }
+//! ```
+//!
+//! ## Float Backing
+//!
+//! Bitfields can also be backed by `f32` or `f64`, which are stored as the bits of the float.
+//! This exposes the sign, exponent, and mantissa of IEEE 754 floats without converting them to integers by hand.
+//! `into_bits` and `from_bits` work on the unsigned integer, while `From` converts from and into both the float and the integer.
+//! Only these conversions call `to_bits` and `from_bits` of the float, so the accessors stay `const` without requiring Rust 1.83.
+//! Traits like `eq` and `ops` operate on the bits, so `NaN` equals itself and `0.0` differs from `-0.0`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(f32)]
+//! struct Single {
+//!     #[bits(23)]
+//!     mantissa: u32,
+//!     #[bits(8)]
+//!     exponent: u8,
+//!     sign: bool,
+//! }
+//!
+//! let v = Single::from(-1.5);
+//! assert!(v.sign());
+//! assert_eq!(v.exponent(), 127);
+//! assert_eq!(f32::from(v.with_sign(false)), 1.5);
+//! ```
+//!
+//! ## Signed Backing
+//!
+//! Bitfields can also be backed by signed integers, like `i32`, to keep the signatures of registers that are declared as signed in C headers.
+//! `into_bits` and `from_bits` take and return the signed integer, while `From` converts from and into it, and also into the unsigned counterpart.
+//! When nested in other bitfields, the signed bits are not sign-extended.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(i32)]
+//! struct Status {
+//!     #[bits(31)]
+//!     code: u32,
+//!     error: bool,
+//! }
+//!
+//! let status = Status::from(-1);
+//! assert!(status.error());
+//! assert_eq!(status.into_bits(), -1);
+//! assert_eq!(u32::from(status), u32::MAX);
+//! ```
+//!
+//! ## Trait Integrations
+//!
+//! With `zeroize = true`, the macro implements [`zeroize::Zeroize`](https://docs.rs/zeroize) for the bitfield,
+//! which requires the `zeroize` crate as dependency.
+//! The argument also accepts a `cfg` predicate (e.g. `zeroize = cfg(feature = "zeroize")`) to generate the implementation only when a feature of your crate is enabled.
+//! For zeroization on drop, wrap the bitfield into `zeroize::Zeroizing`.
+//!
+//! Similarly, `bytemuck = true` (or a `cfg` predicate) implements the [`bytemuck`](https://docs.rs/bytemuck) traits `Zeroable` and `Pod`, so slices of bitfields can be cast with `bytemuck::cast_slice`.
+//! It is not supported for `NonZero` bitfields, as they cannot be zero, or together with `copy = false`, as `Pod` requires `Copy`.
+//!
+//! For fuzzing, `arbitrary = true` (or a `cfg` predicate) implements [`arbitrary::Arbitrary`](https://docs.rs/arbitrary).
+//! Fields take random bits only if they survive the round trip through their type, so custom types keep valid encodings, and padding keeps its defaults.
+//!
+//! For Substrate runtimes, `scale_codec = true` (or a `cfg` predicate) implements the [`parity-scale-codec`](https://docs.rs/parity-scale-codec) traits `Encode`, `Decode`, and `MaxEncodedLen`, which requires the crate as `parity_scale_codec` dependency.
+//! The raw value is encoded like its integer (or array), and with `validate = true`, decoding fails for invalid bits.
+//!
+//! On targets where `core::fmt` is too heavy, `ufmt = true` (or a `cfg` predicate) implements [`ufmt::uDebug`](https://docs.rs/ufmt) like `Debug`, and `ufmt::uDisplay` in the format of `Display`, which requires the field types to implement them as well.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use zeroize::{Zeroize, Zeroizing};
+//!
+//! #[bitfield(u32, zeroize = true)]
+//! struct Secret {
+//!     key: u32,
+//! }
+//!
+//! let mut secret = Secret::new().with_key(0xdead_beef);
+//! secret.zeroize();
+//! assert_eq!(secret.key(), 0);
+//!
+//! let secret = Zeroizing::new(Secret::new().with_key(0xdead_beef));
+//! ```
+//!
+//! ## Slices
+//!
+//! With `slices = true`, the macro generates `from_raw_slice`, `from_raw_slice_mut`, `as_raw_slice`, and `as_raw_slice_mut`,
+//! which reinterpret slices of the underlying type as slices of the bitfield and vice versa.
+//! This is sound because the generated struct is `repr(transparent)`, but it is opt-in, as it is the only generated code using `unsafe`.
+//! Without allocating, `encode_slice` additionally writes bitfields into a byte buffer and `decode_slice` iterates over the bitfields in a byte buffer, both in native byte order.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, slices = true)]
+//! struct Descriptor {
+//!     #[bits(15)]
+//!     len: u16,
+//!     valid: bool,
+//! }
+//!
+//! let raw = [0x8001u16, 2];
+//! let descriptors = Descriptor::from_raw_slice(&raw);
+//! assert!(descriptors[0].valid());
+//! assert_eq!(descriptors[1].len(), 2);
+//!
+//! let mut buf = [0; 4];
+//! Descriptor::encode_slice(descriptors, &mut buf).unwrap();
+//! assert_eq!(Descriptor::decode_slice(&buf).nth(1).unwrap().len(), 2);
+//! ```
+//!
+//! ## `alloc` Conveniences
+//!
+//! With `alloc = true` (or `alloc = cfg(feature = "alloc")`), the macro generates a few helpers for host-side tooling that depend on the `alloc` crate:
+//! `to_bytes_vec` and `from_bytes_vec` convert slices of bitfields to and from their native-endian bytes,
+//! and `into_raw_vec` and `from_raw_vec` convert between `Vec<Self>` and a `Vec` of the underlying type.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, alloc = true)]
+//! struct Command {
+//!     opcode: u8,
+//!     arg: u8,
+//! }
+//!
+//! let commands = [Command::new().with_opcode(1), Command::new().with_arg(2)];
+//! let bytes = Command::to_bytes_vec(&commands);
+//! assert_eq!(bytes.len(), 4);
+//!
+//! let commands = Command::from_bytes_vec(&bytes).unwrap();
+//! assert_eq!(Command::into_raw_vec(commands), vec![0x0001, 0x0200]);
+//! ```
+//!
+//! ## Bounds Checks
+//!
+//! In debug builds, the `with_<name>` and `set_<name>` accessors assert that the value fits into the field.
+//! The assertion message names the struct, the field, and the valid range, like `MyBitfield::kind: value out of bounds (max 15)`.
+//! It can be replaced per field with `#[bits(4, bounds_message = "...")]`.
+//! As the accessors are `const`, the message cannot contain the offending value.
+//!
+//! With `try_setters = true`, the `try_with_<name>` and `try_set_<name>` accessors check the value in all builds and return `Err` instead of truncating it.
+//! The fallible functions return the generated `<Name>Error` enum, which tells apart values `OutOfBounds`, writes to `ReadOnly` fields, `Invalid` bitfields, and `Parse` errors, and is only generated along with them.
+//! With `error = path::to::Error`, they return a custom error type instead, which has to implement `From<<Name>Error>`.
+//! As this conversion is not `const`, `try_with_<name>` is then no `const fn`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[derive(Debug, PartialEq)]
+//! enum DriverError {
+//!     Register(RegError),
+//! }
+//! impl From<RegError> for DriverError {
+//!     fn from(e: RegError) -> Self {
+//!         Self::Register(e)
+//!     }
+//! }
+//!
+//! #[bitfield(u8, try_setters = true, error = DriverError)]
+//! struct Reg {
+//!     #[bits(4)]
+//!     kind: u8,
+//!     #[bits(4)]
+//!     __: u8,
+//! }
+//!
+//! assert_eq!(Reg::new().try_with_kind(16).unwrap_err(), DriverError::Register(RegError::OutOfBounds));
+//! ```
+//!
+//! The behavior of `with_<name>` and `set_<name>` can be changed for the whole bitfield (`#[bitfield(u32, overflow = saturate)]`) or per field (`#[bits(4, overflow = truncate)]`):
+//! `panic` always asserts, `truncate` discards the bits that do not fit, and `saturate` clamps the value to the range of the field.
+//!
+//! Constraints beyond the bounds can be checked with `#[bits(7, validate = is_valid_asid)]`, where `is_valid_asid` is a `const fn(&u8) -> bool`.
+//! Rejected values panic in the `with_<name>` and `set_<name>` accessors (only in debug builds unless `overflow = panic`), make the `try_` accessors and `try_from_bits` return `Err`, and are compile errors as defaults.
+//!
+//! ## Renaming Fields
+//!
+//! When renaming a field, `#[bits(4, alias = "old_name")]` additionally generates the accessors and constants under the old name, marked as `#[deprecated]`.
+//! This allows migrating callers gradually.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct MyBitfield {
+//!     #[bits(4, alias = "kind")]
+//!     category: u8,
+//!     #[bits(4)]
+//!     __: u8,
+//! }
+//!
+//! #[allow(deprecated)]
+//! let value = MyBitfield::new().with_kind(3);
+//! assert_eq!(value.category(), 3);
+//! ```
+//!
+//! Names from a datasheet can be given with `doc_alias = "TXFE"`, which adds `#[doc(alias)]` to the accessors, so that searching the documentation or the editor for the datasheet name finds them.
+//!
+//! ## Getter Trait
+//!
+//! With `fields_trait = true`, the macro additionally emits a `<Name>Fields` trait containing the getters, which is implemented by the bitfield.
+//! A custom trait name can be given with `fields_trait = MyTrait`.
+//! This allows substituting hand-built fakes in tests where real register values are inconvenient to construct.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, fields_trait = true)]
+//! struct Status {
+//!     busy: bool,
+//!     #[bits(7)]
+//!     count: u8,
+//! }
+//!
+//! struct FakeStatus;
+//! impl StatusFields for FakeStatus {
+//!     fn busy(&self) -> bool { true }
+//!     fn count(&self) -> u8 { 100 }
+//! }
+//!
+//! fn is_congested(status: &impl StatusFields) -> bool {
+//!     status.busy() && status.count() > 10
+//! }
+//! assert!(is_congested(&FakeStatus));
+//! assert!(!is_congested(&Status::new()));
+//! ```
+//!
+//! ## Array Backing
+//!
+//! Bitfields wider than 128 bits can be backed by an array of unsigned integers.
+//! The first element holds the least significant bits, and fields may span multiple elements.
+//! Each field is still limited to 128 bits and is handled in the smallest unsigned integer that fits.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield([u32; 8])]
+//! struct Descriptor {
+//!     #[bits(100)]
+//!     address: u128,
+//!     #[bits(40)]
+//!     length: u64,
+//!     valid: bool,
+//!     #[bits(115)]
+//!     __: (),
+//! }
+//!
+//! let d = Descriptor::new().with_address(0xf_1234_5678).with_valid(true);
+//! assert_eq!(d.address(), 0xf_1234_5678);
+//! let raw: [u32; 8] = d.into();
+//! assert_eq!(raw[1], 0xf);
+//! ```
+//!
+//! Byte arrays like `[u8; N]` hold the bytes of a message in order, so `endian = big` on the bitfield stores all multi-byte fields big endian.
+//! Single fields can still override the byte order with `endian = little`, like legacy fields of mixed-endian protocols.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield([u8; 4], endian = big)]
+//! struct Header {
+//!     #[bits(16)]
+//!     kind: u16,
+//!     #[bits(16, endian = little)]
+//!     legacy: u16,
+//! }
+//!
+//! let header = Header::new().with_kind(0x1234).with_legacy(0x5678);
+//! assert_eq!(header.0, [0x12, 0x34, 0x78, 0x56]);
+//! ```
+//!
+//! ## Nested Bitfields
+//!
+//! Every bitfield has `const` `from_bits`/`into_bits` functions, so it can be used as the field type of another bitfield.
+//! Bitfields also have a `BITS` constant, and `#[bits(8, checked)]` asserts at compile time that the `BITS` of the field type fit into the field.
+//! The raw types of `into_bits` and `from_bits` are checked at compile time as well, so they cannot be truncated silently: `into_bits` may not return more bits than the raw type of the bitfield, and `from_bits` has to take all bits of the field.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Status {
+//!     ready: bool,
+//!     #[bits(7)]
+//!     code: u8,
+//! }
+//!
+//! #[bitfield(u32)]
+//! struct Register {
+//!     #[bits(8, checked)]
+//!     status: Status,
+//!     #[bits(24)]
+//!     value: u32,
+//! }
+//!
+//! let reg = Register::new().with_status(Status::new().with_code(3));
+//! assert_eq!(reg.status().code(), 3);
+//! assert_eq!(reg.into_bits(), 3 << 1);
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Wide {
+//!     #[bits(16)]
+//!     value: u16,
+//! }
+//!
+//! #[bitfield(u8)]
+//! struct Narrow {
+//!     #[bits(8)]
+//!     wide: Wide, // `into_bits` returns 16 bits
+//! }
+//! ```
+//!
+//! ## `FromBits` Derive
+//!
+//! The `FromBits` derive generates the `const` `into_bits`/`from_bits` functions for fieldless enums.
+//! The bits have the type of the `#[repr]` attribute, or without one the smallest unsigned integer that holds every discriminant (`isize` if they are not all non-negative literals), and the `BITS` constant holds the number of bits required for all variants.
+//! Unknown bit patterns are mapped to the variant marked with `#[fallback]`, or panic otherwise.
+//! `try_from_bits` returns `None` for them instead, regardless of the fallback.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use bitfield_struct::FromBits;
+//!
+//! #[derive(FromBits, Debug, PartialEq, Eq)]
+//! #[repr(u8)]
+//! enum Mode {
+//!     Off = 0,
+//!     On = 1,
+//!     #[fallback]
+//!     Invalid = 3,
+//! }
+//!
+//! #[bitfield(u8)]
+//! struct Control {
+//!     #[bits(2)]
+//!     mode: Mode,
+//!     #[bits(6)]
+//!     __: u8,
+//! }
+//!
+//! assert_eq!(Control::from_bits(1).mode(), Mode::On);
+//! assert_eq!(Control::from_bits(2).mode(), Mode::Invalid);
+//! assert_eq!(Mode::BITS, 2);
+//! ```
+//!
+//! Without a fallback, `#[bits(2, exhaustive)]` converts every bit pattern of the field at compile time, so a missing variant is a compile error instead of a panic.
+//!
+//! ```compile_fail
+//! # use bitfield_struct::{bitfield, FromBits};
+//! #[derive(FromBits)]
+//! #[repr(u8)]
+//! enum Speed {
+//!     Slow = 0,
+//!     Medium = 1,
+//!     Fast = 2,
+//! }
+//!
+//! #[bitfield(u8, debug = false)]
+//! struct Fan {
+//!     #[bits(2, exhaustive)] // the pattern 3 is not handled
+//!     speed: Speed,
+//!     #[bits(6)]
+//!     __: u8,
+//! }
+//! ```
+//!
+//! ## Access Modes
+//!
+//! The `access` argument of the bits attribute restricts the generated accessors.
+//! `access = RO` only generates the getter, `access = WO` only the setter and builder (`RW` is the default).
+//! Write-only fields are omitted from the `Debug` output.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Status {
+//!     #[bits(access = RO)]
+//!     busy: bool,
+//!     #[bits(7, access = WO)]
+//!     command: u8,
+//! }
+//!
+//! let status = Status::from_bits(1).with_command(3);
+//! assert!(status.busy());
+//! assert_eq!(status.into_bits(), 0b111);
+//! ```
+//!
+//! Write-1-to-clear fields, like pending interrupts, use `access = W1C`.
+//! They have a getter, and `clear_<field>` and `with_<field>_cleared` write ones to the field to clear it in hardware.
+//! `without_w1c` returns a copy with these fields set to zero, so writing a read value back does not clear them, which `modify` does automatically.
+//! Fields that are cleared by reading them use `access = RC`, which replaces the getter with `take_<field>`, returning the value and setting the field to zero.
+//! Like write-only fields, they are omitted from the `Debug` output.
+//!
+//! ## Fixed Positions
+//!
+//! Fields can be pinned to a fixed bit range with `#[bits(4..8)]` (or `#[bits(4, offset = 4)]`), independent of the declaration order.
+//! The bit positions are counted from the least significant bit, regardless of the `order`.
+//! Fields without a fixed position follow the previous field, and gaps between the fields are left as zeroed padding.
+//! Overlapping fields are rejected, unless they are marked with `overlaps`, like `#[bits(0..16, overlaps)] raw: u16`.
+//! Such aliases neither count towards the size nor move the following fields, and they are left out of `new_with` and the defaults.
+//! A field can also be split over multiple ranges, like `#[bits([16..40, 56..64])]`, where the first range holds the least significant bits of the value.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Datasheet {
+//!     #[bits(12..16)]
+//!     opcode: u8,
+//!     #[bits(0..8)]
+//!     operand: u8,
+//! }
+//!
+//! let val = Datasheet::new().with_opcode(0xa).with_operand(0x55);
+//! assert_eq!(val.into_bits(), 0xa055);
+//! ```
+//!
+//! ## Layout Metadata
+//!
+//! The associated `FIELDS` constant lists the layout of all fields (without padding) in declaration order.
+//! Each entry is a `(name, offset, bits, signed)` tuple, so generic tooling can iterate the layout without depending on the struct:
+//!
+//! - `name` is the name of the field,
+//! - `offset` is the position of its lowest bit, counted from the least significant bit regardless of `order`,
+//! - `bits` is the number of bits of the field,
+//! - `signed` is `true` if the field is a signed integer, like `i8`, whose bits are sign extended.
+//!   Fields of custom types are always `false`, even if their own conversion is signed.
+//!
+//! `LAYOUT` bundles them with the name and size of the bitfield as `(name, bits, fields)`, which can be collected into register maps.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Flags {
+//!     #[bits(4)]
+//!     level: i8,
+//!     #[bits(4)]
+//!     kind: u8,
+//! }
+//!
+//! for (name, offset, bits, signed) in Flags::FIELDS {
+//!     println!("{name}: {offset}..{} (signed: {signed})", offset + bits);
+//! }
+//! assert_eq!(Flags::FIELDS[1], ("kind", 4, 4, false));
+//! assert_eq!(Flags::LAYOUT.0, "Flags");
+//! assert_eq!(Flags::LAYOUT.1, 8);
+//! ```
+//!
+//! As a proc-macro crate can only export macros, it cannot provide a named type for `LAYOUT`.
+//! Instead, `layout = path::to::Layout` builds it as a struct of your crate with the fields `name`, `bits`, and `fields`, so that the layouts of all bitfields share one type.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! pub struct Layout {
+//!     pub name: &'static str,
+//!     pub bits: usize,
+//!     pub fields: &'static [(&'static str, usize, usize, bool)],
+//! }
+//!
+//! #[bitfield(u8, layout = Layout)]
+//! struct Flags {
+//!     #[bits(4)]
+//!     level: i8,
+//!     #[bits(4)]
+//!     kind: u8,
+//! }
+//!
+//! const REGISTERS: &[Layout] = &[Flags::LAYOUT];
+//! assert_eq!(REGISTERS[0].name, "Flags");
+//! assert_eq!(REGISTERS[0].fields.len(), 2);
+//! ```
+//!
+//! ## Dynamic Field Access
+//!
+//! With `field_enum = true`, the macro additionally emits a `<Name>Field` enum with one variant per field (a custom name can be given with `field_enum = MyEnum`).
+//! The bitfield then gets `get` and `set` functions that access the raw bits of a field selected at runtime.
+//! The values are `u64`, or `u128` if a field is wider than 64 bits.
+//! `set` returns `Err` if the value does not fit or the field is read-only.
+//! `diff` compares two values and yields the fields whose bits differ, together with the old and new bits, e.g. to log register changes.
+//! Accessors named `get`, `set`, or `diff` would clash with these functions and are rejected, so such fields have to be renamed with `#[bits(get = ...)]`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, field_enum = true)]
+//! struct MyReg {
+//!     #[bits(4)]
+//!     irq_mask: u8,
+//!     #[bits(12)]
+//!     level: u16,
+//! }
+//!
+//! let mut reg = MyReg::new();
+//! let field = MyRegField::from_name("irq_mask").unwrap();
+//! reg.set(field, 0xa).unwrap();
+//! assert_eq!(reg.get(field), 0xa);
+//! assert_eq!(MyRegField::ALL.map(MyRegField::name), ["irq_mask", "level"]);
+//!
+//! let changed: Vec<_> = reg.diff(&reg.with_level(3)).collect();
+//! assert_eq!(changed, [(MyRegField::Level, 0, 3)]);
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, field_enum = true)]
+//! struct Mux {
+//!     #[bits(4)]
+//!     get: u8, // error: clashes with `Mux::get`
+//!     #[bits(4)]
+//!     set: u8,
+//! }
+//! ```
+//!
+//! ## Atomics
+//!
+//! With `atomic = true` (or `atomic = cfg(...)`), the macro additionally emits an `Atomic<Name>` wrapper around the matching atomic integer.
+//! Besides `load`, `store`, `swap`, and `fetch_update`, it has a `fetch_update_<field>` function for every readable and writable field, which retries until the update of only that field succeeds.
+//! For bitfields stored in existing atomics, the bitfield itself also gets `set_<field>_atomic(&AtomicU32, value, Ordering)` functions, which replace only that field.
+//! Multiple fields are changed with a single read-modify-write by `modify(|b| b.with_a(1).with_b(b.b() + 1), Ordering)` (or `modify_atomic` for existing atomics), where the closure is called again with the current value if another thread changed it in the meantime.
+//! Fields named like these functions, such as `modify` or `modify_atomic`, keep their accessors, and the functions of the same name are omitted.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use core::sync::atomic::Ordering;
+//!
+//! #[bitfield(u32, atomic = true)]
+//! struct Lock {
+//!     locked: bool,
+//!     #[bits(31)]
+//!     waiters: u32,
+//! }
+//!
+//! let lock = AtomicLock::new(Lock::new());
+//! lock.fetch_update_waiters(Ordering::Relaxed, Ordering::Relaxed, |w| Some(w + 1))
+//!     .unwrap();
+//! assert_eq!(lock.load(Ordering::Relaxed).waiters(), 1);
+//!
+//! let raw = core::sync::atomic::AtomicU32::new(0);
+//! Lock::set_locked_atomic(&raw, true, Ordering::AcqRel);
+//! assert_eq!(raw.load(Ordering::Relaxed), 1);
+//! ```
+//!
+//! ## Byte Conversions
+//!
+//! Every bitfield can be converted from and into byte arrays with `from_be_bytes`/`to_be_bytes` and their `_le_` and `_ne_` variants.
+//! Array backed bitfields are converted as a whole, with the first element holding the least significant bytes.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Header {
+//!     #[bits(4)]
+//!     version: u8,
+//!     #[bits(12)]
+//!     length: u16,
+//! }
+//!
+//! let header = Header::from_be_bytes([0x12, 0x34]);
+//! assert_eq!(header.version(), 4);
+//! assert_eq!(header.length(), 0x123);
+//! assert_eq!(header.to_le_bytes(), [0x34, 0x12]);
+//! ```
+//!
+//! ## `Display` and `FromStr`
+//!
+//! With `display = true`, the macro additionally implements `Display` and `FromStr`.
+//! Fields are written as `name=value` and set flags just as `name`, like `kind=3 level=2 present`.
+//! Parsing starts with the defaults (and all flags cleared), so the output of `Display` round-trips.
+//! Custom field types have to implement `Display` and `FromStr` themselves.
+//! Redacted fields are written as `***`, which keeps their defaults when parsed, and read-only fields are parsed like the others.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, display = true)]
+//! struct Entry {
+//!     #[bits(4)]
+//!     kind: u8,
+//!     #[bits(3)]
+//!     level: u8,
+//!     present: bool,
+//! }
+//!
+//! let entry = Entry::new().with_kind(3).with_level(2).with_present(true);
+//! assert_eq!(entry.to_string(), "kind=3 level=2 present");
+//! let parsed: Entry = "kind=3 level=2 present".parse().unwrap();
+//! assert_eq!(parsed.into_bits(), entry.into_bits());
+//! ```
+//!
+//! ## Radix Formatting
+//!
+//! With `radix = true`, the macro additionally implements `Binary`, `Octal`, `LowerHex`, and `UpperHex`, which format the raw value.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32, radix = true)]
+//! struct Reg {
+//!     #[bits(16)]
+//!     low: u16,
+//!     #[bits(16)]
+//!     high: u16,
+//! }
+//!
+//! let reg = Reg::new().with_low(0xbeef).with_high(0xa);
+//! assert_eq!(format!("{reg:#010x}"), "0x000abeef");
+//! ```
+//!
+//! ## Validating Conversions
+//!
+//! With `validate = true`, the macro implements `TryFrom` instead of `From` for the raw type, together with a `try_from_bits` function.
+//! They reject raw values where a field does not survive the round trip through its type (like enums without a matching variant) or where the padding differs from its defaults.
+//! The rejected value is returned as error.
+//! Types with their own conversion are checked with their `const fn try_from_bits(bits) -> Option<Self>`, which the `FromBits` derive generates, so that unknown patterns do not panic.
+//! The same applies to `try_getters`, `arbitrary`, and `kani`.
+//! Types without it, like nested bitfields, can be converted explicitly with `#[bits(8, from = Inner::from_bits, into = Inner::into_bits)]`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, validate = true)]
+//! struct Packet {
+//!     #[bits(4)]
+//!     kind: u8,
+//!     #[bits(4, default = 0b1010)]
+//!     __: u8,
+//! }
+//!
+//! assert_eq!(Packet::try_from(0xa3).map(|p| p.kind()), Ok(3));
+//! assert_eq!(Packet::try_from(0x03).err(), Some(0x03));
+//! ```
+//!
+//! With `conversions = false`, neither `From` nor `TryFrom` are implemented between the bitfield and its raw type, leaving only `from_bits` and `into_bits` (and `try_from_bits`).
+//! This makes unchecked conversions explicit and avoids conflicts with custom implementations.
+//!
+//! ## Custom Constructors
+//!
+//! `new = false` omits the generated `new`, `new_zeroed`, and `new_with` functions, so that a custom constructor can take its place.
+//! Alternatively, they can be given a visibility that differs from the struct, like `new = pub(crate)`.
+//! The `Default` implementation is still generated unless it is disabled with `default = false`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, new = false, default = false)]
+//! pub struct Port {
+//!     #[bits(15)]
+//!     number: u16,
+//!     open: bool,
+//! }
+//!
+//! impl Port {
+//!     pub fn new(number: u16) -> Option<Self> {
+//!         (number != 0).then(|| Self::from_bits(0).with_number(number))
+//!     }
+//! }
+//!
+//! assert!(Port::new(0).is_none());
+//! assert_eq!(Port::new(443).unwrap().number(), 443);
+//! ```
+//!
+//! ## Typestate Builder
+//!
+//! With `builder = true`, the macro additionally emits a `<Name>Builder` that is created with `builder()`.
+//! Its `build` function is only available once every field without `default` has been set, so forgetting one is a compile error.
+//! Padding and parity bits are not set with the builder.
+//! A custom builder name can be given with `builder = MyBuilder`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, builder = true)]
+//! struct Descriptor {
+//!     #[bits(12)]
+//!     base: u16,
+//!     #[bits(3, default = 1)]
+//!     kind: u8,
+//!     user: bool,
+//! }
+//!
+//! let desc = Descriptor::builder().base(0x123).user(true).build();
+//! assert_eq!(desc.kind(), 1);
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! # #[bitfield(u16, builder = true)]
+//! # struct Descriptor {
+//! #     #[bits(12)]
+//! #     base: u16,
+//! #     #[bits(3, default = 1)]
+//! #     kind: u8,
+//! #     user: bool,
+//! # }
+//! // `user` is missing
+//! let desc = Descriptor::builder().base(0x123).build();
+//! ```
+//!
+//! ## Equality Without Padding
+//!
+//! With `eq = true`, the macro additionally implements `PartialEq`, `Eq`, and `Hash`, which only consider the bits covered by fields.
+//! Padding bits with arbitrary content, like reserved bits read from hardware, are ignored.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, eq = true)]
+//! struct Reg {
+//!     #[bits(4)]
+//!     value: u8,
+//!     #[bits(4)]
+//!     __: u8,
+//! }
+//!
+//! assert_eq!(Reg::from_bits(0x35), Reg::from_bits(0x05));
+//! ```
+//!
+//! ## Automatic Padding
+//!
+//! With `pad = auto`, the bits that are not covered by members are left as padding, instead of requiring explicit padding members.
+//! These are the most significant bits for the default `order = Lsb` and the least significant bits for `order = Msb`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32, pad = auto)]
+//! struct Control {
+//!     #[bits(4)]
+//!     mode: u8,
+//!     enable: bool,
+//! }
+//!
+//! assert_eq!(Control::new().with_enable(true).into_bits(), 0x10);
+//! assert_eq!(Control::PADDING_MASK, 0xffff_ffe0);
+//! ```
+//!
+//! ## Flags
+//!
+//! With `flags = true`, a bitfield consisting only of `bool` fields additionally gets a constant for each flag (like `Perm::READ`) and the set operations `contains`, `intersects`, `union`, and `difference`.
+//! This is only supported for integer backed bitfields, and flags whose constant would clash with another associated constant, like a `bits` flag with `BITS` or a `default` flag with `DEFAULT`, are rejected.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, flags = true, pad = auto)]
+//! struct Perm {
+//!     read: bool,
+//!     write: bool,
+//!     exec: bool,
+//! }
+//!
+//! const RW: Perm = Perm::READ.union(Perm::WRITE);
+//! assert!(RW.contains(Perm::READ));
+//! assert!(!RW.intersects(Perm::EXEC));
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, flags = true, pad = auto)]
+//! struct Status {
+//!     ready: bool,
+//!     ready_mask: bool, // error: clashes with `Status::READY_MASK` of `ready`
+//! }
+//! ```
+//!
+//! ## Bitwise Operators
+//!
+//! With `ops = true`, the macro additionally implements `BitOr`, `BitAnd`, `BitXor`, and `Not`, which operate on the raw value.
+//! `NonZero` bitfields only implement `BitOr`, as the other operators could result in zero.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, ops = true)]
+//! struct Irq {
+//!     timer: bool,
+//!     uart: bool,
+//!     #[bits(6)]
+//!     __: u8,
+//! }
+//!
+//! let enabled = Irq::new().with_timer(true) | Irq::new().with_uart(true);
+//! assert_eq!(enabled.into_bits(), 0b11);
+//! ```
+//!
+//! ## Field Arrays
+//!
+//! A field with an array type like `[u8; 8]` consists of identical elements that directly follow each other.
+//! The `bits` attribute specifies the size of an element, optionally together with the number of elements, like `#[bits(2 * 8)]`.
+//! Instead of the usual accessors, the elements are accessed with an index (`prio(i)`, `with_prio(i, value)`, and `set_prio(i, value)`), which panic if it is out of bounds.
+//! `new_with` takes an array with a value for each element, but field arrays are not supported by `Display`, the builder, and the other generated field lists.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Priorities {
+//!     #[bits(2 * 8)]
+//!     prio: [u8; 8],
+//! }
+//!
+//! let prio = Priorities::new().with_prio(0, 3).with_prio(7, 1);
+//! assert_eq!(prio.prio(7), 1);
+//! assert_eq!(prio.into_bits(), 0x4003);
+//! assert_eq!(Priorities::PRIO_LEN, 8);
+//! assert_eq!(Priorities::new_with([3, 0, 0, 0, 0, 0, 0, 1]).into_bits(), 0x4003);
+//! ```
+//!
+//! ## Views
+//!
+//! Some registers change their layout depending on a tag field, like page table entries with a `huge` bit.
+//! Each layout is its own bitfield, and `view = <pattern> => <name>: <Type>` on the tag field generates an `as_<name>()` accessor that returns the bits as `Type` if the tag matches the pattern.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u64)]
+//! struct Entry {
+//!     present: bool,
+//!     #[bits(6)]
+//!     __: u8,
+//!     #[bits(view = true => huge: HugeEntry, view = false => table: TableEntry)]
+//!     huge: bool,
+//!     #[bits(56)]
+//!     __: u64,
+//! }
+//!
+//! #[bitfield(u64)]
+//! struct HugeEntry {
+//!     #[bits(21)]
+//!     __: u32,
+//!     #[bits(43)]
+//!     frame: u64,
+//! }
+//!
+//! #[bitfield(u64)]
+//! struct TableEntry {
+//!     #[bits(12)]
+//!     __: u16,
+//!     #[bits(52)]
+//!     table: u64,
+//! }
+//!
+//! let entry = Entry::from_bits(0x4000_0081);
+//! assert_eq!(entry.as_huge().unwrap().frame(), 0x200);
+//! assert!(entry.as_table().is_none());
+//! ```
+//!
+//! ## `NonZero` Fields
+//!
+//! Fields of `NonZero` integer types store their value minus one, so that every bit pattern is valid and the zero pattern is the default value `1`.
+//! The all-ones pattern of a field with the full size of its type reads as `MAX`.
+//! For fields where the zero pattern means "absent", `Option<NonZeroU8>` (and the other sizes) maps zero to `None` instead.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use std::num::{NonZeroU8, NonZeroU16};
+//!
+//! #[bitfield(u16)]
+//! struct Slot {
+//!     #[bits(4)]
+//!     len: NonZeroU8,
+//!     #[bits(12)]
+//!     next: Option<NonZeroU16>,
+//! }
+//!
+//! let slot = Slot::new();
+//! assert_eq!((slot.len().get(), slot.next()), (1, None));
+//! let slot = slot.with_len(NonZeroU8::new(16).unwrap()).with_next(NonZeroU16::new(5));
+//! assert_eq!(slot.into_bits(), 0x005f);
+//! ```
+//!
+//! ## Sentinel Values
+//!
+//! Hardware tables often reserve a raw pattern for "invalid" or "unused" entries.
+//! With `#[bits(6, none = 0x3f)]`, an `Option<T>` field decodes this pattern to `None`, which is also its default.
+//! The other values are converted like fields of type `T`, and setting a `Some` value with the sentinel pattern is treated like an overflow.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Slot {
+//!     #[bits(6, none = 0x3f)]
+//!     owner: Option<u8>,
+//!     #[bits(2)]
+//!     kind: u8,
+//! }
+//!
+//! let slot = Slot::new();
+//! assert_eq!(slot.owner(), None);
+//! assert_eq!(slot.into_bits(), 0x3f);
+//! assert_eq!(slot.with_owner(Some(3)).owner(), Some(3));
+//! ```
+//!
+//! ## Scaled Fields
+//!
+//! Sensor registers often encode physical values with a fixed weight per step.
+//! Floating point fields with `#[bits(10, scale = 0.25, bias = -40.0)]` return `raw * scale + bias`, and the setters quantize to the nearest step.
+//! Values below the `bias` saturate to the raw zero, which is also the default.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Temperature {
+//!     #[bits(10, scale = 0.25, bias = -40.0)]
+//!     celsius: f32,
+//!     #[bits(6)]
+//!     __: u8,
+//! }
+//!
+//! let temp = Temperature::new().with_celsius(21.3);
+//! assert_eq!(temp.celsius(), 21.25);
+//! assert_eq!(temp.into_bits(), 245);
+//! ```
+//!
+//! ## Accessor Names
+//!
+//! The getter, `with_`, and `set_` functions can be renamed per field with `get`, `with`, and `set`, for example to follow an existing API.
+//! The other generated functions keep the name of the field.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8)]
+//! struct Control {
+//!     #[bits(4, get = read_kind, set = write_kind)]
+//!     kind: u8,
+//!     #[bits(get = is_enabled)]
+//!     enabled: bool,
+//!     #[bits(3)]
+//!     __: u8,
+//! }
+//!
+//! let mut ctrl = Control::new().with_enabled(true);
+//! ctrl.write_kind(5);
+//! assert_eq!(ctrl.read_kind(), 5);
+//! assert!(ctrl.is_enabled());
+//! ```
+//!
+//! ## Immutable Bitfields
+//!
+//! With `setters = false`, the bitfield has no functions that take `&mut self`, like `set_<field>`, `update_<field>`, or `modify`.
+//! Values are only changed with the `with_<field>` functions that return a modified copy.
+//! Likewise, `take_<field>` of read-to-clear fields consumes the value and returns the cleared copy together with the field, as `(Self, T)`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, setters = false)]
+//! struct Frozen {
+//!     #[bits(8)]
+//!     value: u8,
+//! }
+//!
+//! let frozen = Frozen::new().with_value(1);
+//! assert_eq!(frozen.map_value(|v| v + 1).value(), 2);
+//! ```
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u8, setters = false)]
+//! struct Frozen {
+//!     #[bits(8)]
+//!     value: u8,
+//! }
+//!
+//! let mut frozen = Frozen::new().with_value(1);
+//! frozen.set_value(2); // no setters
+//! ```
+//!
+//! ## Conditional Fields
+//!
+//! Fields with `#[cfg(...)]` attributes are only part of the layout if their predicate holds.
+//! The bitfield is generated for every combination of the predicates (up to four), so the offsets and the size check depend on the active configuration.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32)]
+//! struct Descriptor {
+//!     #[cfg(target_pointer_width = "64")]
+//!     #[bits(16)]
+//!     high: u16,
+//!     #[cfg(not(target_pointer_width = "64"))]
+//!     #[bits(16)]
+//!     __: u16,
+//!     #[bits(16)]
+//!     low: u16,
+//! }
+//!
+//! assert_eq!(Descriptor::LOW_OFFSET, 16);
+//! ```
+//!
+//! ## Invariants
+//!
+//! Constraints that span multiple fields can be given as `#[bitfield(u8, invariant = divider_set)]`, where `divider_set` is a `const fn(&Self) -> bool`.
+//! It is asserted (only in debug builds unless `overflow = panic`) by:
+//!
+//! - the `&mut self` functions, like `set_<field>`, `modify`, and `set_raw`,
+//! - the atomic functions, like `set_<field>_atomic` and `modify_atomic`,
+//! - `new_with` and the builder.
+//!
+//! The fallible `FromStr` and the `set` of the field enum return `Err` instead, and with `validate = true`, `try_from_bits` rejects violations as well.
+//! The `with_<field>` functions do not check it, so that chained calls may pass through intermediate states, and neither do `from_bits`, `store`, and `swap` of the atomic wrapper, which take whole values.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! const fn divider_set(clock: &Clock) -> bool {
+//!     !clock.enable() || clock.divider() != 0
+//! }
+//!
+//! #[bitfield(u8, invariant = divider_set)]
+//! struct Clock {
+//!     enable: bool,
+//!     #[bits(7)]
+//!     divider: u8,
+//! }
+//!
+//! let mut clock = Clock::new();
+//! clock.modify(|c| c.with_enable(true).with_divider(4));
+//! assert_eq!(clock.divider(), 4);
+//! ```
+//!
+//! ## Computed Fields
+//!
+//! Fields with `#[bits(computed = <expr>)]` have no bits of their own and only get a `const` getter, which evaluates the expression.
+//! This keeps derived values next to the layout, but they are not part of `FIELDS`, `new_with`, or the `Debug` output.
+//! As only the getter is generated, other options of the `bits` attribute are rejected.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Packet {
+//!     #[bits(4)]
+//!     hdr_len: u8,
+//!     #[bits(12)]
+//!     payload_len: u16,
+//!     /// The length in bytes, as the header length is counted in words
+//!     #[bits(computed = self.hdr_len() as u16 * 4 + self.payload_len())]
+//!     total_len: u16,
+//! }
+//!
+//! let packet = Packet::new().with_hdr_len(5).with_payload_len(100);
+//! assert_eq!(packet.total_len(), 120);
+//! ```
+//!
+//! ## `zerocopy` and Other Derives
+//!
+//! Attributes on the struct are kept, so derives like those of [`zerocopy`](https://docs.rs/zerocopy) can be added directly.
+//! The generated struct is `#[repr(transparent)]` over its integer (or array) unless `repr` adds an alignment, so it fulfills their layout requirements.
+//! `NonZero` bitfields only support `TryFromBytes`, as zero is not a valid bit pattern.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+//!
+//! #[bitfield(u32)]
+//! #[derive(FromBytes, IntoBytes, KnownLayout, Immutable)]
+//! struct Descriptor {
+//!     #[bits(16)]
+//!     len: u16,
+//!     #[bits(16)]
+//!     flags: u16,
+//! }
+//!
+//! let dma_buffer: [u32; 2] = [0x0010_0008, 0x0003_0040];
+//! let descriptors = <[Descriptor]>::ref_from_bytes(dma_buffer.as_bytes()).unwrap();
+//! assert_eq!(descriptors.len(), 2);
+//! assert_eq!(descriptors[0].len(), 8);
+//! assert_eq!(descriptors[1].flags(), 3);
+//! assert_eq!(descriptors.as_bytes(), dma_buffer.as_bytes());
+//! ```
+//!
+//! ## Kani Proofs
+//!
+//! With `kani = true`, the macro implements `kani::Arbitrary` for the bitfield and generates a [Kani](https://model-checking.github.io/kani/) proof harness named `kani_<struct>_round_trip`, both only under `#[cfg(kani)]`.
+//! For every readable and writable field, the harness proves that setting a value that survives the round trip through the field type stores exactly its bits, and that the bits of all other fields remain unchanged.
+//! `NonZero` bitfields only get the `Arbitrary` implementation, as their setters may panic.
+//!
+//! ```ignore
+//! #[bitfield(u32, kani = true)]
+//! struct Control {
+//!     #[bits(4)]
+//!     kind: u8,
+//!     #[bits(28)]
+//!     addr: u32,
+//! }
+//! // cargo kani --harness kani_control_round_trip
+//! ```
+//!
+//! ## Register Blocks
+//!
+//! The `register_block` attribute creates a wrapper for memory-mapped registers.
+//! Every field is a register with an `#[offset(...)]` in bytes from the base address.
+//! The registers are accessed with volatile `read_<register>`, `write_<register>`, and `modify_<register>` functions.
+//! Like `modify` of the bitfield, `modify_<register>` passes the read value through `without_w1c`, so that write-1-to-clear fields are only cleared if the closure sets them.
+//! Misaligned or overlapping registers are rejected at compile time.
+//!
+//! ```rust
+//! # use bitfield_struct::{bitfield, register_block};
+//! #[bitfield(u32)]
+//! struct Control {
+//!     enable: bool,
+//!     #[bits(31)]
+//!     divider: u32,
+//! }
+//!
+//! #[register_block]
+//! struct Timer {
+//!     #[offset(0x00)]
+//!     control: Control,
+//!     #[offset(0x04)]
+//!     counter: u32,
+//! }
+//!
+//! let mut memory = [0u32; 2];
+//! // Safety: the registers are backed by `memory`
+//! let timer = unsafe { Timer::new(memory.as_mut_ptr().cast()) };
+//! timer.write_control(Control::new().with_divider(8));
+//! timer.modify_control(|c| c.with_enable(true));
+//! assert!(timer.read_control().enable());
+//! assert_eq!(Timer::COUNTER_OFFSET, 4);
+//! ```
+//!
+//! ## svd2rust-Style API
+//!
+//! With `svd = true`, the bitfield gets the closure-based `read`, `write`, and `modify` functions known from [svd2rust](https://docs.rs/svd2rust) peripheral access crates.
+//! They use the generated `<Name>R` and `<Name>W` types, whose field functions return readers and writers with `bits`, `variant`, and for flags `bit`, `bit_is_set`, `bit_is_clear`, `set_bit`, and `clear_bit`.
+//! `write` starts from the default values and `modify` from the current ones; this `modify` replaces the usual one.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32, svd = true)]
+//! struct Control {
+//!     enable: bool,
+//!     #[bits(31)]
+//!     divider: u32,
+//! }
+//!
+//! let mut control = Control::new();
+//! control.write(|w| w.divider().bits(4));
+//! control.modify(|r, w| w.enable().bit(r.divider().bits() > 2));
+//! assert!(control.read().enable().bit_is_set());
+//! ```
+//!
+//! ## Derive Form
+//!
+//! `#[derive(Bitfield)]` with a following `#[bitfield(...)]` attribute is an alternative to the attribute macro that leaves the struct untouched, so it works together with other attribute macros.
+//! It generates the bitfield as `Packed<Name>`, which is converted from and into the struct with `From`.
+//! Padding fields are prefixed with `_` and zeroed when unpacking, and only read-write fields are supported.
+//! If the `bitfield` attribute macro is imported as well, the argument attribute has to follow the derive, as the name is ambiguous otherwise.
+//! Alternatively, the arguments can be given with `#[bitfield_args(...)]`, which cannot be confused with the attribute macro.
+//!
+//! ```
+//! use bitfield_struct::Bitfield;
+//!
+//! #[derive(Bitfield, Clone, Copy)]
+//! #[bitfield(u8)]
+//! struct Entry {
+//!     #[bits(7)]
+//!     address: u8,
+//!     valid: bool,
+//! }
+//!
+//! let packed = PackedEntry::from(Entry { address: 3, valid: true });
+//! assert_eq!(packed.into_bits(), 0x83);
+//! assert_eq!(Entry::from(packed).address, 3);
+//! ```
+//!
+//! ## Tuple Structs
+//!
+//! Bitfields can also be tuple structs, whose fields get positional accessors like `get_0`, `with_0`, and `set_0`.
+//! The other generated items use the names `f0`, `f1`, and so on, for example `F0_BITS` or the `Debug` output, and fields of type `()` are padding.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16)]
+//! struct Flags(#[bits(3)] u8, bool, #[bits(12)] ());
+//!
+//! let flags = Flags::new().with_0(5).with_1(true);
+//! assert_eq!(flags.get_0(), 5);
+//! assert_eq!(format!("{flags:?}"), "Flags { f0: 5, f1: true }");
+//! ```
+//!
+//! ## Representation
+//!
+//! The generated struct is `#[repr(transparent)]` by default.
+//! The `repr` argument replaces it, like `repr = C` for types shared with C code or `repr = (C, align(64))` for a larger alignment.
+//! As an alignment larger than the raw type adds padding, it cannot be combined with `slices` or `bytemuck`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32, repr = (C, align(64)))]
+//! struct Descriptor {
+//!     #[bits(31)]
+//!     address: u32,
+//!     valid: bool,
+//! }
+//!
+//! assert_eq!(core::mem::align_of::<Descriptor>(), 64);
+//! ```
+//!
+//! ## Move Semantics
+//!
+//! The generated struct derives `Copy` and `Clone`.
+//! With `copy = false`, it only derives `Clone`, so that moving a value can model ownership, like a one-shot token that has to be consumed exactly once.
+//!
+//! ```compile_fail
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u32, copy = false)]
+//! struct Doorbell {
+//!     #[bits(32)]
+//!     tail: u32,
+//! }
+//!
+//! fn ring(doorbell: Doorbell) {}
+//!
+//! let doorbell = Doorbell::new().with_tail(1);
+//! ring(doorbell);
+//! ring(doorbell); // already consumed
+//! ```
+//!
+//! ## Layout Tables
+//!
+//! With `doc_table = true`, the macro appends a Markdown table of the fields with their bits, access mode, and default to the documentation of the struct.
+//! So the register map in the rendered documentation is always in sync with the code.
+//!
+//! ## `char` Fields
+//!
+//! `char` fields store the Unicode scalar value and take 21 bits by default.
+//! Fewer bits restrict the characters, like `#[bits(7)]` to ASCII, and characters that do not fit are handled like any other overflow.
+//! Bits that are no valid scalar value, like surrogates, are read as `char::REPLACEMENT_CHARACTER`, and `try_<field>` (with `try_getters = true`) returns them as `Err`.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! #[bitfield(u16, try_setters = true)]
+//! struct Tag {
+//!     #[bits(7)]
+//!     first: char,
+//!     #[bits(7)]
+//!     second: char,
+//!     #[bits(2)]
+//!     __: u8,
+//! }
+//!
+//! let tag = Tag::new().with_first('O').with_second('K');
+//! assert_eq!((tag.first(), tag.second()), ('O', 'K'));
+//! assert!(Tag::new().try_with_first('é').is_err());
+//! ```
+//!
+//! ## Arbitrary Width Integers
+//!
+//! Fields can use the arbitrary width integers of the [`arbitrary-int`](https://docs.rs/arbitrary-int) crate, like `u4` or `u12`.
+//! Their width is inferred from the type name, and the values are converted with the `const` functions `new` and `value`, so the types guarantee the range of the fields.
+//! A smaller `#[bits(N)]` restricts them further, like for other types.
+//! As the macro only sees the name, it assumes this for every type named `u<N>`; types with such names from other crates are treated like any other custom type once they have their own `from` and `into` conversions.
+//! The integers of the `ux` crate are not supported, as they cannot be converted back in `const` functions.
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! use arbitrary_int::{u4, u12};
+//!
+//! #[bitfield(u16)]
+//! struct Entry {
+//!     kind: u4,
+//!     addr: u12,
+//! }
+//!
+//! let entry = Entry::new().with_kind(u4::new(9)).with_addr(u12::new(0xabc));
+//! assert_eq!(entry.kind(), u4::new(9));
+//! assert_eq!(entry.into_bits(), 0xabc9);
+//! ```
+//!
+//! ## Frames
+//!
+//! Protocol messages that are sequences of bitfields can be declared with `#[frame]`.
+//! It concatenates the bytes of the fields in declaration order, big endian by default or with `#[frame(endian = little)]`.
+//! Fields of type `[u8; N]` are fixed gaps that are copied as they are.
+//! The frame gets the byte offsets of its fields as `<FIELD>_OFFSET`, the total `SIZE`, `to_bytes`, and the inverse `from_bytes`.
+//!
+//! ```
+//! # use bitfield_struct::{bitfield, frame};
+//! #[bitfield(u16)]
+//! struct Header {
+//!     #[bits(4)]
+//!     version: u8,
+//!     #[bits(12)]
+//!     len: u16,
+//! }
+//!
+//! #[frame]
+//! struct Message {
+//!     header: Header,
+//!     reserved: [u8; 2],
+//!     crc: Header,
+//! }
+//!
+//! assert_eq!(Message::CRC_OFFSET, 4);
+//! let message = Message {
+//!     header: Header::new().with_version(1).with_len(4),
+//!     reserved: [0; 2],
+//!     crc: Header::from_bits(0xabcd),
+//! };
+//! let bytes: [u8; Message::SIZE] = message.to_bytes();
+//! assert_eq!(bytes, [0x00, 0x41, 0, 0, 0xab, 0xcd]);
+//! assert_eq!(Message::from_bytes(bytes).header.len(), 4);
+//! ```
+//!
+//! ## Limitations
+//!
+//! Bit widths have to be integer literals, like `#[bits(12)]`, and cannot be constant expressions like `#[bits(ADDR_BITS)]`.
+//! The macro computes the offsets, masks, and types of the accessors while it is expanded, before the compiler evaluates any constants.
+//! Computing them with constant expressions instead would turn the overlap and size errors at the offending field into const assertions without a precise location, so this is not supported.
+//! Widths shared with other code can be checked against the generated constants instead, so they cannot drift apart:
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! const ADDR_BITS: usize = 12;
+//!
+//! #[bitfield(u16)]
+//! struct Entry {
+//!     #[bits(12)]
+//!     addr: u16,
+//!     #[bits(4)]
+//!     kind: u8,
+//! }
+//! const _: () = assert!(Entry::ADDR_BITS == ADDR_BITS);
+//! ```
+//!
+//! For the same reason, the widths of custom field types are not inferred from their `BITS` constant, which `#[bits(N, checked)]` compares to the width instead.
+//! There is also no public trait for custom field types, as a `proc-macro` crate can only export macros, and trait methods could not be called from the `const` accessors.
+//! Instead, the types provide `const fn into_bits(self)` and `const fn from_bits(bits)` by convention, like the generated bitfields and the `FromBits` derive.
+//!
+//! Bitfields cannot be generic either, as their `const` accessors cannot call the trait methods that would convert generic field types.
+//! Variants for different address widths can be generated with a `macro_rules!` wrapper instead:
+//!
+//! ```
+//! # use bitfield_struct::bitfield;
+//! macro_rules! entry {
+//!     ($name:ident, $bits:literal) => {
+//!         #[bitfield(u64, pad = auto)]
+//!         struct $name {
+//!             present: bool,
+//!             #[bits($bits)]
+//!             addr: u64,
+//!         }
+//!     };
+//! }
+//! entry!(Entry32, 32);
+//! entry!(Entry48, 48);
+//!
+//! assert_eq!(Entry32::ADDR_BITS, 32);
+//! assert_eq!(Entry48::new().with_addr(1 << 40).addr(), 1 << 40);
+//! ```
+//!
+
+#![warn(clippy::unwrap_used)]
+
+use proc_macro as pc;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use std::ops::Range;
+use std::stringify;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::Token;
+
+/// Creates a bitfield for this struct.
+///
+/// The arguments first, have to begin with the underlying type of the bitfield:
+/// For example: `#[bitfield(u64)]`.
+///
+/// It can contain an extra `debug` argument for disabling the `Debug` trait
+/// generation (`#[bitfield(u64, debug = false)]`).
+#[proc_macro_attribute]
+pub fn bitfield(args: pc::TokenStream, input: pc::TokenStream) -> pc::TokenStream {
+    match bitfield_cfg(args.into(), input.into()) {
+        Ok(result) => result.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Generates the `const` `into_bits`/`from_bits` functions for a fieldless enum.
+///
+/// The bits are converted into the type of the `#[repr]` attribute, or the
+/// smallest unsigned integer that holds every discriminant.
+/// Unknown bit patterns are mapped to the variant marked with `#[fallback]`,
+/// or panic if there is none.
+/// `try_from_bits` returns `None` for them instead.
+#[proc_macro_derive(FromBits, attributes(fallback))]
+pub fn from_bits(input: pc::TokenStream) -> pc::TokenStream {
+    match from_bits_inner(input.into()) {
+        Ok(result) => result.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Creates a bitfield named `Packed<Name>` for this struct, which stays a normal struct.
+///
+/// The arguments of the bitfield are given with a `#[bitfield(u64)]` attribute after the derive,
+/// or with `#[bitfield_args(u64)]`, which cannot be confused with the attribute macro.
+/// Unlike the attribute macro, it does not rewrite the struct, which is converted from and into the bitfield with `From`.
+#[proc_macro_derive(Bitfield, attributes(bitfield, bitfield_args, bits))]
+pub fn derive_bitfield(input: pc::TokenStream) -> pc::TokenStream {
+    match derive_bitfield_inner(input.into()) {
+        Ok(result) => result.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn derive_bitfield_inner(input: TokenStream) -> syn::Result<TokenStream> {
+    use syn::ext::IdentExt;
+
+    let input = syn::parse2::<syn::ItemStruct>(input)?;
+    let name = &input.ident;
+    let packed = format_ident!("Packed{name}");
+    let mut args = input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("bitfield") || a.path().is_ident("bitfield_args"));
+    let Some(first) = args.next() else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "the bitfield arguments are missing, like `#[bitfield(u32)]`",
+        ));
+    };
+    if let Some(second) = args.next() {
+        return Err(syn::Error::new(
+            second.span(),
+            "the bitfield arguments are given more than once",
+        ));
+    }
+    let args = first;
+    let args = args.meta.require_list()?.tokens.clone();
+    let syn::Fields::Named(fields) = &input.fields else {
+        return Err(syn::Error::new(
+            input.fields.span(),
+            "only named fields are supported",
+        ));
+    };
+
+    let mut pack = Vec::new();
+    let mut unpack = Vec::new();
+    for field in &fields.named {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+        let cfgs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("cfg"))
+            .collect();
+        if ident.to_string().starts_with('_') {
+            unpack.push(quote!(#( #cfgs )* #ident: Default::default()));
+            continue;
+        }
+
+        let mut get = ident.clone();
+        let mut with = format_ident!("with_{}", ident.unraw());
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("bits")) {
+            // Malformed attributes are reported when the field is parsed
+            let Ok(bits) = attr.parse_args::<BitsAttr>() else {
+                continue;
+            };
+            if bits.computed.is_some()
+                || bits.overlaps
+                || bits.access.is_some_and(|a| a != Access::ReadWrite)
+            {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "derived bitfields only support read-write fields",
+                ));
+            }
+            get = bits.get.unwrap_or(get);
+            with = bits.with.unwrap_or(with);
+        }
+
+        if let syn::Type::Array(array) = &field.ty {
+            let len = &array.len;
+            pack.push(quote! {
+                #( #cfgs )*
+                for i in 0..#len {
+                    this = this.#with(i, v.#ident[i]);
+                }
+            });
+            unpack.push(quote!(#( #cfgs )* #ident: core::array::from_fn(|i| v.#get(i))));
+        } else {
+            pack.push(quote!(#( #cfgs )* { this = this.#with(v.#ident); }));
+            unpack.push(quote!(#( #cfgs )* #ident: v.#get()));
+        }
+    }
+
+    // The bitfield only keeps the documentation of the struct
+    let mut bitfield = input.clone();
+    bitfield.ident = packed.clone();
+    bitfield
+        .attrs
+        .retain(|a| a.path().is_ident("doc") || a.path().is_ident("cfg"));
+    let bitfield = bitfield_cfg(args, bitfield.into_token_stream())?;
+
+    Ok(quote! {
+        #bitfield
+
+        impl From<#name> for #packed {
+            fn from(v: #name) -> Self {
+                let mut this = Self::DEFAULT;
+                #( #pack )*
+                this
+            }
+        }
+
+        impl From<#packed> for #name {
+            fn from(v: #packed) -> Self {
+                Self {
+                    #( #unpack, )*
+                }
+            }
+        }
+    })
+}
+
+fn from_bits_inner(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let name = &input.ident;
+    let vis = &input.vis;
+    let syn::Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "FromBits only supports enums",
+        ));
+    };
+
+    let mut repr = infer_repr(data);
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if let Some(ident) = meta.path.get_ident() {
+                    if type_bits(&syn::parse_quote!(#ident)).0 != TypeClass::Other {
+                        repr = syn::parse_quote!(#ident);
+                    }
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    let mut fallback = None;
+    let mut variants = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        if !variant.fields.is_empty() {
+            return Err(syn::Error::new(
+                variant.span(),
+                "FromBits only supports fieldless enums",
+            ));
+        }
+        for attr in &variant.attrs {
+            if attr.path().is_ident("fallback") {
+                if fallback.is_some() {
+                    return Err(syn::Error::new(attr.span(), "only one fallback is allowed"));
+                }
+                fallback = Some(&variant.ident);
+            }
+        }
+        variants.push(&variant.ident);
+    }
+
+    // The bits required to represent every discriminant
+    let width = if type_bits(&repr).0 == TypeClass::SInt {
+        quote!(#repr::BITS - (bits ^ (bits >> (#repr::BITS - 1))).leading_zeros() + 1)
+    } else {
+        quote!(#repr::BITS - bits.leading_zeros())
+    };
+
+    let fallback = if let Some(fallback) = fallback {
+        quote!(Self::#fallback)
+    } else {
+        let message = format!("invalid bits for {name}");
+        quote!(panic!(#message))
+    };
+
+    Ok(quote! {
+        impl #name {
+            /// The number of bits required to store every variant.
+            #vis const BITS: usize = {
+                let mut max = 0;
+                #(
+                    let bits = Self::#variants as #repr;
+                    let width = #width;
+                    if width > max {
+                        max = width;
+                    }
+                )*
+                max as usize
+            };
+            /// Converts the variant into its bits.
+            #vis const fn into_bits(self) -> #repr {
+                self as #repr
+            }
+            /// Converts the bits into the matching variant.
+            #vis const fn from_bits(bits: #repr) -> Self {
+                match Self::try_from_bits(bits) {
+                    Some(this) => this,
+                    None => #fallback,
+                }
+            }
+            /// Converts the bits into the matching variant, or `None` if there is none.
+            #vis const fn try_from_bits(bits: #repr) -> Option<Self> {
+                match bits {
+                    #( bits if bits == Self::#variants as #repr => Some(Self::#variants), )*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+
+/// The smallest unsigned integer that holds every discriminant, or `isize` if
+/// they are not all non-negative literals.
+fn infer_repr(data: &syn::DataEnum) -> syn::Type {
+    let mut next = Some(0u128);
+    let mut max = 0;
+    for variant in &data.variants {
+        let value = match &variant.discriminant {
+            Some((
+                _,
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit),
+                    ..
+                }),
+            )) => lit.base10_parse::<u128>().ok(),
+            Some(_) => None,
+            None => next,
+        };
+        let Some(value) = value else {
+            return syn::parse_quote!(isize);
+        };
+        max = max.max(value);
+        next = value.checked_add(1);
+    }
+    let bits = (u128::BITS - max.leading_zeros())
+        .max(8)
+        .next_power_of_two();
+    syn::Type::Verbatim(format_ident!("u{bits}").into_token_stream())
+}
+
+/// Creates a memory-mapped register block for this struct.
+///
+/// The fields are the bitfield types of the registers, each with an
+/// `#[offset(0x04)]` attribute for its byte offset from the base address.
+/// The struct then holds the base address and gets volatile `read_<register>`,
+/// `write_<register>`, and `modify_<register>` functions.
+#[proc_macro_attribute]
+pub fn register_block(args: pc::TokenStream, input: pc::TokenStream) -> pc::TokenStream {
+    match register_block_inner(args.into(), input.into()) {
+        Ok(result) => result.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn register_block_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(syn::Error::new(
+            args.span(),
+            "register_block has no arguments",
+        ));
+    }
+    let input = syn::parse2::<syn::ItemStruct>(input)?;
+    let name = &input.ident;
+    let vis = &input.vis;
+    let attrs = &input.attrs;
+    let syn::Fields::Named(fields) = &input.fields else {
+        return Err(syn::Error::new(
+            input.fields.span(),
+            "only named fields are supported",
+        ));
+    };
+
+    let mut registers = Vec::with_capacity(fields.named.len());
+    for field in &fields.named {
+        let mut offset = None;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("offset")) {
+            let lit = attr.parse_args::<syn::LitInt>()?;
+            offset = Some(lit.base10_parse::<usize>()?);
+        }
+        let Some(offset) = offset else {
+            return Err(syn::Error::new(
+                field.span(),
+                "registers require an #[offset(...)] attribute",
+            ));
+        };
+        registers.push((field, offset));
+    }
+    registers.sort_by_key(|(_, offset)| *offset);
+
+    // Registers have to be aligned and must not overlap
+    let mut checks = Vec::new();
+    for (field, offset) in &registers {
+        let ty = &field.ty;
+        let message = format!("register at {offset:#x} is not aligned");
+        checks.push(quote!(assert!(#offset % core::mem::align_of::<#ty>() == 0, #message);));
+    }
+    for pair in registers.windows(2) {
+        let [(first, offset), (_, next)] = pair else {
+            continue;
+        };
+        let ty = &first.ty;
+        let message = format!("registers at {offset:#x} and {next:#x} overlap");
+        checks.push(quote!(assert!(#offset + core::mem::size_of::<#ty>() <= #next, #message);));
+    }
+
+    let accessors = registers.iter().map(|(field, offset)| {
+        let syn::Field { ident, ty, vis, .. } = field;
+        let doc = field
+            .attrs
+            .iter()
+            .filter(|a| !a.path().is_ident("offset"))
+            .collect::<Vec<_>>();
+        let ident = ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
+        let read = format_ident!("read_{ident}");
+        let write = format_ident!("write_{ident}");
+        let modify = format_ident!("modify_{ident}");
+        let offset_ident = format_ident!("{}_OFFSET", ident.to_uppercase());
+        quote! {
+            /// The byte offset of the register.
+            #vis const #offset_ident: usize = #offset;
+
+            #( #doc )*
+            #vis fn #read(&self) -> #ty {
+                // SAFETY: the caller of `new` guarantees that the register is valid
+                unsafe { core::ptr::read_volatile(self.0.add(#offset).cast::<#ty>()) }
+            }
+            #( #doc )*
+            #vis fn #write(&self, value: #ty) {
+                // SAFETY: the caller of `new` guarantees that the register is valid
+                unsafe { core::ptr::write_volatile(self.0.add(#offset).cast::<#ty>(), value) }
+            }
+            #( #doc )*
+            #vis fn #modify(&self, f: impl FnOnce(#ty) -> #ty) {
+                // Bitfields with write-1-to-clear fields have an inherent `without_w1c`,
+                // which takes precedence over this fallback for all other types
+                #[allow(dead_code)]
+                trait WriteBack: Sized {
+                    fn without_w1c(self) -> Self {
+                        self
+                    }
+                }
+                impl<T> WriteBack for T {}
+                self.#write(f(self.#read().without_w1c()));
+            }
+        }
+    });
+
+    Ok(quote! {
+        #( #attrs )*
+        #vis struct #name(*mut u8);
+
+        const _: () = {
+            #( #checks )*
+        };
+
+        impl #name {
+            /// Creates the register block at the given base address.
+            ///
+            /// # Safety
+            ///
+            /// `base` has to point to the registers of this block,
+            /// which have to be valid for volatile reads and writes while the block is used.
+            #vis const unsafe fn new(base: *mut u8) -> Self {
+                Self(base)
+            }
+
+            /// Returns the base address.
+            #vis const fn base(&self) -> *mut u8 {
+                self.0
+            }
+
+            #( #accessors )*
+        }
+    })
+}
+
+/// Generates the svd2rust-style `read`, `write`, and `modify` functions
+/// with their reader and writer types.
+#[allow(clippy::too_many_arguments)]
+fn svd_api(
+    name: &Ident,
+    vis: &syn::Visibility,
+    bits_ty: &syn::Type,
+    members: &[Member],
+    defaults: &TokenStream,
+    setters: bool,
+    modify_check: &Option<TokenStream>,
+    write_back: &TokenStream,
+) -> TokenStream {
+    let reader = format_ident!("{name}R");
+    let writer = format_ident!("{name}W");
+    let field_reader = format_ident!("{name}FieldReader");
+    let field_writer = format_ident!("{name}FieldWriter");
+    let fields = || {
+        members
+            .iter()
+            .filter(|m| !m.overlaps)
+            .filter_map(|m| m.inner.as_ref())
+    };
+    let reads = fields().filter(|f| f.access.read()).map(|f| {
+        let MemberInner {
+            get_ident, ty, vis, ..
+        } = f;
+        let doc = f.attrs.iter().filter(|a| a.path().is_ident("doc"));
+        quote! {
+            #( #doc )*
+            #vis fn #get_ident(&self) -> #field_reader<#ty> {
+                #field_reader(self.0.#get_ident())
+            }
+        }
+    });
+    let writes = fields().filter(|f| f.access.write()).map(|f| {
+        let MemberInner {
+            get_ident,
+            with_ident,
+            ty,
+            vis,
+            ..
+        } = f;
+        let doc = f.attrs.iter().filter(|a| a.path().is_ident("doc"));
+        quote! {
+            #( #doc )*
+            #vis fn #get_ident(&mut self) -> #field_writer<'_, #ty> {
+                #field_writer {
+                    w: self,
+                    with: #name::#with_ident,
+                }
+            }
+        }
+    });
+
+    let reader_doc = format!("The reader of [`{name}`], returned by [`{name}::read`].");
+    let read = quote! {
+        #[doc = #reader_doc]
+        #vis struct #reader(#name);
+
+        impl #reader {
+            /// Returns the raw bits.
+            #vis const fn bits(&self) -> #bits_ty {
+                #name(self.0 .0).into_bits()
+            }
+            #( #reads )*
+        }
+
+        /// The value of a single field.
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #vis struct #field_reader<T>(T);
+
+        impl<T: Copy> #field_reader<T> {
+            /// Returns the value of the field.
+            #vis fn bits(&self) -> T {
+                self.0
+            }
+            /// Returns the value of the field.
+            #vis fn variant(&self) -> T {
+                self.0
+            }
+        }
+
+        impl #field_reader<bool> {
+            /// Returns the value of the flag.
+            #vis fn bit(&self) -> bool {
+                self.0
+            }
+            /// Returns if the flag is set.
+            #vis fn bit_is_set(&self) -> bool {
+                self.0
+            }
+            /// Returns if the flag is cleared.
+            #vis fn bit_is_clear(&self) -> bool {
+                !self.0
+            }
+        }
+
+        impl #name {
+            /// Returns the reader of the svd2rust-style API.
+            #vis fn read(&self) -> #reader {
+                #reader(Self(self.0))
+            }
+        }
+    };
+    if !setters {
+        return read;
+    }
+
+    let writer_doc =
+        format!("The writer of [`{name}`], used by [`{name}::write`] and [`{name}::modify`].");
+    quote! {
+        #read
+
+        #[doc = #writer_doc]
+        #vis struct #writer(#name);
+
+        impl #writer {
+            /// Replaces the raw bits.
+            #vis fn bits(&mut self, bits: #bits_ty) -> &mut Self {
+                self.0 = #name::from_bits(bits);
+                self
+            }
+            #( #writes )*
+        }
+
+        /// Writes a single field.
+        #vis struct #field_writer<'a, T> {
+            w: &'a mut #writer,
+            with: fn(#name, T) -> #name,
+        }
+
+        impl<'a, T> #field_writer<'a, T> {
+            /// Sets the value of the field.
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn bits(self, value: T) -> &'a mut #writer {
+                self.w.0 = (self.with)(#name(self.w.0 .0), value);
+                self.w
+            }
+            /// Sets the value of the field.
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn variant(self, value: T) -> &'a mut #writer {
+                self.bits(value)
+            }
+        }
+
+        impl<'a> #field_writer<'a, bool> {
+            /// Sets the value of the flag.
+            #vis fn bit(self, value: bool) -> &'a mut #writer {
+                self.bits(value)
+            }
+            /// Sets the flag.
+            #vis fn set_bit(self) -> &'a mut #writer {
+                self.bits(true)
+            }
+            /// Clears the flag.
+            #vis fn clear_bit(self) -> &'a mut #writer {
+                self.bits(false)
+            }
+        }
+
+        impl #name {
+            /// Replaces the bitfield with the default values changed by the writer in `f`.
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn write(&mut self, f: impl FnOnce(&mut #writer) -> &mut #writer) {
+                let mut w = #writer(Self::from_bits(#defaults));
+                f(&mut w);
+                *self = w.0;
+                #modify_check
+            }
+            /// Changes the bitfield with the writer in `f`, which also gets a reader of the previous value.
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn modify(
+                &mut self,
+                f: impl for<'w> FnOnce(&#reader, &'w mut #writer) -> &'w mut #writer,
+            ) {
+                let mut w = #writer(#write_back);
+                f(&#reader(Self(self.0)), &mut w);
+                *self = w.0;
+                #modify_check
+            }
+        }
+    }
+}
+
+/// Generates the bitfield once for every combination of the `#[cfg]` attributes on its fields,
+/// so that each configuration has its own layout.
+fn bitfield_cfg(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let item = syn::parse2::<syn::ItemStruct>(input.clone())?;
+    // The distinct predicates, compared by their tokens
+    let mut predicates = Vec::<TokenStream>::new();
+    let mut index = |attr: &syn::Attribute| -> syn::Result<Option<usize>> {
+        if !attr.path().is_ident("cfg") {
+            return Ok(None);
+        }
+        let tokens = &attr.meta.require_list()?.tokens;
+        let key = tokens.to_string();
+        Ok(Some(
+            match predicates.iter().position(|p| p.to_string() == key) {
+                Some(index) => index,
+                None => {
+                    predicates.push(tokens.clone());
+                    predicates.len() - 1
+                }
+            },
+        ))
+    };
+    // The predicates each field depends on
+    let mut field_predicates = Vec::new();
+    for field in &item.fields {
+        let mut indices = Vec::new();
+        for attr in &field.attrs {
+            indices.extend(index(attr)?);
+        }
+        field_predicates.push(indices);
+    }
+    if predicates.is_empty() {
+        return bitfield_inner(args, input);
+    }
+    if predicates.len() > 4 {
+        return Err(syn::Error::new(
+            item.ident.span(),
+            "bitfields are limited to 4 different #[cfg] predicates",
+        ));
+    }
+
+    let mut output = TokenStream::new();
+    for combination in 0..1usize << predicates.len() {
+        let enabled = |index: &usize| combination & (1 << index) != 0;
+        let mut variant = item.clone();
+        if let syn::Fields::Named(fields) = &mut variant.fields {
+            fields.named = (fields.named.iter().zip(&field_predicates))
+                .filter(|(_, indices)| indices.iter().all(enabled))
+                .map(|(field, _)| {
+                    let mut field = field.clone();
+                    field.attrs.retain(|attr| !attr.path().is_ident("cfg"));
+                    field
+                })
+                .collect();
+        }
+        let condition = predicates.iter().enumerate().map(|(index, predicate)| {
+            if enabled(&index) {
+                predicate.clone()
+            } else {
+                quote!(not(#predicate))
+            }
+        });
+        let cfg: syn::Attribute = syn::parse_quote!(#[cfg(all( #( #condition ),* ))]);
+
+        // Errors, like a mismatching size, only apply to their configuration
+        let tokens = bitfield_inner(args.clone(), variant.to_token_stream())
+            .unwrap_or_else(syn::Error::into_compile_error);
+        for mut item in syn::parse2::<syn::File>(tokens)?.items {
+            if let Some(attrs) = item_attrs(&mut item) {
+                attrs.insert(0, cfg.clone());
+            }
+            item.to_tokens(&mut output);
+        }
+    }
+    Ok(output)
+}
+
+/// The attributes of an item.
+fn item_attrs(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    use syn::Item;
+    match item {
+        Item::Const(item) => Some(&mut item.attrs),
+        Item::Enum(item) => Some(&mut item.attrs),
+        Item::ExternCrate(item) => Some(&mut item.attrs),
+        Item::Fn(item) => Some(&mut item.attrs),
+        Item::ForeignMod(item) => Some(&mut item.attrs),
+        Item::Impl(item) => Some(&mut item.attrs),
+        Item::Macro(item) => Some(&mut item.attrs),
+        Item::Mod(item) => Some(&mut item.attrs),
+        Item::Static(item) => Some(&mut item.attrs),
+        Item::Struct(item) => Some(&mut item.attrs),
+        Item::Trait(item) => Some(&mut item.attrs),
+        Item::TraitAlias(item) => Some(&mut item.attrs),
+        Item::Type(item) => Some(&mut item.attrs),
+        Item::Union(item) => Some(&mut item.attrs),
+        Item::Use(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+/// Creates a frame that concatenates bitfields into a byte buffer.
+///
+/// The fields are stored back-to-back in declaration order, each bitfield with its
+/// `to_be_bytes` (or `to_le_bytes` with `endian = little`) and `[u8; N]` fields as fixed gaps.
+/// The struct then gets `<FIELD>_OFFSET` and `SIZE` constants, `to_bytes`, and the inverse `from_bytes`.
+#[proc_macro_attribute]
+pub fn frame(args: pc::TokenStream, input: pc::TokenStream) -> pc::TokenStream {
+    match frame_inner(args.into(), input.into()) {
+        Ok(result) => result.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn frame_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let endian = if args.is_empty() {
+        "be"
+    } else {
+        let args = syn::parse2::<syn::MetaNameValue>(args)?;
+        let value = match &args.value {
+            syn::Expr::Path(path) => path.path.get_ident(),
+            _ => None,
+        };
+        match value.map(Ident::to_string).as_deref() {
+            Some("big") if args.path.is_ident("endian") => "be",
+            Some("little") if args.path.is_ident("endian") => "le",
+            _ => {
+                return Err(syn::Error::new(
+                    args.span(),
+                    "expected `endian = big` or `endian = little`",
+                ))
+            }
+        }
+    };
+    let to_bytes = format_ident!("to_{endian}_bytes");
+    let from_bytes = format_ident!("from_{endian}_bytes");
+
+    let input = syn::parse2::<syn::ItemStruct>(input)?;
+    let name = &input.ident;
+    let vis = &input.vis;
+    let syn::Fields::Named(fields) = &input.fields else {
+        return Err(syn::Error::new(
+            input.fields.span(),
+            "only named fields are supported",
+        ));
+    };
+
+    let mut checks = Vec::new();
+    let mut offsets = Vec::with_capacity(fields.named.len());
+    let mut writes = Vec::with_capacity(fields.named.len());
+    let mut reads = Vec::with_capacity(fields.named.len());
+    // The offset of the next field, starting with zero
+    let mut next = quote!(0);
+    for field in &fields.named {
+        let syn::Field { ident, ty, vis, .. } = field;
+        let ident_str = ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
+        let offset = format_ident!("{}_OFFSET", ident_str.to_uppercase());
+        // Byte arrays are gaps that are copied as they are
+        let (size, write, read) = match ty {
+            syn::Type::Array(array) if type_bits(&array.elem) == (TypeClass::UInt, 8) => {
+                let len = &array.len;
+                (quote!(#len), quote!(&self.#ident), quote!(raw))
+            }
+            _ => {
+                let message = format!("{name}::{ident_str}: frames require whole bytes");
+                checks.push(quote!(assert!(<#ty>::BITS % 8 == 0, #message);));
+                (
+                    quote!(<#ty>::BITS / 8),
+                    quote!(&self.#ident.#to_bytes()),
+                    quote!(<#ty>::#from_bytes(raw)),
+                )
+            }
+        };
+        offsets.push(quote! {
+            /// The byte offset of the field.
+            #vis const #offset: usize = #next;
+        });
+        writes.push(quote! {
+            bytes[Self::#offset..Self::#offset + #size].copy_from_slice(#write);
+        });
+        reads.push(quote! {
+            #ident: {
+                let mut raw = [0; #size];
+                raw.copy_from_slice(&bytes[Self::#offset..Self::#offset + #size]);
+                #read
+            }
+        });
+        next = quote!(Self::#offset + #size);
+    }
+
+    Ok(quote! {
+        #input
+
+        const _: () = {
+            #( #checks )*
+        };
+
+        impl #name {
+            #( #offsets )*
+            /// The number of bytes of the frame.
+            #vis const SIZE: usize = #next;
+
+            /// Concatenates the bytes of the fields.
+            #vis fn to_bytes(self) -> [u8; Self::SIZE] {
+                let mut bytes = [0; Self::SIZE];
+                #( #writes )*
+                bytes
+            }
+
+            /// Splits the bytes into the fields, the inverse of `to_bytes`.
+            #vis fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+                Self {
+                    #( #reads, )*
+                }
+            }
+        }
+    })
+}
+
+fn bitfield_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<syn::ItemStruct>(input)?;
+    let Params {
+        ty,
+        int,
+        storage,
+        bits,
+        debug,
+        debug_order,
+        default,
+        order,
+        zeroize,
+        bytemuck,
+        arbitrary,
+        kani,
+        scale_codec,
+        ufmt,
+        svd,
+        slices,
+        alloc,
+        fields_trait,
+        field_enum,
+        atomic,
+        display,
+        radix,
+        validate,
+        new,
+        builder,
+        eq,
+        pad_auto,
+        flags,
+        ops,
+        setters,
+        must_use_getters,
+        try_setters,
+        try_getters,
+        inline,
+        conversions,
+        repr,
+        copy,
+        doc_table,
+        msb0,
+        overflow,
+        endian,
+        error,
+        invariant,
+        layout,
+    } = syn::parse2::<Params>(args)?;
+
+    let span = input.fields.span();
+    let name = input.ident;
+    let name_str = name.to_string();
+    let vis = input.vis;
+    let attrs: TokenStream = input.attrs.iter().map(ToTokens::to_token_stream).collect();
+    let error = ErrorTy {
+        name: format_ident!("{name}Error"),
+        custom: error,
+    };
+
+    let fields = match input.fields {
+        syn::Fields::Named(fields) => fields,
+        syn::Fields::Unnamed(fields) => positional_fields(fields),
+        syn::Fields::Unit => {
+            return Err(syn::Error::new(
+                span,
+                "bitfields require at least one field",
+            ))
+        }
+    };
+
+    // Asserts the invariant on a modified bitfield
+    let invariant_check = |this: TokenStream| {
+        let invariant = invariant.as_ref()?;
+        let name = invariant.to_token_stream().to_string().replace(' ', "");
+        let message = format!("{name_str}: invariant `{name}` violated");
+        Some(match overflow {
+            Overflow::Panic => quote!(assert!(#invariant(#this), #message);),
+            _ => quote!(debug_assert!(#invariant(#this), #message);),
+        })
+    };
+    // Trait methods of generic field types cannot be called from the `const` accessors
+    if !input.generics.params.is_empty() || input.generics.where_clause.is_some() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "bitfields cannot be generic, because their `const` accessors cannot call trait methods",
+        ));
+    }
+
+    // The next offsets, counted from the least and most significant bit
+    let (mut lsb_offset, mut msb_offset) = (0, 0);
+    let mut placed = false;
+    let mut members = Vec::with_capacity(fields.named.len());
+    let mut computed = Vec::new();
+    for field in fields.named {
+        // Computed fields only have a getter and no bits of their own
+        if let (Some(expr), Some(ident)) = (field_computed(&field.attrs)?, &field.ident) {
+            let syn::Field { attrs, vis, ty, .. } = &field;
+            let attrs = attrs.iter().filter(|a| !a.path().is_ident("bits"));
+            computed.push(quote! {
+                #( #attrs )*
+                #vis const fn #ident(&self) -> #ty {
+                    #expr
+                }
+            });
+            continue;
+        }
+        // Fields may be placed from the other end with their own order
+        let order = field_order(&field.attrs).unwrap_or(order);
+        let offset = match order {
+            Order::Lsb => &mut lsb_offset,
+            Order::Msb => &mut msb_offset,
+        };
+        let mut f = Member::new(
+            int.clone(),
+            storage.clone(),
+            bits,
+            field,
+            *offset,
+            order,
+            overflow,
+            endian,
+            &name_str,
+        )?;
+        if !setters {
+            f.disable_setters();
+        }
+        if must_use_getters {
+            f.must_use_getters();
+        }
+        if try_getters {
+            f.try_getters();
+        }
+        f.set_inline(&inline);
+        if msb0 {
+            f.set_msb0(bits);
+        }
+        if let Some(check) = invariant_check(quote!(&*self)) {
+            f.set_invariant(&check);
+        }
+        if try_setters {
+            f.set_error(&error);
+        }
+        // Aliases neither occupy bits of their own nor move the following fields
+        if !f.overlaps {
+            *offset = f.end(order, bits);
+            placed |= f.placed;
+        }
+        members.push(f);
+    }
+
+    let mut ranges: Vec<Range<usize>> = members
+        .iter()
+        .filter(|m| !m.overlaps)
+        .flat_map(|m| m.ranges.clone())
+        .collect();
+    ranges.sort_by_key(|r| r.start);
+    for pair in ranges.windows(2) {
+        if pair[0].end > pair[1].start {
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "The members at bits {:?} and {:?} overlap!",
+                    pair[0], pair[1]
+                ),
+            ));
+        }
+    }
+
+    // Gaps between fixed members (and the remaining bits with `pad = auto`) are left as hidden padding
+    let offset = if placed || pad_auto {
+        bits
+    } else {
+        ranges.iter().map(ExactSizeIterator::len).sum()
+    };
+
+    if offset < bits {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "The bitfiled size ({bits} bits) has to be equal to the sum of its members ({offset} bits)!. \
+                You might have to add padding (a {} bits large member prefixed with \"_\", or `pad = auto`).",
+                bits - offset
+            ),
+        ));
+    }
+    if offset > bits {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "The size of the members ({offset} bits) is larger than the type ({bits} bits)!."
+            ),
+        ));
+    }
+
+    // The functions of the field enum would clash with accessors of the same name
+    if field_enum.is_some() {
+        let reserved: &[&str] = if setters {
+            &["get", "set", "diff"]
+        } else {
+            &["get", "diff"]
+        };
+        for m in &members {
+            let (ident, accessors) = match (&m.inner, &m.array) {
+                (Some(inner), _) => (
+                    &inner.ident,
+                    vec![&inner.get_ident, &inner.with_ident, &inner.set_ident],
+                ),
+                (None, Some(array)) => (&array.ident, vec![&array.ident]),
+                (None, None) => continue,
+            };
+            if let Some(clash) = accessors
+                .into_iter()
+                .find(|a| reserved.contains(&&*a.to_string()))
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "`{clash}` conflicts with the `{clash}` function of `field_enum`, \
+                        rename the accessor with `#[bits(get = ...)]` or `#[bits(set = ...)]`"
+                    ),
+                ));
+            }
+        }
+    }
+
+    // The raw bits of a default initialized bitfield
+    let defaults = members.iter().map(Member::default);
+    let zero = storage.zero();
+    let raw_defaults = quote! {{
+        let mut this: #int = #zero;
+        #( #defaults )*
+        this
+    }};
+    // Signed storages take and return the signed integer in `from_bits` and `into_bits`
+    let bits_ty = storage.bits_ty(&int);
+    let default_bits = storage.cast_bits(raw_defaults.clone());
+    let mut checks: Vec<TokenStream> = members
+        .iter()
+        .flat_map(Member::scalars)
+        .flat_map(|m| [m.default_check(), m.exhaustive_check(), m.width_check()])
+        .collect();
+    // `new` and `Default` would panic on zero
+    if matches!(storage, Storage::NonZero) && (new.is_some() || default) {
+        let message = format!(
+            "{name_str}: NonZero bitfields need a nonzero default, or `new = false, default = false`"
+        );
+        checks.push(quote!(assert!(#raw_defaults != 0, #message);));
+    }
+
+    let debug_impl = if debug {
+        let mut debug_members: Vec<&Member> = members.iter().collect();
+        if debug_order == DebugOrder::Offset {
+            debug_members.sort_by_key(|m| m.offset);
+        }
+        let debug_fields = debug_members.iter().map(|m| m.debug());
+        let alternate_fields = debug_members.iter().map(|m| m.debug_alternate());
+        quote! {
+            impl core::fmt::Debug for #name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    if f.alternate() {
+                        f.debug_struct(#name_str)
+                            #( #alternate_fields )*
+                            .finish()
+                    } else {
+                        f.debug_struct(#name_str)
+                            #( #debug_fields )*
+                            .finish()
+                    }
+                }
+            }
+        }
+    } else {
+        TokenStream::default()
+    };
+
+    // Like `Debug` and `Display`, for targets where `core::fmt` is too heavy
+    let ufmt_impl = {
+        let mut debug_members: Vec<&Member> = members.iter().collect();
+        if debug_order == DebugOrder::Offset {
+            debug_members.sort_by_key(|m| m.offset);
+        }
+        let debug_fields = debug_members.into_iter().map(Member::udebug);
+        let display_fields = members.iter().map(Member::udisplay);
+        let redacted = members
+            .iter()
+            .flat_map(Member::scalars)
+            .any(|m| m.inner.as_ref().is_some_and(|inner| inner.redact));
+        let redacted = redacted.then(|| {
+            quote! {
+                /// Replaces redacted values
+                struct Redacted;
+                impl ::ufmt::uDebug for Redacted {
+                    fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::ufmt::uWrite + ?Sized,
+                    {
+                        f.write_str("***")
+                    }
+                }
+            }
+        });
+        [
+            quote! {
+                impl ::ufmt::uDebug for #name {
+                    fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::ufmt::uWrite + ?Sized,
+                    {
+                        #redacted
+                        f.debug_struct(#name_str)?
+                            #( #debug_fields )*
+                            .finish()
+                    }
+                }
+            },
+            quote! {
+                impl ::ufmt::uDisplay for #name {
+                    #[allow(unused_assignments)]
+                    fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::ufmt::uWrite + ?Sized,
+                    {
+                        let mut sep = "";
+                        #( #display_fields )*
+                        Ok(())
+                    }
+                }
+            },
+        ]
+        .map(|code| ufmt.wrap(code))
+    };
+
+    let display_impl = if display {
+        let display_fields = members.iter().map(Member::display);
+        let bools = members.iter().filter_map(|m| {
+            let inner = m.inner.as_ref()?;
+            (type_bits(&inner.ty).0 == TypeClass::Bool && inner.access.read() && !inner.redact)
+                .then(|| {
+                    let try_with = m.try_with(inner, quote!(this), &error);
+                    quote! {
+                        this = {
+                            let value = false;
+                            #try_with
+                        }?;
+                    }
+                })
+        });
+        let parse_fields = members.iter().filter_map(|m| m.parse_str(&error));
+        let error_ty = error.ty();
+        let parse = error.variant("Parse");
+        // Parsed text is rejected instead of panicking
+        let invalid = error.variant("Invalid");
+        let parse_check = invariant
+            .as_ref()
+            .map(|invariant| quote!(if !#invariant(&this) { return Err(#invalid); }));
+        quote! {
+            impl core::fmt::Display for #name {
+                #[allow(unused_assignments)]
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let mut sep = "";
+                    #( #display_fields )*
+                    Ok(())
+                }
+            }
+
+            impl core::str::FromStr for #name {
+                type Err = #error_ty;
+                /// Parses the `Display` output, fields that are not given keep their defaults.
+                #[allow(unused_comparisons, clippy::manual_range_contains)]
+                fn from_str(s: &str) -> Result<Self, #error_ty> {
+                    let mut this = Self::from_bits(#default_bits);
+                    // Flags are only written if they are set
+                    #( #bools )*
+                    for part in s.split_whitespace() {
+                        let (key, value) = match part.split_once('=') {
+                            Some((key, value)) => (key, Some(value)),
+                            None => (part, None),
+                        };
+                        match key {
+                            #( #parse_fields )*
+                            _ => return Err(#parse),
+                        }
+                    }
+                    #parse_check
+                    Ok(this)
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let radix_impl = if radix {
+        if matches!(storage, Storage::Array { .. }) {
+            return Err(syn::Error::new(
+                ty.span(),
+                "radix is not supported for array backed bitfields",
+            ));
+        }
+        let raw = storage.get(quote!(self.0));
+        let traits = [
+            format_ident!("Binary"),
+            format_ident!("Octal"),
+            format_ident!("LowerHex"),
+            format_ident!("UpperHex"),
+        ];
+        quote! {
+            #(
+                impl core::fmt::#traits for #name {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        core::fmt::#traits::fmt(&#raw, f)
+                    }
+                }
+            )*
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let mut parity_members = members.iter().filter(|m| m.parity().is_some());
+    let parity_impl = match (parity_members.next(), parity_members.next()) {
+        (Some(member), None) => member.parity_impl(bits)?,
+        (Some(_), Some(second)) => {
+            let second = second.inner.as_ref().expect("parity requires a field");
+            return Err(syn::Error::new(
+                second.ident.span(),
+                "only a single parity field is supported",
+            ));
+        }
+        _ => TokenStream::new(),
+    };
+
+    // Random bits are only taken for fields that decode to valid values,
+    // other fields and the reserved bits keep their defaults
+    let arbitrary_impl = {
+        let fields = members
+            .iter()
+            .filter(|m| !m.overlaps && m.parity().is_none())
+            .flat_map(Member::scalars)
+            .filter_map(|member| {
+                let inner = member.inner.as_ref()?;
+                let base_ty = &member.base_ty;
+                let extract = member.extract(&quote!(random), base_ty);
+                let round_trips = member.round_trips(inner);
+                let insert = member.insert();
+                Some(quote! {{
+                    let raw: #base_ty = #extract;
+                    if #round_trips {
+                        let value = raw;
+                        #insert
+                    }
+                }})
+            });
+        let with_parity =
+            (!parity_impl.is_empty()).then(|| quote!(let this = this.with_valid_parity();));
+        let check_invariant = invariant.as_ref().map(|invariant| {
+            quote! {
+                if !#invariant(&this) {
+                    return Err(::arbitrary::Error::IncorrectFormat);
+                }
+            }
+        });
+        let try_new_this = storage.try_wrap(quote!(this), quote!(()));
+        arbitrary.wrap(quote! {
+            impl<'a> ::arbitrary::Arbitrary<'a> for #name {
+                fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                    let random: #int = u.arbitrary()?;
+                    let mut this: #int = #raw_defaults;
+                    #( #fields )*
+                    let this: Result<Self, ()> = #try_new_this;
+                    let Ok(this) = this else {
+                        return Err(::arbitrary::Error::IncorrectFormat);
+                    };
+                    #with_parity
+                    #check_invariant
+                    Ok(this)
+                }
+            }
+        })
+    };
+
+    let kani_impl = if kani {
+        let harness = format_ident!("kani_{}_round_trip", name_str.to_lowercase());
+        // Setting a field to a value that round trips stores exactly its bits,
+        // and leaves the bits of all other fields untouched
+        let proofs = members
+            .iter()
+            .filter(|m| !m.overlaps && m.array.is_none())
+            .filter_map(|member| {
+                let inner = member.inner.as_ref()?;
+                let MemberInner {
+                    access,
+                    get_ident,
+                    with_ident,
+                    ..
+                } = inner;
+                if !access.read() || !access.write() {
+                    return None;
+                }
+                let base_ty = &member.base_ty;
+                let extract = member.extract(&quote!(this.0), base_ty);
+                let extract_updated = member.extract(&quote!(updated.0), base_ty);
+                let round_trips = member.round_trips(inner);
+                let mask = storage.mask(&member.ranges);
+                let (updated, other, mask) = match storage {
+                    Storage::Array { .. } => (quote!(updated.0), quote!(other.0), mask),
+                    _ => (quote!([updated.0]), quote!([other.0]), quote!([#mask])),
+                };
+                Some(quote! {{
+                    let this: #name = kani::any();
+                    let other: #name = kani::any();
+                    let raw: #base_ty = #extract;
+                    kani::assume(#round_trips);
+                    let updated = other.#with_ident(this.#get_ident());
+                    assert!(#extract_updated == raw);
+                    let (updated, other, mask) = (#updated, #other, #mask);
+                    let mut i = 0;
+                    while i < mask.len() {
+                        assert!((updated[i] ^ other[i]) & !mask[i] == 0);
+                        i += 1;
+                    }
+                }})
+            });
+        // Zero is no valid `NonZero` bitfield, so the setters may panic
+        let proof = (!matches!(storage, Storage::NonZero)).then(|| {
+            quote! {
+                #[cfg(kani)]
+                #[kani::proof]
+                fn #harness() {
+                    #( #proofs )*
+                }
+            }
+        });
+        quote! {
+            #[cfg(kani)]
+            impl kani::Arbitrary for #name {
+                fn any() -> Self {
+                    Self(kani::any())
+                }
+            }
+            #proof
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    // The raw value is encoded with the fixed size of its type
+    let scale_codec_impl = {
+        let codec = quote!(::parity_scale_codec);
+        let message = format!("invalid bits for {name}");
+        let decode = match (&storage, validate) {
+            (Storage::NonZero, false) => quote!(Ok(Self(raw))),
+            (Storage::NonZero, true) => {
+                quote!(Self::try_from_bits(raw.get()).map_err(|_| #message.into()))
+            }
+            (_, false) => quote!(Ok(Self::from_bits(raw))),
+            (_, true) => quote!(Self::try_from_bits(raw).map_err(|_| #message.into())),
+        };
+        [
+            quote! {
+                impl #codec::Encode for #name {
+                    fn size_hint(&self) -> usize {
+                        #codec::Encode::size_hint(&self.0)
+                    }
+                    fn encode_to<T: #codec::Output + ?Sized>(&self, dest: &mut T) {
+                        #codec::Encode::encode_to(&self.0, dest)
+                    }
+                }
+            },
+            quote!(impl #codec::EncodeLike for #name {}),
+            quote! {
+                impl #codec::Decode for #name {
+                    fn decode<I: #codec::Input>(input: &mut I) -> Result<Self, #codec::Error> {
+                        let raw: #ty = #codec::Decode::decode(input)?;
+                        #decode
+                    }
+                }
+            },
+            quote! {
+                impl #codec::MaxEncodedLen for #name {
+                    fn max_encoded_len() -> usize {
+                        <#ty as #codec::MaxEncodedLen>::max_encoded_len()
+                    }
+                }
+            },
+        ]
+        .map(|code| scale_codec.wrap(code))
+    };
+
+    if !zeroize.is_no() && matches!(storage, Storage::NonZero) {
+        return Err(syn::Error::new(
+            ty.span(),
+            "zeroize is not supported for NonZero bitfields",
+        ));
+    }
+    let zeroize_impl = zeroize.wrap(quote! {
+        impl ::zeroize::Zeroize for #name {
+            fn zeroize(&mut self) {
+                ::zeroize::Zeroize::zeroize(&mut self.0);
+            }
+        }
+    });
+
+    // Larger alignments add padding, so the struct has another layout than its raw type
+    if let Some(align) = repr.iter().find(|m| m.path().is_ident("align")) {
+        if slices || !bytemuck.is_no() {
+            return Err(syn::Error::new(
+                align.span(),
+                "`align` is not supported together with `slices` and `bytemuck`",
+            ));
+        }
+    }
+    let derive = if copy {
+        quote!(#[derive(Copy, Clone)])
+    } else {
+        quote!(#[derive(Clone)])
+    };
+    let repr = if repr.is_empty() {
+        quote!(#[repr(transparent)])
+    } else {
+        quote!(#[repr( #( #repr ),* )])
+    };
+
+    if !bytemuck.is_no() && matches!(storage, Storage::NonZero) {
+        return Err(syn::Error::new(
+            ty.span(),
+            "bytemuck is not supported for NonZero bitfields",
+        ));
+    }
+    if !bytemuck.is_no() && !copy {
+        return Err(syn::Error::new(
+            ty.span(),
+            "bytemuck requires `Copy`, which is not derived with `copy = false`",
+        ));
+    }
+    // Every bit pattern is valid, as the struct is a transparent integer (or array)
+    let bytemuck_impl = [
+        quote!(unsafe impl ::bytemuck::Zeroable for #name {}),
+        quote!(unsafe impl ::bytemuck::Pod for #name {}),
+    ]
+    .map(|code| bytemuck.wrap(code));
+
+    let slices_impl = if slices {
+        let size = bits / 8;
+        // Zero is no valid `NonZero` bitfield
+        let (item, decode) = match storage {
+            Storage::NonZero => (
+                quote!(Option<Self>),
+                quote!(Self::new_checked(#int::from_ne_bytes(bytes))),
+            ),
+            _ => (quote!(Self), quote!(Self::from_ne_bytes(bytes))),
+        };
+        quote! {
+            impl #name {
+                /// Views a slice of raw values as a slice of bitfields.
+                #vis const fn from_raw_slice(raw: &[#ty]) -> &[Self] {
+                    // SAFETY: `Self` is `repr(transparent)` over the raw type
+                    unsafe { core::slice::from_raw_parts(raw.as_ptr().cast(), raw.len()) }
+                }
+                /// Views a mutable slice of raw values as a slice of bitfields.
+                #vis fn from_raw_slice_mut(raw: &mut [#ty]) -> &mut [Self] {
+                    // SAFETY: `Self` is `repr(transparent)` over the raw type
+                    unsafe { core::slice::from_raw_parts_mut(raw.as_mut_ptr().cast(), raw.len()) }
+                }
+                /// Views a slice of bitfields as a slice of raw values.
+                #vis const fn as_raw_slice(slice: &[Self]) -> &[#ty] {
+                    // SAFETY: `Self` is `repr(transparent)` over the raw type
+                    unsafe { core::slice::from_raw_parts(slice.as_ptr().cast(), slice.len()) }
+                }
+                /// Views a mutable slice of bitfields as a slice of raw values.
+                #vis fn as_raw_slice_mut(slice: &mut [Self]) -> &mut [#ty] {
+                    // SAFETY: `Self` is `repr(transparent)` over the raw type
+                    unsafe { core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), slice.len()) }
+                }
+                /// Writes the native-endian bytes of the bitfields consecutively into `bytes`.
+                ///
+                /// Returns the number of written bytes, or `None` if `bytes` is too short.
+                #vis fn encode_slice(values: &[Self], bytes: &mut [u8]) -> Option<usize> {
+                    let len = values.len() * #size;
+                    let bytes = bytes.get_mut(..len)?;
+                    for (chunk, value) in bytes.chunks_exact_mut(#size).zip(values) {
+                        chunk.copy_from_slice(&Self(value.0).to_ne_bytes());
+                    }
+                    Some(len)
+                }
+                /// Reads bitfields from consecutive native-endian bytes,
+                /// ignoring the remainder that is too short for a bitfield.
+                #vis fn decode_slice(bytes: &[u8]) -> impl Iterator<Item = #item> + '_ {
+                    bytes.chunks_exact(#size).map(|chunk| {
+                        let mut bytes = [0; #size];
+                        bytes.copy_from_slice(chunk);
+                        #decode
+                    })
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    if !alloc.is_no() && matches!(storage, Storage::Array { .. }) {
+        return Err(syn::Error::new(
+            ty.span(),
+            "alloc is not supported for array backed bitfields",
+        ));
+    }
+    let raw_value = storage.get(quote!(value.0));
+    let checked_new = match storage {
+        Storage::NonZero => quote!(Self::new_checked(raw)),
+        _ => {
+            let this = storage.wrap(quote!(raw));
+            quote!(Some(#this))
+        }
+    };
+    let alloc_impl = alloc.wrap(quote! {
+        const _: () = {
+            extern crate alloc;
+            use alloc::vec::Vec;
+
+            impl #name {
+                /// Concatenates the native-endian bytes of the raw values.
+                #vis fn to_bytes_vec(values: &[Self]) -> Vec<u8> {
+                    let mut bytes = Vec::with_capacity(core::mem::size_of_val(values));
+                    for value in values {
+                        bytes.extend_from_slice(&#raw_value.to_ne_bytes());
+                    }
+                    bytes
+                }
+                /// Parses consecutive native-endian raw values.
+                ///
+                /// Returns `None` if the length is not a multiple of the bitfield size
+                /// or a value is invalid.
+                #vis fn from_bytes_vec(bytes: &[u8]) -> Option<Vec<Self>> {
+                    let chunks = bytes.chunks_exact(core::mem::size_of::<#int>());
+                    if !chunks.remainder().is_empty() {
+                        return None;
+                    }
+                    chunks
+                        .map(|chunk| {
+                            let raw = #int::from_ne_bytes(chunk.try_into().ok()?);
+                            #checked_new
+                        })
+                        .collect()
+                }
+                /// Converts the bitfields into their raw values.
+                #vis fn into_raw_vec(values: Vec<Self>) -> Vec<#ty> {
+                    values.into_iter().map(|value| value.0).collect()
+                }
+                /// Converts raw values into bitfields.
+                #vis fn from_raw_vec(raw: Vec<#ty>) -> Vec<Self> {
+                    raw.into_iter().map(Self).collect()
+                }
+            }
+        };
+    });
+
+    let fields_trait_impl = match fields_trait {
+        Some(trait_name) => {
+            let trait_name = trait_name.unwrap_or_else(|| format_ident!("{name}Fields"));
+            let trait_doc = format!("The getters of [`{name}`].");
+            let signatures = members.iter().filter_map(Member::getter_signature);
+            let getters = members.iter().filter_map(|m| {
+                let signature = m.getter_signature()?;
+                let get_ident = &m.inner.as_ref()?.get_ident;
+                Some(quote!(#signature { #name::#get_ident(self) }))
+            });
+            quote! {
+                #[doc = #trait_doc]
+                #vis trait #trait_name {
+                    #( #signatures; )*
+                }
+                impl #trait_name for #name {
+                    #( #getters )*
+                }
+            }
+        }
+        None => TokenStream::new(),
+    };
+
+    let builder_impl = match builder {
+        Some(builder_name) => {
+            use syn::ext::IdentExt;
+            let builder_name = builder_name.unwrap_or_else(|| format_ident!("{name}Builder"));
+            let build_check = invariant_check(quote!(&this));
+            let raw_bits = storage.cast_bits(quote!(self.0));
+            let builder_doc = format!(
+                "A builder for [`{name}`] that tracks which fields without default have been set."
+            );
+            let fields: Vec<_> = members
+                .iter()
+                .filter_map(|m| Some((m, m.inner.as_ref()?)))
+                .collect();
+            // One const parameter for each required field
+            let states: Vec<_> = fields
+                .iter()
+                .filter(|(_, inner)| inner.required)
+                .map(|(_, inner)| {
+                    format_ident!("{}_SET", inner.ident.unraw().to_string().to_uppercase())
+                })
+                .collect();
+            let unset = states.iter().map(|_| quote!(false));
+            let set = states.iter().map(|_| quote!(true));
+            let mut required = 0;
+            let setters: Vec<_> = fields
+                .iter()
+                .map(|(m, inner)| {
+                    let MemberInner {
+                        ident,
+                        ty,
+                        attrs,
+                        vis,
+                        ..
+                    } = inner;
+                    let doc: TokenStream = attrs
+                        .iter()
+                        .filter(|a| a.path().is_ident("doc"))
+                        .map(ToTokens::to_token_stream)
+                        .collect();
+                    let convert = m.convert();
+                    let insert = m.insert();
+                    let output = if inner.required {
+                        let index = required;
+                        required += 1;
+                        let params = states.iter().enumerate().map(|(i, state)| {
+                            if i == index {
+                                quote!(true)
+                            } else {
+                                quote!(#state)
+                            }
+                        });
+                        quote!(#builder_name<#( #params ),*>)
+                    } else {
+                        quote!(Self)
+                    };
+                    quote! {
+                        #doc
+                        #[cfg_attr(debug_assertions, track_caller)]
+                        #vis const fn #ident(self, value: #ty) -> #output {
+                            let mut this = self.0;
+                            #convert
+                            #insert
+                            #builder_name(this)
+                        }
+                    }
+                })
+                .collect();
+            quote! {
+                #[doc = #builder_doc]
+                #[must_use]
+                #vis struct #builder_name<#( const #states: bool ),*>(#int);
+
+                impl #name {
+                    /// Creates a builder, which can only be finished once all fields
+                    /// without default have been set.
+                    #vis const fn builder() -> #builder_name<#( #unset ),*> {
+                        #builder_name(#raw_defaults)
+                    }
+                }
+
+                impl<#( const #states: bool ),*> #builder_name<#( #states ),*> {
+                    #( #setters )*
+                }
+
+                impl #builder_name<#( #set ),*> {
+                    /// Creates the bitfield from the set fields.
+                    #vis const fn build(self) -> #name {
+                        let this = #name::from_bits(#raw_bits);
+                        #build_check
+                        this
+                    }
+                }
+            }
+        }
+        None => TokenStream::new(),
+    };
+
+    // The error enum is only generated for the fallible functions
+    let error_def = (try_setters || display || (field_enum.is_some() && setters))
+        .then(|| error.definition(&vis, &name));
+    let field_enum_impl = match field_enum {
+        Some(enum_name) => {
+            use syn::ext::IdentExt;
+            let enum_name = enum_name.unwrap_or_else(|| format_ident!("{name}Field"));
+            let enum_doc = format!("The fields of [`{name}`], for dynamic access.");
+            let fields: Vec<_> = members.iter().filter_map(|m| m.inner.as_ref()).collect();
+            let variants: Vec<_> = fields
+                .iter()
+                .map(|f| format_ident!("{}", upper_camel_case(&f.ident.unraw().to_string())))
+                .collect();
+            let names: Vec<_> = fields.iter().map(|f| f.ident.unraw().to_string()).collect();
+            let count = fields.len();
+            // Fields wider than 64 bits need a larger value type
+            let value_ty = if members.iter().any(|m| m.inner.is_some() && m.bits > 64) {
+                quote!(u128)
+            } else {
+                quote!(u64)
+            };
+            let value_syn: syn::Type = syn::parse2(value_ty.clone())?;
+            let raw = storage.get(quote!(self.0));
+            let getters = members.iter().filter(|m| m.inner.is_some()).map(|m| {
+                let extract = m.extract(&raw, &value_syn);
+                quote!((#extract) as #value_ty)
+            });
+            let field_setters = members
+                .iter()
+                .filter_map(|m| Some((m, m.inner.as_ref()?)))
+                .map(|(m, inner)| {
+                    if !inner.access.write() {
+                        let read_only = error.variant("ReadOnly");
+                        return quote!(Err(#read_only));
+                    }
+                    let (base_ty, mask) = (&m.base_ty, mask(m.bits));
+                    let insert = m.insert();
+                    let invalid = error.variant("Invalid");
+                    let try_new_this = storage.try_wrap(quote!(this), invalid.clone());
+                    let out_of_bounds = error.variant("OutOfBounds");
+                    let check = invariant
+                        .as_ref()
+                        .map(|invariant| quote!(if !#invariant(&this) { return Err(#invalid); }));
+                    quote! {{
+                        if value > #mask {
+                            return Err(#out_of_bounds);
+                        }
+                        let value = value as #base_ty;
+                        let mut this = #raw;
+                        #insert
+                        let this = #try_new_this?;
+                        #check
+                        *self = this;
+                        Ok(())
+                    }}
+                });
+            let error_ty = error.ty();
+            let set_fn = setters.then(|| {
+                quote! {
+                    /// Replaces the raw bits of the given field.
+                    ///
+                    /// Returns `Err` if the value does not fit, the field is read-only,
+                    /// or the result violates the invariant.
+                    #[allow(unused_comparisons)]
+                    #vis fn set(&mut self, field: #enum_name, value: #value_ty) -> Result<(), #error_ty> {
+                        match field {
+                            #( #enum_name::#variants => #field_setters, )*
+                        }
+                    }
+                }
+            });
+            quote! {
+                #[doc = #enum_doc]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                #vis enum #enum_name {
+                    #( #variants, )*
+                }
+
+                impl #enum_name {
+                    /// All fields in declaration order.
+                    #vis const ALL: [Self; #count] = [#( Self::#variants, )*];
+
+                    /// The name of the field.
+                    #vis const fn name(self) -> &'static str {
+                        match self {
+                            #( Self::#variants => #names, )*
+                        }
+                    }
+
+                    /// Looks up a field by its name.
+                    #vis fn from_name(name: &str) -> Option<Self> {
+                        match name {
+                            #( #names => Some(Self::#variants), )*
+                            _ => None,
+                        }
+                    }
+                }
+
+                impl #name {
+                    /// Returns the raw bits of the given field.
+                    #vis const fn get(&self, field: #enum_name) -> #value_ty {
+                        match field {
+                            #( #enum_name::#variants => #getters, )*
+                        }
+                    }
+
+                    /// Returns the fields whose raw bits differ from `other`,
+                    /// together with the bits in `self` and in `other`.
+                    #vis fn diff(&self, other: &Self) -> impl Iterator<Item = (#enum_name, #value_ty, #value_ty)> {
+                        let (this, other) = (Self(self.0), Self(other.0));
+                        #enum_name::ALL.into_iter().filter_map(move |field| {
+                            let (old, new) = (this.get(field), other.get(field));
+                            (old != new).then_some((field, old, new))
+                        })
+                    }
+
+                    #set_fn
+                }
+            }
+        }
+        None => TokenStream::new(),
+    };
+
+    // Accessors of fields named like `modify` take precedence over the functions of the bitfield
+    let unclaimed = |function: &str| {
+        members
+            .iter()
+            .filter_map(|m| m.inner.as_ref())
+            .all(|f| f.get_ident != function && f.with_ident != function && f.set_ident != function)
+    };
+
+    // With `atomic`, bitfields stored in existing atomics are also updated in place
+    let atomic_check = invariant_check(quote!(&new));
+    let modify_atomic = unclaimed("modify_atomic");
+    let atomic_fns = atomic_type(&storage, &int).map(|(atomic_int, width)| {
+        let setters = members
+            .iter()
+            .filter(|m| {
+                let set_atomic = m.inner.as_ref().map(|f| format!("set_{}_atomic", f.ident));
+                set_atomic.is_some_and(|set_atomic| unclaimed(&set_atomic))
+            })
+            .filter_map(|m| m.atomic_setter(&atomic_int, &atomic_check));
+        let modify_atomic_fn = modify_atomic.then(|| {
+            quote! {
+                /// Atomically applies the setters in `f` to the bitfield stored in `atomic`.
+                ///
+                /// All changes are written with a single compare-exchange, which is retried
+                /// with the current value if another thread changed it in the meantime,
+                /// so `f` may be called multiple times.
+                ///
+                /// Returns the previous bitfield.
+                #[cfg_attr(debug_assertions, track_caller)]
+                #vis fn modify_atomic(
+                    atomic: &#atomic_int,
+                    mut f: impl FnMut(Self) -> Self,
+                    order: core::sync::atomic::Ordering,
+                ) -> Self {
+                    use core::sync::atomic::Ordering;
+                    // The load of a failed exchange must not have release semantics
+                    let fetch_order = match order {
+                        Ordering::Release => Ordering::Relaxed,
+                        Ordering::AcqRel => Ordering::Acquire,
+                        order => order,
+                    };
+                    let update = |raw| {
+                        let new = f(Self(raw));
+                        #atomic_check
+                        Some(new.0)
+                    };
+                    match atomic.fetch_update(order, fetch_order, update) {
+                        Ok(raw) | Err(raw) => Self(raw),
+                    }
+                }
+            }
+        });
+        atomic.wrap(quote! {
+            #[cfg(target_has_atomic = #width)]
+            impl #name {
+                #modify_atomic_fn
+                #( #setters )*
+            }
+        })
+    });
+
+    let atomic_impl = if atomic.is_no() {
+        TokenStream::new()
+    } else {
+        let (atomic_int, _) = atomic_type(&storage, &int).ok_or_else(|| {
+            syn::Error::new(
+                ty.span(),
+                "atomic requires an unsigned integer up to 64 bits",
+            )
+        })?;
+        let atomic_name = format_ident!("Atomic{name}");
+        let atomic_doc = format!("An atomic [`{name}`].");
+        let modify_doc =
+            format!("Atomically applies the setters in `f`, see [`{name}::modify_atomic`].");
+        let modify_fn = modify_atomic.then(|| {
+            quote! {
+                #[doc = #modify_doc]
+                ///
+                /// Returns the previous bitfield.
+                #[cfg_attr(debug_assertions, track_caller)]
+                #vis fn modify(
+                    &self,
+                    f: impl FnMut(#name) -> #name,
+                    order: core::sync::atomic::Ordering,
+                ) -> #name {
+                    #name::modify_atomic(&self.0, f, order)
+                }
+            }
+        });
+        let updates = members.iter().filter_map(|m| m.atomic_update(&name));
+        let default_impl = default.then(|| {
+            quote! {
+                impl Default for #atomic_name {
+                    fn default() -> Self {
+                        Self::new(#name::from_bits(#default_bits))
+                    }
+                }
+            }
+        });
+        atomic.wrap(quote! {
+            #[doc = #atomic_doc]
+            #[repr(transparent)]
+            #vis struct #atomic_name(#atomic_int);
+
+            impl #atomic_name {
+                /// Creates a new atomic bitfield.
+                #vis const fn new(value: #name) -> Self {
+                    Self(#atomic_int::new(value.0))
+                }
+                /// Consumes the atomic and returns the contained bitfield.
+                #vis fn into_inner(self) -> #name {
+                    #name(self.0.into_inner())
+                }
+                /// Loads the bitfield.
+                #vis fn load(&self, order: core::sync::atomic::Ordering) -> #name {
+                    #name(self.0.load(order))
+                }
+                /// Stores the bitfield.
+                #vis fn store(&self, value: #name, order: core::sync::atomic::Ordering) {
+                    self.0.store(value.0, order)
+                }
+                /// Stores the bitfield, returning the previous one.
+                #vis fn swap(&self, value: #name, order: core::sync::atomic::Ordering) -> #name {
+                    #name(self.0.swap(value.0, order))
+                }
+                /// Updates the bitfield with `f` until it succeeds, or `f` returns `None`.
+                ///
+                /// Returns the previous bitfield.
+                #vis fn fetch_update(
+                    &self,
+                    set_order: core::sync::atomic::Ordering,
+                    fetch_order: core::sync::atomic::Ordering,
+                    mut f: impl FnMut(#name) -> Option<#name>,
+                ) -> Result<#name, #name> {
+                    self.0
+                        .fetch_update(set_order, fetch_order, |raw| {
+                            let new = f(#name(raw))?;
+                            #atomic_check
+                            Some(new.0)
+                        })
+                        .map(#name)
+                        .map_err(#name)
+                }
+                #modify_fn
+                #( #updates )*
+            }
+
+            impl From<#name> for #atomic_name {
+                fn from(value: #name) -> Self {
+                    Self::new(value)
+                }
+            }
+
+            #default_impl
+        })
+    };
+
+    let bytes_impl = bytes_impl(&storage, &int, &vis, bits / 8);
+
+    let field_infos = members.iter().filter_map(Member::info);
+    // A proc-macro crate cannot export types, so the named struct is provided by the user
+    let layout_const = match &layout {
+        Some(layout) => quote! {
+            #vis const LAYOUT: #layout = #layout {
+                name: #name_str,
+                bits: #bits,
+                fields: Self::FIELDS,
+            };
+        },
+        None => quote! {
+            #vis const LAYOUT: (&'static str, usize, &'static [(&'static str, usize, usize, bool)]) =
+                (#name_str, #bits, Self::FIELDS);
+        },
+    };
+
+    let doc_table = doc_table.then(|| {
+        let mut table = String::from("\n| Field | Bits | Access | Default |\n|---|---|---|---|");
+        for member in &members {
+            let (ident, first) = match (&member.inner, &member.array) {
+                (Some(inner), _) => (&inner.ident, member),
+                (None, Some(array)) => match array.elems.first() {
+                    Some(first) => (&array.ident, first),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let Some(inner) = &first.inner else {
+                continue;
+            };
+            let bits = member
+                .ranges
+                .iter()
+                .map(|r| format!("{}..{}", r.start, r.end))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let default = doc_default(&first.default);
+            table += &format!(
+                "\n| `{ident}` | {bits} | {} | `{default}` |",
+                inner.access.name()
+            );
+        }
+        quote!(#[doc = #table])
+    });
+    let raw_ty = storage.raw_ty(&int);
+    let occupied: Vec<_> = members
+        .iter()
+        .filter(|m| m.inner.is_some() || m.array.is_some())
+        .flat_map(|m| m.ranges.clone())
+        .collect();
+    let occupied_mask = storage.mask(&occupied);
+    let mut padding = Vec::new();
+    let mut start = 0;
+    for range in ranges.iter().filter(|r| occupied.contains(r)) {
+        padding.push(start..range.start);
+        start = range.end;
+    }
+    padding.push(start..bits);
+    let padding_mask = storage.mask(&padding);
+    // The mask constants of fields named `occupied` or `padding` take precedence
+    let field_masks: Vec<String> = members
+        .iter()
+        .flat_map(Member::scalars)
+        .filter_map(|m| m.inner.as_ref())
+        .flat_map(|f| std::iter::once(&f.ident).chain(&f.aliases))
+        .map(|ident| format!("{}_MASK", ident.to_string().to_uppercase()))
+        .collect();
+    let occupied_const = (!field_masks.iter().any(|m| m == "OCCUPIED_MASK")).then(|| {
+        quote! {
+            /// The bits that are covered by fields.
+            #vis const OCCUPIED_MASK: #raw_ty = #occupied_mask;
+        }
+    });
+    let padding_const = (!field_masks.iter().any(|m| m == "PADDING_MASK")).then(|| {
+        quote! {
+            /// The bits that are not covered by fields, like padding and gaps.
+            #vis const PADDING_MASK: #raw_ty = #padding_mask;
+        }
+    });
+
+    let flags_impl = if flags {
+        if !matches!(storage, Storage::Int) {
+            return Err(syn::Error::new(
+                ty.span(),
+                "flags is only supported for integer bitfields",
+            ));
+        }
+        use syn::ext::IdentExt;
+        // The other associated constants of the bitfield and its fields
+        let mut reserved: Vec<String> = [
+            "FIELDS",
+            "OCCUPIED_MASK",
+            "PADDING_MASK",
+            "BITS",
+            "DEFAULT",
+            "LAYOUT",
+        ]
+        .map(String::from)
+        .into();
+        for inner in members.iter().filter_map(|m| m.inner.as_ref()) {
+            let upper = inner.ident.unraw().to_string().to_uppercase();
+            for suffix in ["BITS", "OFFSET", "MASK", "MSB_OFFSET"] {
+                reserved.push(format!("{upper}_{suffix}"));
+            }
+        }
+        let mut consts = Vec::new();
+        for member in &members {
+            let Some(MemberInner { ident, ty, vis, .. }) = &member.inner else {
+                continue;
+            };
+            if type_bits(ty).0 != TypeClass::Bool || member.bits != 1 {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "flags requires all fields to be 1 bit bool",
+                ));
+            }
+            let flag = format_ident!("{}", ident.unraw().to_string().to_uppercase());
+            if reserved.contains(&flag.to_string()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("the flag constant `{flag}` conflicts with the associated constant `{name}::{flag}`"),
+                ));
+            }
+            let doc = format!("The `{ident}` flag.");
+            let range = member.offset..member.offset + 1;
+            let mask = storage.mask(std::slice::from_ref(&range));
+            consts.push(quote! {
+                #[doc = #doc]
+                #vis const #flag: Self = Self(#mask);
+            });
+        }
+        quote! {
+            impl #name {
+                #( #consts )*
+                /// Whether all flags of `other` are set.
+                #vis const fn contains(self, other: Self) -> bool {
+                    self.0 & other.0 == other.0
+                }
+                /// Whether any flag of `other` is set.
+                #vis const fn intersects(self, other: Self) -> bool {
+                    self.0 & other.0 != 0
+                }
+                /// The flags that are set in either `self` or `other`.
+                #vis const fn union(self, other: Self) -> Self {
+                    Self(self.0 | other.0)
+                }
+                /// The flags of `self` that are not set in `other`.
+                #vis const fn difference(self, other: Self) -> Self {
+                    Self(self.0 & !other.0)
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let ops_impl = if ops {
+        let this = storage.get(quote!(self.0));
+        let rhs = storage.get(quote!(rhs.0));
+        // Only `|` keeps `NonZero` bitfields nonzero
+        let nonzero = matches!(storage, Storage::NonZero);
+        let binary = [
+            (quote!(BitOr), quote!(bitor), quote!(|)),
+            (quote!(BitAnd), quote!(bitand), quote!(&)),
+            (quote!(BitXor), quote!(bitxor), quote!(^)),
+        ]
+        .into_iter()
+        .take(if nonzero { 1 } else { 3 })
+        .map(|(op, f, token)| {
+            let bits = storage.cast_bits(match storage {
+                Storage::Array { .. } => quote!(core::array::from_fn(|i| #this[i] #token #rhs[i])),
+                _ => quote!(#this #token #rhs),
+            });
+            quote! {
+                impl core::ops::#op for #name {
+                    type Output = Self;
+                    fn #f(self, rhs: Self) -> Self {
+                        Self::from_bits(#bits)
+                    }
+                }
+            }
+        });
+        let not = storage.cast_bits(match storage {
+            Storage::Array { .. } => quote!(core::array::from_fn(|i| !#this[i])),
+            _ => quote!(!#this),
+        });
+        let not_impl = (!nonzero).then(|| {
+            quote! {
+                impl core::ops::Not for #name {
+                    type Output = Self;
+                    fn not(self) -> Self {
+                        Self::from_bits(#not)
+                    }
+                }
+            }
+        });
+        quote! {
+            #( #binary )*
+            #not_impl
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let eq_impl = if eq {
+        let this = storage.get(quote!(self.0));
+        let other = storage.get(quote!(other.0));
+        let (eq, hash) = match storage {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => (
+                quote!(#this & OCCUPIED == #other & OCCUPIED),
+                quote!(core::hash::Hash::hash(&(#this & OCCUPIED), state)),
+            ),
+            Storage::Array { .. } => (
+                quote! {
+                    (0..#this.len()).all(|i| {
+                        #this[i] & OCCUPIED[i] == #other[i] & OCCUPIED[i]
+                    })
+                },
+                quote! {
+                    for i in 0..#this.len() {
+                        core::hash::Hash::hash(&(#this[i] & OCCUPIED[i]), state);
+                    }
+                },
+            ),
+        };
+        quote! {
+            impl PartialEq for #name {
+                /// Compares only the fields, ignoring padding.
+                fn eq(&self, other: &Self) -> bool {
+                    const OCCUPIED: #raw_ty = #occupied_mask;
+                    #eq
+                }
+            }
+            impl Eq for #name {}
+            impl core::hash::Hash for #name {
+                /// Hashes only the fields, ignoring padding.
+                fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                    const OCCUPIED: #raw_ty = #occupied_mask;
+                    #hash
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+    let from_bits = storage.wrap(quote!(bits));
+    let into_bits = storage.bits(quote!(self.0));
+
+    let from_raw = if validate {
+        let checks = validity_checks(&storage, &int, &members, bits);
+        let invariant = invariant
+            .as_ref()
+            .map(|invariant| quote!(&& #invariant(&this)));
+        let this = match storage {
+            Storage::NonZero => quote! {
+                match Self::new_checked(bits) {
+                    Some(this) => this,
+                    None => return Err(bits),
+                }
+            },
+            _ => storage.wrap(quote!(bits)),
+        };
+        let raw = storage.bits(quote!(v));
+        quote! {
+            impl #name {
+                /// Creates a bitfield from the raw bits, returning them as `Err` if
+                /// a field does not decode to a valid value or reserved bits differ
+                /// from their defaults.
+                #vis const fn try_from_bits(bits: #bits_ty) -> Result<Self, #bits_ty> {
+                    let this = #this;
+                    let reserved: #int = #raw_defaults;
+                    if true #( && #checks )* #invariant {
+                        Ok(this)
+                    } else {
+                        Err(bits)
+                    }
+                }
+            }
+            impl TryFrom<#ty> for #name {
+                type Error = #ty;
+                fn try_from(v: #ty) -> Result<Self, #ty> {
+                    match Self::try_from_bits(#raw) {
+                        Ok(this) => Ok(this),
+                        Err(_) => Err(v),
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl From<#ty> for #name {
+                fn from(v: #ty) -> Self {
+                    Self(v)
+                }
+            }
+        }
+    };
+
+    let conversion_impls = match &storage {
+        Storage::Int | Storage::Array { .. } => quote! {
+            #from_raw
+            impl From<#name> for #ty {
+                fn from(v: #name) -> #ty {
+                    v.0
+                }
+            }
+        },
+        Storage::Float(float) => {
+            let from_float = if validate {
+                quote! {
+                    impl TryFrom<#float> for #name {
+                        type Error = #float;
+                        fn try_from(v: #float) -> Result<Self, #float> {
+                            match Self::try_from_bits(v.to_bits()) {
+                                Ok(this) => Ok(this),
+                                Err(_) => Err(v),
+                            }
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    impl From<#float> for #name {
+                        fn from(v: #float) -> Self {
+                            Self(v.to_bits())
+                        }
+                    }
+                }
+            };
+            quote! {
+                #from_raw
+                #from_float
+                impl From<#name> for #ty {
+                    fn from(v: #name) -> #ty {
+                        v.0
+                    }
+                }
+                impl From<#name> for #float {
+                    fn from(v: #name) -> #float {
+                        #float::from_bits(v.0)
+                    }
+                }
+            }
+        }
+        Storage::NonZero | Storage::Signed { .. } => {
+            let raw = storage.get(quote!(v.0));
+            quote! {
+                #from_raw
+                impl From<#name> for #ty {
+                    fn from(v: #name) -> #ty {
+                        v.0
+                    }
+                }
+                impl From<#name> for #int {
+                    fn from(v: #name) -> #int {
+                        #raw
+                    }
+                }
+            }
+        }
+    };
+    let conversion_impls = conversions.then_some(conversion_impls);
+    let conversions = match &storage {
+        Storage::NonZero => quote! {
+            impl #name {
+                /// Creates a bitfield from the raw value, returning `None` if it is zero.
+                #vis const fn new_checked(value: #int) -> Option<Self> {
+                    match #ty::new(value) {
+                        Some(v) => Some(Self(v)),
+                        None => None,
+                    }
+                }
+            }
+
+            #conversion_impls
+        },
+        _ => conversion_impls.unwrap_or_default(),
+    };
+
+    let new_fn = new.map(|new_vis| {
+        let new_vis = new_vis.unwrap_or_else(|| vis.clone());
+        let args = members.iter().filter(|m| !m.overlaps).filter_map(|m| {
+            if let Some(ArrayInner {
+                ident, ty, elems, ..
+            }) = &m.array
+            {
+                let len = elems.len();
+                return Some(quote!(#ident: [#ty; #len]));
+            }
+            let MemberInner { ident, ty, .. } = m.inner.as_ref()?;
+            Some(quote!(#ident: #ty))
+        });
+        let inserts = members.iter().filter_map(Member::new_with_insert);
+        let new_this = storage.wrap(quote!(this));
+        let new_with_check = invariant_check(quote!(&this));
+        // Zero is no valid `NonZero` bitfield
+        let new_zeroed = (!matches!(storage, Storage::NonZero)).then(|| {
+            let zero = storage.zero();
+            quote! {
+                /// Creates a new bitfield with all bits zero, ignoring the field defaults.
+                #new_vis const fn new_zeroed() -> Self {
+                    Self::from_bits(#zero)
+                }
+            }
+        });
+        quote! {
+            /// Creates a new default initialized bitfield.
+            #new_vis const fn new() -> Self {
+                Self::from_bits(#default_bits)
+            }
+            #new_zeroed
+            /// Creates a new bitfield with the given values for all fields,
+            /// padding is default initialized.
+            #[allow(clippy::too_many_arguments)]
+            #[cfg_attr(debug_assertions, track_caller)]
+            #new_vis const fn new_with(#( #args ),*) -> Self {
+                let mut this: #int = #raw_defaults;
+                #( #inserts )*
+                let this = #new_this;
+                #new_with_check
+                this
+            }
+        }
+    });
+
+    let default_impl = if default {
+        quote! {
+            impl Default for #name {
+                fn default() -> Self {
+                    Self::from_bits(#default_bits)
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    // Writing back a read value must not clear the write-1-to-clear fields
+    let w1c: Vec<_> = members
+        .iter()
+        .filter(|m| {
+            m.inner
+                .as_ref()
+                .is_some_and(|i| i.access == Access::WriteOneToClear)
+        })
+        .collect();
+    let without_w1c_fn = (!w1c.is_empty()).then(|| {
+        let raw = storage.get(quote!(self.0));
+        let inserts = w1c.iter().map(|m| {
+            let base_ty = &m.base_ty;
+            let insert = m.insert();
+            quote! {{
+                let value: #base_ty = 0;
+                #insert
+            }}
+        });
+        let new_self = storage.wrap(quote!(this));
+        quote! {
+            /// Returns a copy with the write-1-to-clear fields set to zero,
+            /// so that it can be written back without clearing them.
+            #[must_use]
+            #vis const fn without_w1c(self) -> Self {
+                let mut this = #raw;
+                #( #inserts )*
+                #new_self
+            }
+        }
+    });
+    let write_back = if w1c.is_empty() {
+        quote!(Self(self.0))
+    } else {
+        quote!(Self(self.0).without_w1c())
+    };
+
+    let modify_check = invariant_check(quote!(&*self));
+
+    let raw = storage.get(quote!(self.0));
+    let raw_eq = |other: TokenStream| match storage {
+        Storage::Array { .. } => quote! {{
+            let (raw, other) = (#raw, #other);
+            let mut i = 0;
+            while i < raw.len() && raw[i] == other[i] {
+                i += 1;
+            }
+            i == raw.len()
+        }},
+        _ => quote!(#raw == #other),
+    };
+    let is_default = raw_eq(raw_defaults.clone());
+    // Zero is no valid `NonZero` bitfield
+    let is_zero = (!matches!(storage, Storage::NonZero)).then(|| {
+        let is_zero = raw_eq(storage.zero());
+        quote! {
+            /// Returns if all bits are zero.
+            #vis const fn is_zero(&self) -> bool {
+                #is_zero
+            }
+        }
+    });
+
+    // Fields named `raw` take precedence
+    let raw_fns = members
+        .iter()
+        .filter_map(|m| m.inner.as_ref())
+        .all(|f| f.get_ident != "raw" && f.with_ident != "with_raw")
+        .then(|| {
+            let set_raw = setters.then(|| {
+                quote! {
+                    /// Replaces the raw bits of the bitfield.
+                    #[cfg_attr(debug_assertions, track_caller)]
+                    #inline
+                    #vis fn set_raw(&mut self, bits: #bits_ty) {
+                        *self = Self::from_bits(bits);
+                        #modify_check
+                    }
+                }
+            });
+            quote! {
+                /// Returns the raw bits of the bitfield, like `into_bits`.
+                #inline
+                #vis const fn raw(&self) -> #bits_ty {
+                    #into_bits
+                }
+                /// Returns a copy with the raw bits replaced, like `from_bits`.
+                #[must_use]
+                #inline
+                #vis const fn with_raw(self, bits: #bits_ty) -> Self {
+                    Self::from_bits(bits)
+                }
+                #set_raw
+            }
+        });
+    // The svd2rust-style API replaces `modify`
+    let svd_impl = svd.then(|| {
+        svd_api(
+            &name,
+            &vis,
+            &bits_ty,
+            &members,
+            &default_bits,
+            setters,
+            &modify_check,
+            &write_back,
+        )
+    });
+    let modify_fn = (setters && !svd && unclaimed("modify")).then(|| {
+        quote! {
+            /// Replaces the bitfield with the result of `f`, which can chain multiple setters.
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn modify(&mut self, f: impl FnOnce(Self) -> Self) {
+                *self = f(#write_back);
+                #modify_check
+            }
+        }
+    });
+
+    Ok(quote! {
+        #attrs
+        #doc_table
+        #derive
+        #repr
+        #vis struct #name(#ty);
+
+        #error_def
+
+        // The defaults have to fit into their fields, `exhaustive` conversions must not panic,
+        // and `checked` field types must fit into their bits
+        const _: () = {
+            #( #checks )*
+        };
+
+        impl #name {
+            #new_fn
+
+            /// Creates a bitfield from the raw bits.
+            #inline
+            #vis const fn from_bits(bits: #bits_ty) -> Self {
+                #from_bits
+            }
+
+            /// Returns the raw bits of the bitfield.
+            #inline
+            #vis const fn into_bits(self) -> #bits_ty {
+                #into_bits
+            }
+
+            #raw_fns
+
+            /// Returns if the bitfield equals the default initialized one, including the padding.
+            #vis const fn is_default(&self) -> bool {
+                #is_default
+            }
+            #is_zero
+
+            #modify_fn
+            #without_w1c_fn
+
+            /// The layout of the fields (without padding) in declaration order,
+            /// as `(name, offset, bits, signed)`.
+            ///
+            /// The offset counts from the least significant bit, and `signed` is only
+            /// `true` for signed integer fields, not for custom types.
+            #vis const FIELDS: &'static [(&'static str, usize, usize, bool)] = &[
+                #( #field_infos, )*
+            ];
+            #occupied_const
+            #padding_const
+            /// The number of bits of the bitfield.
+            #vis const BITS: usize = #bits;
+            /// The bitfield with the default values, like `new`.
+            #vis const DEFAULT: Self = Self::from_bits(#default_bits);
+            /// The name, the number of bits, and the `FIELDS` of the bitfield.
+            #layout_const
+
+            #bytes_impl
+
+            #( #members )*
+
+            #( #computed )*
+
+            #parity_impl
+        }
+
+        #default_impl
+
+        #conversions
+
+        #debug_impl
+
+        #( #ufmt_impl )*
+
+        #eq_impl
+
+        #flags_impl
+
+        #ops_impl
+
+        #display_impl
+
+        #radix_impl
+
+        #zeroize_impl
+
+        #( #bytemuck_impl )*
+
+        #arbitrary_impl
+
+        #kani_impl
+        #svd_impl
+
+        #( #scale_codec_impl )*
+
+        #slices_impl
+
+        #alloc_impl
+
+        #fields_trait_impl
+
+        #field_enum_impl
+
+        #atomic_fns
+
+        #builder_impl
+
+        #atomic_impl
+    })
+}
+
+/// Names the fields of a tuple struct `f0`, `f1`, ..., with the accessors `get_0`, `with_0`, and `set_0`.
+///
+/// Fields of type `()` become padding.
+fn positional_fields(fields: syn::FieldsUnnamed) -> syn::FieldsNamed {
+    let named = fields
+        .unnamed
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut field)| {
+            if matches!(&field.ty, syn::Type::Tuple(t) if t.elems.is_empty()) {
+                field.ident = Some(format_ident!("_{i}"));
+            } else {
+                field.ident = Some(format_ident!("f{i}"));
+                let (get, with, set) = (
+                    format_ident!("get_{i}"),
+                    format_ident!("with_{i}"),
+                    format_ident!("set_{i}"),
+                );
+                field
+                    .attrs
+                    .push(syn::parse_quote!(#[bits(get = #get, with = #with, set = #set)]));
+            }
+            field
+        });
+    syn::FieldsNamed {
+        brace_token: Default::default(),
+        named: named.collect(),
+    }
+}
+
+/// Represents a member where accessor functions should be generated for.
+struct Member {
+    offset: usize,
+    bits: usize,
+    /// The bits of the member, multiple ranges if it is split
+    ranges: Vec<Range<usize>>,
+    /// Whether the offset was fixed by the field
+    placed: bool,
+    /// Whether the member intentionally aliases the bits of other members
+    overlaps: bool,
+    base_ty: syn::Type,
+    storage: Storage,
+    default: TokenStream,
+    inner: Option<MemberInner>,
+    /// The elements of field arrays, which have no `inner`
+    array: Option<ArrayInner>,
+}
+
+/// A field array of identical elements with indexed accessors
+struct ArrayInner {
+    ident: syn::Ident,
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    ty: syn::Type,
+    elems: Vec<Member>,
+}
+
+struct MemberInner {
+    ident: syn::Ident,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    into: TokenStream,
+    from: TokenStream,
+    /// The conversion into `Option<ty>`, which does not panic on unknown patterns
+    try_from: TokenStream,
+    parity: Option<(Parity, Range<usize>)>,
+    redact: bool,
+    access: Access,
+    range: Option<(Option<TokenStream>, TokenStream)>,
+    bounds_message: String,
+    overflow: Overflow,
+    /// Old names for which deprecated accessors are generated
+    aliases: Vec<syn::Ident>,
+    /// Whether the builder requires this field to be set
+    required: bool,
+    /// Views of the bitfield selected by the value of this field
+    views: Vec<View>,
+    /// Names of the getter, `with_`, and `set_` functions
+    get_ident: syn::Ident,
+    with_ident: syn::Ident,
+    set_ident: syn::Ident,
+    /// Whether the `&mut self` setters are generated
+    setters: bool,
+    /// Whether the getter is `#[must_use]`
+    must_use: bool,
+    /// Whether the fallible `try_` getter is generated for custom types
+    try_getter: bool,
+    /// The `#[inline]` attribute of the accessors
+    inline: TokenStream,
+    /// The size of the bitfield, if the bits are numbered from the most significant bit
+    msb0: Option<usize>,
+    /// The order in which the bits are stored
+    permutation: Permutation,
+    /// Whether every bit pattern is converted at compile time
+    exhaustive: bool,
+    /// Whether the `BITS` of the type are compared to the bits at compile time
+    checked: bool,
+    /// The type converted with its `into_bits` and `from_bits`, whose widths are checked
+    nested: Option<syn::Type>,
+    /// The function that validates values, and the message if it fails
+    validate: Option<(syn::Path, String)>,
+    /// Checks the invariant of the bitfield after the `&mut self` setters
+    invariant: TokenStream,
+    /// The format string of the value in `Debug`
+    fmt: Option<syn::LitStr>,
+    /// The error of the `try_with_` and `try_set_` functions
+    error: Option<ErrorTy>,
+}
+
+impl Member {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        int: syn::Type,
+        storage: Storage,
+        base_bits: usize,
+        f: syn::Field,
+        offset: usize,
+        order: Order,
+        overflow: Overflow,
+        byte_order: Permutation,
+        name: &str,
+    ) -> syn::Result<Self> {
+        let span = f.span();
+
+        let syn::Field {
+            mut attrs,
+            vis,
+            ident,
+            ty,
+            ..
+        } = f;
+
+        let ident = ident.ok_or_else(|| syn::Error::new(span, "Not supported"))?;
+        let ignore = ident.to_string().starts_with('_');
+
+        // Datasheet names become `#[doc(alias)]`es of all accessors
+        let mut doc_aliases = Vec::new();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("bits")) {
+            // Malformed attributes are reported when the field is parsed
+            let Ok(bits) = attr.parse_args::<BitsAttr>() else {
+                continue;
+            };
+            if ignore && !bits.doc_aliases.is_empty() {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "doc aliases are not supported on padding",
+                ));
+            }
+            doc_aliases.extend(bits.doc_aliases);
+        }
+        attrs.extend(
+            doc_aliases
+                .iter()
+                .map(|alias| syn::parse_quote!(#[doc(alias = #alias)])),
+        );
+
+        if let syn::Type::Array(array) = &ty {
+            if ignore {
+                return Err(syn::Error::new(ty.span(), "padding cannot be an array"));
+            }
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(len),
+                ..
+            }) = &array.len
+            else {
+                return Err(syn::Error::new(
+                    array.len.span(),
+                    "the array length has to be a literal",
+                ));
+            };
+            let len: usize = len.base10_parse()?;
+            for attr in attrs.iter().filter(|a| a.path().is_ident("bits")) {
+                let BitsAttr { count, .. } = attr.parse_args()?;
+                if count.is_some_and(|count| count != len) {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "the element count does not match the array length",
+                    ));
+                }
+            }
+            // Each element is a field of its own, directly following the previous one
+            let mut elems: Vec<Member> = Vec::with_capacity(len);
+            let mut next = offset;
+            for _ in 0..len {
+                let elem = syn::Field {
+                    attrs: attrs.clone(),
+                    vis: vis.clone(),
+                    mutability: syn::FieldMutability::None,
+                    ident: Some(ident.clone()),
+                    colon_token: None,
+                    ty: (*array.elem).clone(),
+                };
+                let elem = Member::new(
+                    int.clone(),
+                    storage.clone(),
+                    base_bits,
+                    elem,
+                    next,
+                    order,
+                    overflow,
+                    byte_order,
+                    name,
+                )?;
+                if elem.placed || elem.parity().is_some() || elem.array.is_some() {
+                    return Err(syn::Error::new(
+                        ty.span(),
+                        "field arrays do not support fixed positions, parity, or nesting",
+                    ));
+                }
+                next += elem.bits;
+                elems.push(elem);
+            }
+            let bits = next - offset;
+            let (offset, base_ty) = match elems.first() {
+                Some(first) => (
+                    elems.iter().map(|e| e.offset).min().unwrap_or(first.offset),
+                    first.base_ty.clone(),
+                ),
+                None => return Err(syn::Error::new(array.len.span(), "empty field array")),
+            };
+            attrs.retain(|a| !a.path().is_ident("bits"));
+            return Ok(Self {
+                offset,
+                bits,
+                ranges: std::iter::once(offset..offset + bits).collect(),
+                placed: false,
+                overlaps: elems.iter().any(|e| e.overlaps),
+                base_ty,
+                storage,
+                default: TokenStream::new(),
+                inner: None,
+                array: Some(ArrayInner {
+                    ident,
+                    attrs,
+                    vis,
+                    ty: (*array.elem).clone(),
+                    elems,
+                }),
+            });
+        }
+
+        let Field {
+            bits,
+            offset: fixed_offset,
+            parts,
+            ty,
+            mut default,
+            explicit_default,
+            into,
+            from,
+            try_from,
+            parity,
+            redact,
+            access,
+            range,
+            bounds_message,
+            overflow,
+            aliases,
+            views,
+            overlaps,
+            get,
+            with,
+            set,
+            permutation,
+            exhaustive,
+            checked,
+            nested,
+            validate,
+            fmt,
+        } = parse_field(
+            &attrs,
+            &ty,
+            ignore,
+            overflow,
+            byte_order,
+            &format!("{name}::{ident}"),
+        )?;
+        if bits > 128 {
+            return Err(syn::Error::new(ty.span(), "fields are limited to 128 bits"));
+        }
+        let base_ty = storage.member_ty(&int, bits);
+
+        let end = match &parts {
+            Some(parts) => parts.iter().map(|r| r.end).max().unwrap_or_default(),
+            None => fixed_offset.unwrap_or(offset) + bits,
+        };
+        if end > base_bits {
+            return Err(syn::Error::new(
+                ty.span(),
+                "The total size of the members is too large!",
+            ));
+        };
+
+        // compute the offset
+        let placed = fixed_offset.is_some();
+        let offset = match fixed_offset {
+            Some(offset) => offset,
+            None if order == Order::Lsb => offset,
+            None => base_bits - offset - bits,
+        };
+        let ranges = parts.unwrap_or_else(|| std::iter::once(offset..offset + bits).collect());
+
+        if bits > 0 && !ignore {
+            if into.is_empty() || from.is_empty() {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "Custom types require 'into', and 'from' in the #[bits] attribute",
+                ));
+            }
+
+            if default.is_empty() {
+                default = quote!(#ty::from_bits(0));
+            }
+
+            // remove our attribute
+            attrs.retain(|a| !a.path().is_ident("bits"));
+            let with_ident = with.unwrap_or_else(|| format_ident!("with_{ident}"));
+            let set_ident = set.unwrap_or_else(|| format_ident!("set_{ident}"));
+            let get_ident = get.unwrap_or_else(|| ident.clone());
+            // Parity bits are computed, the others have to be set if they have no default
+            let required = !explicit_default && parity.is_none() && !overlaps;
+
+            Ok(Self {
+                offset,
+                bits,
+                ranges,
+                placed,
+                overlaps,
+                base_ty,
+                storage,
+                default,
+                inner: Some(MemberInner {
+                    ident,
+                    ty,
+                    attrs,
+                    vis,
+                    into,
+                    from,
+                    try_from,
+                    parity,
+                    redact,
+                    access,
+                    range,
+                    bounds_message,
+                    overflow,
+                    aliases,
+                    required,
+                    views,
+                    get_ident,
+                    with_ident,
+                    set_ident,
+                    setters: true,
+                    must_use: false,
+                    try_getter: false,
+                    inline: TokenStream::new(),
+                    msb0: None,
+                    permutation,
+                    exhaustive,
+                    checked,
+                    nested,
+                    validate,
+                    invariant: TokenStream::new(),
+                    fmt,
+                    error: None,
+                }),
+                array: None,
+            })
+        } else {
+            if default.is_empty() {
+                default = quote!(0);
+            }
+
+            Ok(Self {
+                offset,
+                bits,
+                ranges,
+                placed,
+                overlaps,
+                base_ty,
+                storage,
+                default,
+                inner: None,
+                array: None,
+            })
+        }
+    }
+
+    /// Generates the `try_with_` and `try_set_` functions with this error type.
+    fn set_error(&mut self, error: &ErrorTy) {
+        if let Some(inner) = &mut self.inner {
+            inner.error = Some(error.clone());
+        }
+        if let Some(array) = &mut self.array {
+            array
+                .elems
+                .iter_mut()
+                .for_each(|elem| elem.set_error(error));
+        }
+    }
+
+    /// Checks the invariant of the bitfield after the `&mut self` setters.
+    fn set_invariant(&mut self, check: &TokenStream) {
+        if let Some(inner) = &mut self.inner {
+            inner.invariant = check.clone();
+        }
+        if let Some(array) = &mut self.array {
+            array
+                .elems
+                .iter_mut()
+                .for_each(|elem| elem.set_invariant(check));
+        }
+    }
+
+    /// Adds `#[must_use]` to the getters.
+    fn must_use_getters(&mut self) {
+        if let Some(inner) = &mut self.inner {
+            inner.must_use = true;
+        }
+        if let Some(array) = &mut self.array {
+            array.elems.iter_mut().for_each(Member::must_use_getters);
+        }
+    }
+
+    /// Generates the fallible `try_` getters.
+    fn try_getters(&mut self) {
+        if let Some(inner) = &mut self.inner {
+            inner.try_getter = true;
+        }
+        if let Some(array) = &mut self.array {
+            array.elems.iter_mut().for_each(Member::try_getters);
+        }
+    }
+
+    /// Sets the `#[inline]` attribute of the accessors.
+    fn set_inline(&mut self, inline: &TokenStream) {
+        if let Some(inner) = &mut self.inner {
+            inner.inline = inline.clone();
+        }
+        if let Some(array) = &mut self.array {
+            array
+                .elems
+                .iter_mut()
+                .for_each(|elem| elem.set_inline(inline));
+        }
+    }
+
+    /// Numbers the bits from the most significant bit of the `bits` large bitfield.
+    fn set_msb0(&mut self, bits: usize) {
+        if let Some(inner) = &mut self.inner {
+            inner.msb0 = Some(bits);
+        }
+        if let Some(array) = &mut self.array {
+            array.elems.iter_mut().for_each(|elem| elem.set_msb0(bits));
+        }
+    }
+
+    /// Omits the `&mut self` setters, leaving only the `with_` functions.
+    fn disable_setters(&mut self) {
+        if let Some(inner) = &mut self.inner {
+            inner.setters = false;
+        }
+        if let Some(array) = &mut self.array {
+            array.elems.iter_mut().for_each(Member::disable_setters);
+        }
+    }
+
+    /// The bits after the member in the given `order`.
+    fn end(&self, order: Order, base_bits: usize) -> usize {
+        match order {
+            Order::Lsb => self.ranges.iter().map(|r| r.end).max().unwrap_or_default(),
+            Order::Msb => {
+                base_bits
+                    - self
+                        .ranges
+                        .iter()
+                        .map(|r| r.start)
+                        .min()
+                        .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Extracts the value of the member from the underlying `raw` value, as `ty`.
+    fn extract(&self, raw: &TokenStream, ty: &syn::Type) -> TokenStream {
+        let extract = if let [range] = &self.ranges[..] {
+            self.storage.extract(raw, range.start, range.len(), ty)
+        } else {
+            // The first range holds the least significant bits
+            let mut pos = 0;
+            let parts = self.ranges.iter().map(|range| {
+                let extract = self.storage.extract(raw, range.start, range.len(), ty);
+                let shift = pos;
+                pos += range.len();
+                quote!((#extract << #shift))
+            });
+            let parts: Vec<_> = parts.collect();
+            quote!((0 #( | #parts )*))
+        };
+        match self.permutation().method() {
+            Some(method) => {
+                let base_ty = &self.base_ty;
+                let bits = self.bits as u32;
+                quote!((((#extract as #base_ty).#method() >> (#base_ty::BITS - #bits)) as #ty))
+            }
+            None => extract,
+        }
+    }
+
+    /// Replaces the bits of the member in the underlying `this` variable with `value`.
+    fn insert(&self) -> TokenStream {
+        let insert = if let [range] = &self.ranges[..] {
+            self.storage.insert(range.start, range.len())
+        } else {
+            let mut pos = 0;
+            let parts = self.ranges.iter().map(|range| {
+                let insert = self.storage.insert(range.start, range.len());
+                let shift = pos;
+                pos += range.len();
+                quote! {{
+                    let value = value >> #shift;
+                    #insert
+                }}
+            });
+            let parts: Vec<_> = parts.collect();
+            quote!(#( #parts )*)
+        };
+        match self.permutation().method() {
+            Some(method) => {
+                let base_ty = &self.base_ty;
+                let bits = self.bits as u32;
+                quote! {{
+                    let value = value.#method() >> (#base_ty::BITS - #bits);
+                    #insert
+                }}
+            }
+            None => insert,
+        }
+    }
+
+    /// The order in which the bits of the field are stored.
+    fn permutation(&self) -> Permutation {
+        self.inner
+            .as_ref()
+            .map_or(Permutation::Identity, |inner| inner.permutation)
+    }
+
+    /// The member itself, or the elements of a field array.
+    fn scalars(&self) -> &[Member] {
+        match &self.array {
+            Some(array) => &array.elems,
+            None => std::slice::from_ref(self),
+        }
+    }
+
+    fn parity(&self) -> Option<&(Parity, Range<usize>)> {
+        self.inner.as_ref()?.parity.as_ref()
+    }
+
+    /// Generates the parity finalizer and check for this parity member.
+    fn parity_impl(&self, base_bits: usize) -> syn::Result<TokenStream> {
+        let (Some(inner), Some((parity, over))) = (&self.inner, self.parity()) else {
+            return Ok(TokenStream::new());
+        };
+        let MemberInner {
+            ident,
+            vis,
+            get_ident,
+            with_ident,
+            ..
+        } = inner;
+
+        if over.end > base_bits {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("the parity range exceeds the bitfield size ({base_bits} bits)"),
+            ));
+        }
+        if over.contains(&self.offset) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "the parity range must not contain the parity bit itself",
+            ));
+        }
+
+        if over.len() > 128 {
+            return Err(syn::Error::new(
+                ident.span(),
+                "the parity range is limited to 128 bits",
+            ));
+        }
+        let raw = self.storage.get(quote!(self.0));
+        let range_ty = self.storage.member_ty(&self.base_ty, over.len());
+        let covered = self
+            .storage
+            .extract(&raw, over.start, over.len(), &range_ty);
+        let (name, odd) = match parity {
+            Parity::Even => ("even", false),
+            Parity::Odd => ("odd", true),
+        };
+        let expected = odd as u32;
+        let doc_with = format!(
+            "Returns a copy with `{ident}` set to the {name} parity over the bits {}..{}.",
+            over.start, over.end
+        );
+        let doc_check = format!(
+            "Checks whether `{ident}` matches the {name} parity over the bits {}..{}.",
+            over.start, over.end
+        );
+
+        Ok(quote! {
+            #[doc = #doc_with]
+            #vis const fn with_valid_parity(self) -> Self {
+                let ones = #covered.count_ones();
+                self.#with_ident(ones % 2 != #expected)
+            }
+            #[doc = #doc_check]
+            #vis const fn is_parity_ok(&self) -> bool {
+                (#covered.count_ones() + self.#get_ident() as u32) % 2 == #expected
+            }
+        })
+    }
+
+    /// The documented getter signature, without the body.
+    fn getter_signature(&self) -> Option<TokenStream> {
+        let MemberInner {
+            get_ident,
+            ty,
+            attrs,
+            access,
+            ..
+        } = self.inner.as_ref()?;
+        if !access.read() {
+            return None;
+        }
+        let doc = attrs.iter().filter(|a| a.path().is_ident("doc"));
+        Some(quote! {
+            #( #doc )*
+            fn #get_ident(&self) -> #ty
+        })
+    }
+
+    /// Generates the `fetch_update_<field>` function of the atomic wrapper.
+    fn atomic_update(&self, name: &Ident) -> Option<TokenStream> {
+        let MemberInner {
+            ident,
+            get_ident,
+            with_ident,
+            ty,
+            vis,
+            access,
+            ..
+        } = self.inner.as_ref()?;
+        if *access != Access::ReadWrite {
+            return None;
+        }
+        let fetch_update = format_ident!("fetch_update_{ident}");
+        let doc = format!(
+            "Updates the `{ident}` field with `f` until it succeeds, or `f` returns `None`.\n\n\
+            Returns the previous bitfield."
+        );
+        Some(quote! {
+            #[doc = #doc]
+            #vis fn #fetch_update(
+                &self,
+                set_order: core::sync::atomic::Ordering,
+                fetch_order: core::sync::atomic::Ordering,
+                mut f: impl FnMut(#ty) -> Option<#ty>,
+            ) -> Result<#name, #name> {
+                self.fetch_update(set_order, fetch_order, |v| {
+                    let value = f(v.#get_ident())?;
+                    Some(v.#with_ident(value))
+                })
+            }
+        })
+    }
+
+    /// Generates the `set_<field>_atomic` function, which updates the field in an atomic integer.
+    fn atomic_setter(
+        &self,
+        atomic_int: &TokenStream,
+        check: &Option<TokenStream>,
+    ) -> Option<TokenStream> {
+        let MemberInner {
+            ident,
+            with_ident,
+            ty,
+            vis,
+            access,
+            ..
+        } = self.inner.as_ref()?;
+        if !access.write() {
+            return None;
+        }
+        let set_atomic = format_ident!("set_{ident}_atomic");
+        let doc = format!(
+            "Atomically replaces the `{ident}` field of the bitfield stored in `atomic`, \
+            leaving the other fields untouched.\n\n\
+            Returns the previous bitfield."
+        );
+        let mask = self.storage.mask(&self.ranges);
+        Some(quote! {
+            #[doc = #doc]
+            #[cfg_attr(debug_assertions, track_caller)]
+            #vis fn #set_atomic(
+                atomic: &#atomic_int,
+                value: #ty,
+                order: core::sync::atomic::Ordering,
+            ) -> Self {
+                use core::sync::atomic::Ordering;
+                let bits = Self(0).#with_ident(value).0;
+                let mask = #mask;
+                // The load of a failed exchange must not have release semantics
+                let fetch_order = match order {
+                    Ordering::Release => Ordering::Relaxed,
+                    Ordering::AcqRel => Ordering::Acquire,
+                    order => order,
+                };
+                let update = |raw| {
+                    let new = Self(raw & !mask | bits);
+                    #check
+                    Some(new.0)
+                };
+                match atomic.fetch_update(order, fetch_order, update) {
+                    Ok(raw) | Err(raw) => Self(raw),
+                }
+            }
+        })
+    }
+
+    /// Converts the field `value` into the checked raw `value`, as configured by the overflow behavior.
+    fn convert(&self) -> TokenStream {
+        let Some(MemberInner {
+            into,
+            range,
+            bounds_message,
+            overflow,
+            validate,
+            ..
+        }) = &self.inner
+        else {
+            return TokenStream::new();
+        };
+        // Invalid values are rejected regardless of the overflow behavior
+        let validate = validate.as_ref().map(|(validate, message)| match overflow {
+            Overflow::Panic => quote!(assert!(#validate(&value), #message);),
+            _ => quote!(debug_assert!(#validate(&value), #message);),
+        });
+        let base_ty = &self.base_ty;
+        let mask = mask(self.bits);
+        // Clamp values that do not fit, either before or after the conversion
+        let (saturate, saturate_raw) = match (overflow, range) {
+            (Overflow::Saturate, Some((min, max))) => {
+                let min = min
+                    .as_ref()
+                    .map(|min| quote!(else if value < #min { #min }));
+                (
+                    quote! {
+                        #[allow(unused_comparisons)]
+                        let value = if value > #max { #max } #min else { value };
+                    },
+                    TokenStream::new(),
+                )
+            }
+            (Overflow::Saturate, None) => (
+                TokenStream::new(),
+                quote!(let value = if value > #mask { #mask } else { value };),
+            ),
+            _ => (TokenStream::new(), TokenStream::new()),
+        };
+        let check = overflow.assert(quote!(value <= #mask), bounds_message);
+
+        quote! {
+            #validate
+            #saturate
+            let value: #base_ty = {
+                let this = value;
+                #into
+            };
+            #saturate_raw
+            #check
+        }
+    }
+
+    /// Inserts the argument of `new_with` for this field into the raw `this`.
+    fn new_with_insert(&self) -> Option<TokenStream> {
+        if self.overlaps {
+            return None;
+        }
+        // Field arrays take an array with a value for each element
+        if let Some(ArrayInner { ident, elems, .. }) = &self.array {
+            let inserts = elems.iter().enumerate().map(|(i, elem)| {
+                let convert = elem.convert();
+                let insert = elem.insert();
+                quote! {{
+                    let value = #ident[#i];
+                    #convert
+                    #insert
+                }}
+            });
+            return Some(quote!(#( #inserts )*));
+        }
+        let MemberInner { ident, .. } = self.inner.as_ref()?;
+        let convert = self.convert();
+        let insert = self.insert();
+        Some(quote! {{
+            let value = #ident;
+            #convert
+            #insert
+        }})
+    }
+
+    /// Checks if `value` is in the range of the field and accepted by its validation, before it is converted.
+    fn in_range(&self) -> TokenStream {
+        let validate = (self.inner.as_ref())
+            .and_then(|inner| inner.validate.as_ref())
+            .map(|(validate, _)| quote!(&& #validate(&value)));
+        match self.inner.as_ref().and_then(|inner| inner.range.as_ref()) {
+            Some((min, max)) => {
+                let min = min.as_ref().map(|min| quote!(value >= #min &&));
+                quote!(#min value <= #max #validate)
+            }
+            None => quote!(true #validate),
+        }
+    }
+
+    /// The `(name, offset, bits, signed)` entry of `FIELDS`.
+    fn info(&self) -> Option<TokenStream> {
+        let MemberInner { ident, range, .. } = self.inner.as_ref()?;
+        let name = ident.to_string();
+        let Self { offset, bits, .. } = self;
+        let signed = matches!(range, Some((Some(_), _)));
+        Some(quote!((#name, #offset, #bits, #signed)))
+    }
+
+    /// Writes the field for `Display`: `name=value`, or just `name` for set flags.
+    fn display(&self) -> TokenStream {
+        let Some(inner) = self.inner.as_ref().filter(|inner| inner.access.read()) else {
+            return TokenStream::new();
+        };
+        let get_ident = &inner.get_ident;
+        let ident_str = inner.ident.to_string();
+        if inner.redact {
+            quote! {
+                write!(f, "{sep}{}=***", #ident_str)?;
+                sep = " ";
+            }
+        } else if type_bits(&inner.ty).0 == TypeClass::Bool {
+            quote! {
+                if self.#get_ident() {
+                    write!(f, "{sep}{}", #ident_str)?;
+                    sep = " ";
+                }
+            }
+        } else {
+            quote! {
+                write!(f, "{sep}{}={}", #ident_str, self.#get_ident())?;
+                sep = " ";
+            }
+        }
+    }
+
+    /// Writes the field like `display`, but with `ufmt`.
+    fn udisplay(&self) -> TokenStream {
+        let Some(inner) = self.inner.as_ref().filter(|inner| inner.access.read()) else {
+            return TokenStream::new();
+        };
+        let get_ident = &inner.get_ident;
+        let ident_str = inner.ident.to_string();
+        let value = if inner.redact {
+            quote!(f.write_str("=***")?;)
+        } else {
+            quote! {
+                f.write_str("=")?;
+                ::ufmt::uDisplay::fmt(&self.#get_ident(), f)?;
+            }
+        };
+        if type_bits(&inner.ty).0 == TypeClass::Bool && !inner.redact {
+            quote! {
+                if self.#get_ident() {
+                    f.write_str(sep)?;
+                    f.write_str(#ident_str)?;
+                    sep = " ";
+                }
+            }
+        } else {
+            quote! {
+                f.write_str(sep)?;
+                f.write_str(#ident_str)?;
+                #value
+                sep = " ";
+            }
+        }
+    }
+
+    /// Sets the field of `this` to `value`, evaluating to `Err` if it does not fit.
+    fn try_with(&self, inner: &MemberInner, this: TokenStream, error: &ErrorTy) -> TokenStream {
+        let MemberInner { into, .. } = inner;
+        let (base_ty, mask) = (&self.base_ty, mask(self.bits));
+        let in_range = self.in_range();
+        let raw = self.storage.get(quote!(#this.0));
+        let insert = self.insert();
+        let out_of_bounds = error.variant("OutOfBounds");
+        let try_new_this = self
+            .storage
+            .try_wrap(quote!(this), error.variant("Invalid"));
+        quote! {{
+            if !(#in_range) {
+                return Err(#out_of_bounds);
+            }
+            let value: #base_ty = {
+                let this = value;
+                #into
+            };
+            if value > #mask {
+                return Err(#out_of_bounds);
+            }
+            let mut this = #raw;
+            #insert
+            #try_new_this
+        }}
+    }
+
+    /// Parses the field in `FromStr`, if it is written by `Display` or writable.
+    fn parse_str(&self, error: &ErrorTy) -> Option<TokenStream> {
+        let inner = self
+            .inner
+            .as_ref()
+            .filter(|inner| inner.access.read() || inner.access.write())?;
+        let ident_str = inner.ident.to_string();
+        let ty = &inner.ty;
+        let parse = error.variant("Parse");
+        let value = if type_bits(ty).0 == TypeClass::Bool {
+            quote! {
+                match value {
+                    Some(value) => value.parse().map_err(|_| #parse)?,
+                    None => true,
+                }
+            }
+        } else {
+            quote!(value.ok_or(#parse)?.parse().map_err(|_| #parse)?)
+        };
+        let try_with = self.try_with(inner, quote!(this), error);
+        let error_ty = error.ty();
+        // Redacted values keep their defaults
+        let redacted = inner
+            .redact
+            .then(|| quote!(#ident_str if value == Some("***") => {}));
+        Some(quote! {
+            #redacted
+            #ident_str => {
+                let value: #ty = #value;
+                let next: Result<Self, #error_ty> = #try_with;
+                this = next?;
+            }
+        })
+    }
+
+    fn debug(&self) -> TokenStream {
+        if let Some(ArrayInner { ident, elems, .. }) = &self.array {
+            let ident_str = ident.to_string();
+            let Some(inner) = elems.first().and_then(|e| e.inner.as_ref()) else {
+                return quote!();
+            };
+            let indices = 0..elems.len();
+            if !inner.access.read() {
+                quote!()
+            } else if inner.redact {
+                quote!(.field(#ident_str, &format_args!("***")))
+            } else if let Some(fmt) = &inner.fmt {
+                let get_ident = &inner.get_ident;
+                quote!(.field(#ident_str, &[#( format_args!(#fmt, self.#get_ident(#indices)) ),*]))
+            } else {
+                let get_ident = &inner.get_ident;
+                quote!(.field(#ident_str, &[#( self.#get_ident(#indices) ),*]))
+            }
+        } else if let Some(inner) = &self.inner {
+            let ident_str = inner.ident.to_string();
+            let get_ident = &inner.get_ident;
+            if !inner.access.read() {
+                quote!()
+            } else if inner.redact {
+                quote!(.field(#ident_str, &format_args!("***")))
+            } else if let Some(fmt) = &inner.fmt {
+                quote!(.field(#ident_str, &format_args!(#fmt, self.#get_ident())))
+            } else {
+                quote!(.field(#ident_str, &self.#get_ident()))
+            }
+        } else {
+            quote!()
+        }
+    }
+
+    /// The field of the alternate `Debug` output, which adds the bit ranges and raw bits.
+    fn debug_alternate(&self) -> TokenStream {
+        let Some(inner) = self.inner.as_ref() else {
+            return self.debug();
+        };
+        if self.array.is_some() || !inner.access.read() || inner.redact {
+            return self.debug();
+        }
+        let ident_str = inner.ident.to_string();
+        let get_ident = &inner.get_ident;
+        let raw = self.storage.get(quote!(self.0));
+        let extract = self.extract(&raw, &self.base_ty);
+        let ranges = (self.ranges.iter())
+            .map(|r| format!("{}..{}", r.start, r.end))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let value = inner.fmt.as_ref().map_or("{:?}".into(), syn::LitStr::value);
+        let fmt = format!("{value} (bits {ranges} = {{:#0{}b}})", self.bits + 2);
+        quote!(.field(#ident_str, &format_args!(#fmt, self.#get_ident(), #extract)))
+    }
+
+    /// The field of the `ufmt::uDebug` output, like `debug`.
+    fn udebug(&self) -> TokenStream {
+        let (ident, inner, value) = if let Some(ArrayInner { ident, elems, .. }) = &self.array {
+            let Some(inner) = elems.first().and_then(|e| e.inner.as_ref()) else {
+                return quote!();
+            };
+            let get_ident = &inner.get_ident;
+            let indices = 0..elems.len();
+            (ident, inner, quote!(&[#( self.#get_ident(#indices) ),*]))
+        } else if let Some(inner) = &self.inner {
+            let get_ident = &inner.get_ident;
+            (&inner.ident, inner, quote!(&self.#get_ident()))
+        } else {
+            return quote!();
+        };
+        let ident_str = ident.to_string();
+        if !inner.access.read() {
+            quote!()
+        } else if inner.redact {
+            quote!(.field(#ident_str, &Redacted)?)
+        } else {
+            quote!(.field(#ident_str, #value)?)
+        }
+    }
+
+    /// Applies the default value to the raw `this` integer.
+    fn default(&self) -> TokenStream {
+        // Aliases are initialized by the fields they overlap
+        if self.overlaps {
+            return TokenStream::new();
+        }
+        if let Some(array) = &self.array {
+            let defaults = array.elems.iter().map(Member::default);
+            return quote!(#( #defaults )*);
+        }
+        let default = &self.default;
+        let base_ty = &self.base_ty;
+        let insert = self.insert();
+        if let Some(inner) = &self.inner {
+            let ty = &inner.ty;
+            let into = &inner.into;
+            quote! {{
+                let value: #base_ty = {
+                    let this: #ty = #default;
+                    #into
+                };
+                #insert
+            }}
+        } else {
+            quote! {{
+                let value = #default as #base_ty;
+                #insert
+            }}
+        }
+    }
+
+    /// Converts every bit pattern of `exhaustive` members at compile time,
+    /// which fails if a conversion panics.
+    fn exhaustive_check(&self) -> TokenStream {
+        let Some(inner) = self.inner.as_ref().filter(|inner| inner.exhaustive) else {
+            return TokenStream::new();
+        };
+        let ty = &inner.ty;
+        let from = &inner.from;
+        let base_ty = &self.base_ty;
+        let mask = mask(self.bits);
+        quote! {{
+            let mut this: #base_ty = 0;
+            loop {
+                let _: #ty = { #from };
+                if this == #mask {
+                    break;
+                }
+                this += 1;
+            }
+        }}
+    }
+
+    /// Checks that the `BITS` constant of `checked` member types fits into the member,
+    /// and that the raw types of `into_bits` and `from_bits` are not truncated.
+    fn width_check(&self) -> TokenStream {
+        let Some(inner) = &self.inner else {
+            return TokenStream::new();
+        };
+        let bits = self.bits;
+        let mut checks = TokenStream::new();
+        if inner.checked {
+            let ty = &inner.ty;
+            let message = format!(
+                "the type of `{}` does not fit into {bits} bits",
+                inner.ident
+            );
+            checks.extend(quote!(assert!(<#ty>::BITS as usize <= #bits, #message);));
+        }
+        if let Some(ty) = &inner.nested {
+            let base_ty = &self.base_ty;
+            let into_message = format!(
+                "`{}`: `into_bits` returns more bits than `{}`",
+                inner.ident,
+                base_ty.to_token_stream()
+            );
+            let from_message =
+                format!("`{}`: `from_bits` takes less than {bits} bits", inner.ident);
+            checks.extend(quote! {{
+                const fn raw_bits<T, R>(_: fn(T) -> R) -> (usize, usize) {
+                    (core::mem::size_of::<T>() * 8, core::mem::size_of::<R>() * 8)
+                }
+                assert!(raw_bits(<#ty>::into_bits).1 <= #base_ty::BITS as usize, #into_message);
+                assert!(raw_bits(<#ty>::from_bits).0 >= #bits, #from_message);
+            }});
+        }
+        checks
+    }
+
+    /// Checks if the bits in `raw` survive the round trip through the type of the member.
+    fn round_trips(&self, inner: &MemberInner) -> TokenStream {
+        let MemberInner {
+            ty, into, try_from, ..
+        } = inner;
+        let base_ty = &self.base_ty;
+        let mask = mask(self.bits);
+        let in_range = self.in_range();
+        quote! {{
+            let value: Option<#ty> = {
+                let this = raw;
+                #try_from
+            };
+            #[allow(unused_comparisons)]
+            let valid = match value {
+                Some(value) => #in_range && {
+                    let back: #base_ty = {
+                        let this = value;
+                        #into
+                    };
+                    back & #mask == raw
+                },
+                None => false,
+            };
+            valid
+        }}
+    }
+
+    /// Asserts at compile time that the default value fits into the member.
+    fn default_check(&self) -> TokenStream {
+        if self.overlaps {
+            return TokenStream::new();
+        }
+        let default = &self.default;
+        let base_ty = &self.base_ty;
+        let mask = mask(self.bits);
+        if let Some(inner) = &self.inner {
+            let ty = &inner.ty;
+            let into = &inner.into;
+            let message = format!("invalid default: {}", inner.bounds_message);
+            let in_range = self.in_range();
+            quote! {{
+                let value: #ty = #default;
+                #[allow(unused_comparisons)]
+                let fits = #in_range;
+                assert!(fits, #message);
+                let value: #base_ty = {
+                    let this = value;
+                    #into
+                };
+                #[allow(unused_comparisons)]
+                let fits = value <= #mask;
+                assert!(fits, #message);
+            }}
+        } else {
+            quote! {{
+                #[allow(unused_comparisons)]
+                let fits = (#default as u128) <= #mask;
+                assert!(fits, "default value of padding out of bounds");
+            }}
+        }
+    }
+}
+
+impl ToTokens for Member {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if let Some(array) = &self.array {
+            return array.to_tokens(tokens);
+        }
+        let Self {
+            offset,
+            bits,
+            base_ty,
+            storage,
+            default,
+            inner: Some(inner),
+            ..
+        } = self
+        else {
+            return Default::default();
+        };
+        let MemberInner {
+            ident,
+            ty,
+            attrs,
+            vis,
+            from,
+            access,
+            range,
+            aliases,
+            views,
+            get_ident,
+            with_ident,
+            set_ident,
+            invariant,
+            inline,
+            error,
+            ..
+        } = inner;
 
         let ident_str = ident.to_string();
 
-        let with_ident = format_ident!("with_{ident}");
-        let set_ident = format_ident!("set_{ident}");
+        let try_with_ident = format_ident!("try_with_{ident}");
+        let try_set_ident = format_ident!("try_set_{ident}");
         let bits_ident = format_ident!("{}_BITS", ident_str.to_uppercase());
         let offset_ident = format_ident!("{}_OFFSET", ident_str.to_uppercase());
+        let mask_ident = format_ident!("{}_MASK", ident_str.to_uppercase());
 
-        let location = format!("\n\nBits: {offset}..{}", offset + bits);
+        let convert = self.convert();
+        let mask = mask(*bits);
 
+        let range_consts = match range {
+            Some((min, max)) => {
+                let min_ident = format_ident!("{}_MIN", ident_str.to_uppercase());
+                let max_ident = format_ident!("{}_MAX", ident_str.to_uppercase());
+                let min = min
+                    .as_ref()
+                    .map(|min| quote!(const #min_ident: #ty = #min;));
+                quote! {
+                    #min
+                    const #max_ident: #ty = #max;
+                }
+            }
+            None => TokenStream::new(),
+        };
+
+        let location = location(&self.ranges, inner.msb0);
+
+        let placeholders = [
+            ("{bits}", bits.to_string()),
+            ("{offset}", offset.to_string()),
+            ("{mask}", hex_mask(&self.ranges)),
+            ("{default}", doc_default(default)),
+        ];
         let doc: TokenStream = attrs
             .iter()
             .filter(|a| !a.path().is_ident("bits"))
+            .map(|a| interpolate_doc(a, &placeholders))
+            .collect();
+
+        let raw = storage.get(quote!(self.0));
+        let insert = self.insert();
+        let extract = self.extract(&raw, base_ty);
+        let new_self = storage.wrap(quote!(this));
+        let try_with_doc = format!(
+            "Like [`{with_ident}`](Self::{with_ident}), but returns `Err` if the value does not fit."
+        );
+        let try_set_doc = format!(
+            "Like [`{set_ident}`](Self::{set_ident}), but returns `Err` if the value does not fit."
+        );
+
+        let raw_ty = storage.raw_ty(base_ty);
+        let shifted_mask = storage.mask(&self.ranges);
+
+        tokens.extend(quote! {
+            const #bits_ident: usize = #bits;
+            const #offset_ident: usize = #offset;
+            const #mask_ident: #raw_ty = #shifted_mask;
+            #range_consts
+        });
+        if let Some(base_bits) = inner.msb0 {
+            let msb_offset_ident = format_ident!("{}_MSB_OFFSET", ident_str.to_uppercase());
+            let end = self.ranges.iter().map(|r| r.end).max().unwrap_or_default();
+            let msb_offset = base_bits - end;
+            tokens.extend(quote!(const #msb_offset_ident: usize = #msb_offset;));
+        }
+        if access.write() {
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                #[must_use]
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis const fn #with_ident(self, value: #ty) -> Self {
+                    #convert
+                    let mut this = #raw;
+                    #insert
+                    #new_self
+                }
+            });
+        }
+        if let (true, Some(error)) = (access.write(), error) {
+            let error_ty = error.ty();
+            let constness = error.constness();
+            let try_with = self.try_with(inner, quote!(self), error);
+            tokens.extend(quote! {
+                #[doc = #try_with_doc]
+                #[doc = #location]
+                #[allow(unused_comparisons)]
+                #inline
+                #vis #constness fn #try_with_ident(self, value: #ty) -> Result<Self, #error_ty> {
+                    #try_with
+                }
+            });
+        }
+        if access.write() && inner.setters {
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis fn #set_ident(&mut self, value: #ty) {
+                    *self = Self(self.0).#with_ident(value);
+                    #invariant
+                }
+            });
+            if let Some(error) = error {
+                let error_ty = error.ty();
+                tokens.extend(quote! {
+                    #[doc = #try_set_doc]
+                    #[doc = #location]
+                    #inline
+                    #vis fn #try_set_ident(&mut self, value: #ty) -> Result<(), #error_ty> {
+                        *self = Self(self.0).#try_with_ident(value)?;
+                        #invariant
+                        Ok(())
+                    }
+                });
+            }
+        }
+        let must_use = inner.must_use.then(|| quote!(#[must_use]));
+        if access.read() {
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                #must_use
+                #inline
+                #vis const fn #get_ident(&self) -> #ty {
+                    let this = #extract;
+                    #from
+                }
+            });
+        }
+        if *access == Access::WriteOneToClear {
+            let clear_ident = format_ident!("clear_{ident}");
+            let cleared_ident = format_ident!("with_{ident}_cleared");
+            if inner.setters {
+                tokens.extend(quote! {
+                    /// Writes ones to the field, which clears it in hardware.
+                    #[doc = #location]
+                    #inline
+                    #vis fn #clear_ident(&mut self) {
+                        *self = Self(self.0).#cleared_ident();
+                        #invariant
+                    }
+                });
+            }
+            tokens.extend(quote! {
+                /// Returns a copy with ones written to the field, which clears it in hardware.
+                #[doc = #location]
+                #[must_use]
+                #inline
+                #vis const fn #cleared_ident(self) -> Self {
+                    let value: #base_ty = #mask;
+                    let mut this = #raw;
+                    #insert
+                    #new_self
+                }
+            });
+        }
+        if *access == Access::ReadToClear && inner.setters {
+            let take_ident = format_ident!("take_{ident}");
+            tokens.extend(quote! {
+                #doc
+                /// Reading the field clears it, so it is returned and set to zero.
+                #[doc = #location]
+                #inline
+                #vis fn #take_ident(&mut self) -> #ty {
+                    let value = {
+                        let this = #extract;
+                        #from
+                    };
+                    *self = {
+                        let value: #base_ty = 0;
+                        let mut this = #raw;
+                        #insert
+                        #new_self
+                    };
+                    #invariant
+                    value
+                }
+            });
+        } else if *access == Access::ReadToClear {
+            // Without `&mut self` functions, the cleared copy is returned with the value
+            let take_ident = format_ident!("take_{ident}");
+            tokens.extend(quote! {
+                #doc
+                /// Reading the field clears it, so it is returned with a copy where it is set to zero.
+                #[doc = #location]
+                #[must_use]
+                #inline
+                #vis const fn #take_ident(self) -> (Self, #ty) {
+                    let value = {
+                        let this = #extract;
+                        #from
+                    };
+                    let cleared = {
+                        let value: #base_ty = 0;
+                        let mut this = #raw;
+                        #insert
+                        #new_self
+                    };
+                    (cleared, value)
+                }
+            });
+        }
+        if inner.try_getter && access.read() && type_bits(ty).0 == TypeClass::Other {
+            let try_get_ident = format_ident!("try_{ident}");
+            let round_trips = self.round_trips(inner);
+            let try_get_doc = format!(
+                "Like [`{get_ident}`](Self::{get_ident}), but returns the raw bits as `Err` if they do not survive the round trip through the type, like unknown enum patterns."
+            );
+            tokens.extend(quote! {
+                #[doc = #try_get_doc]
+                #[doc = #location]
+                #inline
+                #vis const fn #try_get_ident(&self) -> Result<#ty, #base_ty> {
+                    let raw: #base_ty = #extract;
+                    if #round_trips {
+                        Ok({
+                            let this = raw;
+                            #from
+                        })
+                    } else {
+                        Err(raw)
+                    }
+                }
+            });
+        }
+        if access.read() && access.write() && type_bits(ty).0 == TypeClass::Bool {
+            let toggle_ident = format_ident!("toggle_{ident}");
+            let toggled_ident = format_ident!("with_{ident}_toggled");
+            if inner.setters {
+                tokens.extend(quote! {
+                    /// Inverts the flag.
+                    #[doc = #location]
+                    #inline
+                    #vis fn #toggle_ident(&mut self) {
+                        self.#set_ident(!self.#get_ident());
+                    }
+                });
+            }
+            tokens.extend(quote! {
+                /// Returns a copy with the flag inverted.
+                #[doc = #location]
+                #[must_use]
+                #inline
+                #vis const fn #toggled_ident(self) -> Self {
+                    let value = !self.#get_ident();
+                    self.#with_ident(value)
+                }
+            });
+        }
+        if access.read() && access.write() {
+            let update_ident = format_ident!("update_{ident}");
+            let map_ident = format_ident!("map_{ident}");
+            let replace_ident = format_ident!("replace_{ident}");
+            let update_doc = format!("Replaces the value with the result of `f`, see [`{set_ident}`](Self::{set_ident}).");
+            let replace_doc = format!("Replaces the value and returns the previous one, see [`{set_ident}`](Self::{set_ident}).");
+            let map_doc = format!("Returns a copy with the value replaced by the result of `f`, see [`{with_ident}`](Self::{with_ident}).");
+            if inner.setters {
+                tokens.extend(quote! {
+                    #[doc = #update_doc]
+                    #[doc = #location]
+                    #[cfg_attr(debug_assertions, track_caller)]
+                    #inline
+                    #vis fn #update_ident(&mut self, f: impl FnOnce(#ty) -> #ty) {
+                        self.#set_ident(f(self.#get_ident()));
+                    }
+                    #[doc = #replace_doc]
+                    #[doc = #location]
+                    #[cfg_attr(debug_assertions, track_caller)]
+                    #inline
+                    #vis fn #replace_ident(&mut self, value: #ty) -> #ty {
+                        let old = self.#get_ident();
+                        self.#set_ident(value);
+                        old
+                    }
+                });
+            }
+            tokens.extend(quote! {
+                #[doc = #map_doc]
+                #[doc = #location]
+                #[must_use]
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis fn #map_ident(self, f: impl FnOnce(#ty) -> #ty) -> Self {
+                    let value = f(self.#get_ident());
+                    self.#with_ident(value)
+                }
+            });
+        }
+
+        // Views may differ in the signedness of their storage
+        let view_bits = match storage {
+            Storage::Array { .. } => quote!(Self(self.0).into_bits()),
+            _ => quote!(Self(self.0).into_bits() as _),
+        };
+        for View {
+            pat,
+            ident: view,
+            ty: view_ty,
+        } in views
+        {
+            let as_view = format_ident!("as_{view}");
+            let view_doc = format!(
+                "Returns the bits as [`{}`] if `{ident}` matches `{}`.",
+                view_ty.to_token_stream(),
+                pat.to_token_stream()
+            );
+            tokens.extend(quote! {
+                #[doc = #view_doc]
+                #inline
+                #vis const fn #as_view(&self) -> Option<#view_ty> {
+                    let value: #ty = {
+                        let this = #extract;
+                        #from
+                    };
+                    match value {
+                        #pat => Some(#view_ty::from_bits(#view_bits)),
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+            });
+        }
+
+        for alias in aliases {
+            let alias_str = alias.to_string();
+            let with_alias = format_ident!("with_{alias}");
+            let set_alias = format_ident!("set_{alias}");
+            let bits_alias = format_ident!("{}_BITS", alias_str.to_uppercase());
+            let offset_alias = format_ident!("{}_OFFSET", alias_str.to_uppercase());
+            let mask_alias = format_ident!("{}_MASK", alias_str.to_uppercase());
+            let bits_note = format!("renamed to `{bits_ident}`");
+            let offset_note = format!("renamed to `{offset_ident}`");
+            let mask_note = format!("renamed to `{mask_ident}`");
+            let with_note = format!("renamed to `{with_ident}`");
+            let note = format!("renamed to `{get_ident}`");
+            let set_note = format!("renamed to `{set_ident}`");
+            tokens.extend(quote! {
+                #[deprecated(note = #bits_note)]
+                const #bits_alias: usize = #bits;
+                #[deprecated(note = #offset_note)]
+                const #offset_alias: usize = #offset;
+                #[deprecated(note = #mask_note)]
+                const #mask_alias: #raw_ty = Self::#mask_ident;
+            });
+            if access.write() {
+                tokens.extend(quote! {
+                    #[deprecated(note = #with_note)]
+                    #[must_use]
+                    #[cfg_attr(debug_assertions, track_caller)]
+                    #inline
+                    #vis const fn #with_alias(self, value: #ty) -> Self {
+                        self.#with_ident(value)
+                    }
+                });
+            }
+            if access.write() && inner.setters {
+                tokens.extend(quote! {
+                    #[deprecated(note = #set_note)]
+                    #[cfg_attr(debug_assertions, track_caller)]
+                    #inline
+                    #vis fn #set_alias(&mut self, value: #ty) {
+                        self.#set_ident(value)
+                    }
+                });
+            }
+            if access.read() {
+                tokens.extend(quote! {
+                    #[deprecated(note = #note)]
+                    #inline
+                    #vis const fn #alias(&self) -> #ty {
+                        self.#get_ident()
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl ToTokens for ArrayInner {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            ident,
+            attrs,
+            vis,
+            ty,
+            elems,
+        } = self;
+        let (Some(first), Some(last)) = (elems.first(), elems.last()) else {
+            return;
+        };
+        let Some(MemberInner {
+            access,
+            get_ident,
+            with_ident,
+            set_ident,
+            setters,
+            must_use,
+            invariant,
+            inline,
+            msb0,
+            ..
+        }) = first.inner.as_ref()
+        else {
+            return;
+        };
+        let storage = &first.storage;
+        let len = elems.len();
+        let offset = first.offset.min(last.offset);
+        let bits = first.bits * len;
+
+        let ident_str = ident.to_string();
+        let bits_ident = format_ident!("{}_BITS", ident_str.to_uppercase());
+        let offset_ident = format_ident!("{}_OFFSET", ident_str.to_uppercase());
+        let len_ident = format_ident!("{}_LEN", ident_str.to_uppercase());
+        let location = format!(
+            "{}, as {len} elements of {} bits",
+            location(std::slice::from_ref(&(offset..offset + bits)), *msb0),
+            first.bits
+        );
+        let doc: TokenStream = attrs
+            .iter()
+            .filter(|a| a.path().is_ident("doc"))
             .map(ToTokens::to_token_stream)
             .collect();
 
-        let mask = u128::MAX >> (u128::BITS - *bits as u32);
-        let mask = syn::LitInt::new(&format!("0x{mask:x}"), Span::mixed_site());
+        let raw = storage.get(quote!(self.0));
+        let new_self = storage.wrap(quote!(this));
+        let indices = 0..len;
+        let must_use = must_use.then(|| quote!(#[must_use]));
 
-        let code = quote! {
+        tokens.extend(quote! {
             const #bits_ident: usize = #bits;
             const #offset_ident: usize = #offset;
+            const #len_ident: usize = #len;
+        });
+        if let Some(base_bits) = msb0 {
+            let msb_offset_ident = format_ident!("{}_MSB_OFFSET", ident_str.to_uppercase());
+            let msb_offset = base_bits - offset - bits;
+            tokens.extend(quote!(const #msb_offset_ident: usize = #msb_offset;));
+        }
+        if access.write() {
+            let inserts = elems.iter().map(|elem| {
+                let convert = elem.convert();
+                let insert = elem.insert();
+                quote!({ #convert #insert })
+            });
+            let indices = indices.clone();
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                ///
+                /// Panics if `index` is out of bounds.
+                #[must_use]
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis const fn #with_ident(self, index: usize, value: #ty) -> Self {
+                    let mut this = #raw;
+                    match index {
+                        #( #indices => #inserts )*
+                        _ => panic!("index out of bounds"),
+                    }
+                    #new_self
+                }
+            });
+        }
+        if access.write() && *setters {
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                ///
+                /// Panics if `index` is out of bounds.
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis fn #set_ident(&mut self, index: usize, value: #ty) {
+                    *self = Self(self.0).#with_ident(index, value);
+                    #invariant
+                }
+            });
+        }
+        if access.read() {
+            let extracts = elems.iter().filter_map(|elem| {
+                let from = &elem.inner.as_ref()?.from;
+                let extract = elem.extract(&raw, &elem.base_ty);
+                Some(quote!({
+                    let this = #extract;
+                    #from
+                }))
+            });
+            tokens.extend(quote! {
+                #doc
+                #[doc = #location]
+                ///
+                /// Panics if `index` is out of bounds.
+                #must_use
+                #[cfg_attr(debug_assertions, track_caller)]
+                #inline
+                #vis const fn #get_ident(&self, index: usize) -> #ty {
+                    match index {
+                        #( #indices => #extracts )*
+                        _ => panic!("index out of bounds"),
+                    }
+                }
+            });
+        }
+    }
+}
 
-            #doc
-            #[doc = #location]
-            #[cfg_attr(debug_assertions, track_caller)]
-            #vis const fn #with_ident(self, value: #ty) -> Self {
-                let value: #base_ty = {
-                    let this = value;
-                    #into
+/// The documentation of the bits of a member, numbered from the most significant bit with `msb0`.
+fn location(ranges: &[Range<usize>], msb0: Option<usize>) -> String {
+    let ranges = ranges.iter().map(|r| match msb0 {
+        Some(bits) => format!("{}..{}", bits - r.end, bits - r.start),
+        None => format!("{}..{}", r.start, r.end),
+    });
+    let ranges = ranges.collect::<Vec<_>>().join(", ");
+    match msb0 {
+        Some(_) => format!("\n\nBits (Msb0): {ranges}"),
+        None => format!("\n\nBits: {ranges}"),
+    }
+}
+
+/// Substitutes layout placeholders like `{bits}` in doc comments.
+fn interpolate_doc(attr: &syn::Attribute, placeholders: &[(&str, String)]) -> TokenStream {
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        path,
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(doc),
+                ..
+            }),
+        ..
+    }) = &attr.meta
+    {
+        if path.is_ident("doc") {
+            let mut value = doc.value();
+            for (placeholder, replacement) in placeholders {
+                value = value.replace(placeholder, replacement);
+            }
+            let doc = syn::LitStr::new(&value, doc.span());
+            return quote!(#[doc = #doc]);
+        }
+    }
+    attr.to_token_stream()
+}
+
+/// Generates the conditions under which the bitfield `this` is valid.
+///
+/// The fields have to survive a round trip through their type,
+/// and the reserved bits have to match the raw defaults in `reserved`.
+fn validity_checks(
+    storage: &Storage,
+    int: &syn::Type,
+    members: &[Member],
+    bits: usize,
+) -> Vec<TokenStream> {
+    let raw = storage.get(quote!(this.0));
+    let mut checks = Vec::new();
+    let mut covered = vec![false; bits];
+    for member in members.iter().flat_map(Member::scalars) {
+        let Some(inner) = &member.inner else {
+            continue;
+        };
+        for range in &member.ranges {
+            covered[range.clone()].fill(true);
+        }
+        let base_ty = &member.base_ty;
+        let extract = member.extract(&raw, base_ty);
+        let round_trips = member.round_trips(inner);
+        checks.push(quote! {{
+            let raw: #base_ty = #extract;
+            #round_trips
+        }});
+    }
+    // Compare the uncovered bits in chunks that fit into `u128`
+    let mut offset = 0;
+    while offset < bits {
+        if covered[offset] {
+            offset += 1;
+            continue;
+        }
+        let len = covered[offset..]
+            .iter()
+            .take(128)
+            .take_while(|covered| !**covered)
+            .count();
+        let ty = storage.member_ty(int, len);
+        let this = storage.extract(&raw, offset, len, &ty);
+        let reserved = storage.extract(&quote!(reserved), offset, len, &ty);
+        checks.push(quote!(#this == #reserved));
+        offset += len;
+    }
+    checks
+}
+
+/// Generates the endian-aware conversions from and into `size` bytes.
+fn bytes_impl(
+    storage: &Storage,
+    int: &syn::Type,
+    vis: &syn::Visibility,
+    size: usize,
+) -> TokenStream {
+    let mut code = TokenStream::new();
+    let endians = [
+        ("be", "big-endian"),
+        ("le", "little-endian"),
+        ("ne", "native"),
+    ];
+    for (endian, order) in endians {
+        let from = format_ident!("from_{endian}_bytes");
+        let to = format_ident!("to_{endian}_bytes");
+        let from_doc =
+            format!("Creates a bitfield from its raw bits as a byte array in {order} byte order.");
+        let to_doc = format!("Returns the raw bits as a byte array in {order} byte order.");
+        let (from_body, to_body) = match storage {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                let raw = storage.get(quote!(self.0));
+                let from_raw = storage.cast_bits(quote!(#int::#from(bytes)));
+                (quote!(Self::from_bits(#from_raw)), quote!(#raw.#to()))
+            }
+            Storage::Array {
+                elem,
+                elem_bits,
+                len,
+            } => {
+                let elem_size = elem_bits / 8;
+                // The position of element `i` in the byte array
+                let start = match endian {
+                    "be" => quote!((#len - 1 - i) * #elem_size),
+                    "le" => quote!(i * #elem_size),
+                    _ => quote! {
+                        if cfg!(target_endian = "big") {
+                            (#len - 1 - i) * #elem_size
+                        } else {
+                            i * #elem_size
+                        }
+                    },
                 };
-                #[allow(unused_comparisons)]
-                debug_assert!(value <= #mask, "value out of bounds");
-                Self(self.0 & !(#mask << #offset) | (value & #mask) << #offset)
+                (
+                    quote! {
+                        let mut this = [0; #len];
+                        let mut i = 0;
+                        while i < #len {
+                            let start = #start;
+                            let mut elem = [0; #elem_size];
+                            let mut j = 0;
+                            while j < #elem_size {
+                                elem[j] = bytes[start + j];
+                                j += 1;
+                            }
+                            this[i] = #elem::#from(elem);
+                            i += 1;
+                        }
+                        Self(this)
+                    },
+                    quote! {
+                        let mut bytes = [0; #size];
+                        let mut i = 0;
+                        while i < #len {
+                            let start = #start;
+                            let elem = self.0[i].#to();
+                            let mut j = 0;
+                            while j < #elem_size {
+                                bytes[start + j] = elem[j];
+                                j += 1;
+                            }
+                            i += 1;
+                        }
+                        bytes
+                    },
+                )
             }
-            #doc
-            #[doc = #location]
-            #vis const fn #ident(&self) -> #ty {
-                let this = (self.0 >> #offset) & #mask;
-                #from
+        };
+        code.extend(quote! {
+            #[doc = #from_doc]
+            #vis const fn #from(bytes: [u8; #size]) -> Self {
+                #from_body
             }
-            #doc
-            #[doc = #location]
-            #[cfg_attr(debug_assertions, track_caller)]
-            #vis fn #set_ident(&mut self, value: #ty) {
-                *self = self.#with_ident(value);
+            #[doc = #to_doc]
+            #vis const fn #to(self) -> [u8; #size] {
+                #to_body
             }
+        });
+    }
+    code
+}
 
-        };
-        tokens.extend(code);
+/// Returns the atomic counterpart of the unsigned integer storage,
+/// and the `target_has_atomic` value it requires.
+fn atomic_type(storage: &Storage, int: &syn::Type) -> Option<(TokenStream, &'static str)> {
+    if !matches!(storage, Storage::Int) {
+        return None;
     }
+    let syn::Type::Path(syn::TypePath { path, .. }) = int else {
+        return None;
+    };
+    let (atomic, width) = match path.get_ident()?.to_string().as_str() {
+        "u8" => (format_ident!("AtomicU8"), "8"),
+        "u16" => (format_ident!("AtomicU16"), "16"),
+        "u32" => (format_ident!("AtomicU32"), "32"),
+        "u64" => (format_ident!("AtomicU64"), "64"),
+        "usize" => (format_ident!("AtomicUsize"), "ptr"),
+        _ => return None,
+    };
+    Some((quote!(core::sync::atomic::#atomic), width))
+}
+
+/// Converts a `snake_case` name into `UpperCamelCase`.
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// Formats a mask of the bits in `ranges` as hex, even if it exceeds 128 bits.
+fn hex_mask(ranges: &[Range<usize>]) -> String {
+    let end = ranges.iter().map(|r| r.end).max().unwrap_or_default();
+    let digits: String = (0..end.div_ceil(4))
+        .rev()
+        .map(|digit| {
+            let nibble = (0..4)
+                .filter(|bit| ranges.iter().any(|r| r.contains(&(digit * 4 + bit))))
+                .fold(0, |nibble, bit| nibble | 1 << bit);
+            char::from_digit(nibble, 16).unwrap_or('0')
+        })
+        .collect();
+    format!("0x{}", digits.trim_start_matches('0'))
+}
+
+/// Formats a default for the docs, with literals as their values (`0x10u8` as `16`).
+fn doc_default(default: &TokenStream) -> String {
+    let literal = |lit: &syn::Lit| match lit {
+        syn::Lit::Int(int) => Some(int.base10_digits().to_string()),
+        syn::Lit::Float(float) => Some(float.base10_digits().to_string()),
+        syn::Lit::Bool(bool) => Some(bool.value.to_string()),
+        _ => None,
+    };
+    let value = match syn::parse2::<syn::Expr>(default.clone()) {
+        Ok(syn::Expr::Lit(syn::ExprLit { lit, .. })) => literal(&lit),
+        Ok(syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        })) => match &*expr {
+            syn::Expr::Lit(syn::ExprLit { lit, .. }) => literal(lit).map(|lit| format!("-{lit}")),
+            _ => None,
+        },
+        _ => None,
+    };
+    value.unwrap_or_else(|| default.to_string().replace(" :: ", "::"))
+}
+
+/// Returns a literal with the lower `bits` set.
+fn mask(bits: usize) -> syn::LitInt {
+    let mask = u128::MAX >> (u128::BITS - bits as u32);
+    syn::LitInt::new(&format!("0x{mask:x}"), Span::mixed_site())
 }
 
 /// Distinguish between different types for code generation.
@@ -613,52 +6457,133 @@ enum TypeClass {
 /// Field information, including the `bits` attribute
 struct Field {
     bits: usize,
+    /// Fixed position of the field
+    offset: Option<usize>,
+    /// Fixed bit ranges of a split field
+    parts: Option<Vec<Range<usize>>>,
     ty: syn::Type,
 
     default: TokenStream,
+    /// Whether the default was given in the `bits` attribute
+    explicit_default: bool,
     into: TokenStream,
     from: TokenStream,
+    /// The conversion into `Option<ty>`, which is `None` for bits that the type rejects
+    try_from: TokenStream,
+    parity: Option<(Parity, Range<usize>)>,
+    redact: bool,
+    access: Access,
+    /// The smallest (only for signed integers) and largest value of integer fields
+    range: Option<(Option<TokenStream>, TokenStream)>,
+    /// The message of the bounds assertion
+    bounds_message: String,
+    overflow: Overflow,
+    aliases: Vec<syn::Ident>,
+    views: Vec<View>,
+    /// Whether the field may overlap other fields
+    overlaps: bool,
+    get: Option<syn::Ident>,
+    with: Option<syn::Ident>,
+    set: Option<syn::Ident>,
+    permutation: Permutation,
+    exhaustive: bool,
+    checked: bool,
+    /// The type converted with its `into_bits` and `from_bits`
+    nested: Option<syn::Type>,
+    validate: Option<(syn::Path, String)>,
+    fmt: Option<syn::LitStr>,
 }
 
 /// Parses the `bits` attribute that allows specifying a custom number of bits.
-fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::Result<Field> {
+///
+/// The `path` (`Struct::field`) is used for diagnostics in the generated code.
+// `is_multiple_of` requires Rust 1.87
+#[allow(clippy::manual_is_multiple_of)]
+fn parse_field(
+    attrs: &[syn::Attribute],
+    ty: &syn::Type,
+    ignore: bool,
+    overflow: Overflow,
+    byte_order: Permutation,
+    path: &str,
+) -> syn::Result<Field> {
     fn malformed(mut e: syn::Error, attr: &syn::Attribute) -> syn::Error {
         e.combine(syn::Error::new(attr.span(), "malformed #[bits] attribute"));
         e
     }
 
-    // Defaults for the different types
-    let (class, ty_bits) = type_bits(ty);
-    let mut ret = match class {
-        TypeClass::Bool => Field {
-            bits: ty_bits,
-            ty: ty.clone(),
-            default: quote!(false),
-            into: quote!(this as _),
-            from: quote!(this != 0),
-        },
-        TypeClass::SInt => Field {
-            bits: ty_bits,
-            ty: ty.clone(),
-            default: quote!(0),
-            into: TokenStream::new(),
-            from: TokenStream::new(),
-        },
-        TypeClass::UInt => Field {
-            bits: ty_bits,
-            ty: ty.clone(),
-            default: quote!(0),
-            into: quote!(this as _),
-            from: quote!(this as _),
-        },
-        TypeClass::Other => Field {
-            bits: ty_bits,
-            ty: ty.clone(),
-            default: TokenStream::new(),
-            into: quote!(#ty::into_bits(this)),
-            from: quote!(#ty::from_bits(this)),
-        },
+    // `Option` fields with a raw pattern for `None` convert their inner type
+    let none = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("bits"))
+        .find_map(|a| a.parse_args::<BitsAttr>().ok()?.none);
+    let custom_conversion = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("bits"))
+        .filter_map(|a| a.parse_args::<BitsAttr>().ok())
+        .any(|a| a.into.is_some() || a.from.is_some());
+    let (class, ty_bits, default, into, from) = match &none {
+        Some(_) => {
+            let Some(inner) = option_inner(ty) else {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "'none' requires an `Option` field",
+                ));
+            };
+            let (class, bits, _, into, from) = type_defaults(inner);
+            if class == TypeClass::SInt {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "'none' is not supported for signed integers",
+                ));
+            }
+            (TypeClass::Other, bits, quote!(None), into, from)
+        }
+        // With custom conversions, types named like `u4` are not assumed to be from `arbitrary-int`
+        None if arbitrary_uint_bits(ty).is_some() && custom_conversion => (
+            TypeClass::Other,
+            0,
+            TokenStream::new(),
+            quote!(#ty::into_bits(this) as _),
+            quote!(#ty::from_bits(this as _)),
+        ),
+        None => type_defaults(ty),
+    };
+    let mut ret = Field {
+        bits: ty_bits,
+        offset: None,
+        parts: None,
+        ty: ty.clone(),
+        default,
+        explicit_default: false,
+        into,
+        from,
+        try_from: TokenStream::new(),
+        parity: None,
+        redact: false,
+        access: Access::ReadWrite,
+        range: None,
+        bounds_message: String::new(),
+        overflow,
+        aliases: Vec::new(),
+        views: Vec::new(),
+        overlaps: false,
+        get: None,
+        with: None,
+        set: None,
+        permutation: Permutation::Identity,
+        exhaustive: false,
+        checked: false,
+        nested: None,
+        validate: None,
+        fmt: None,
     };
+    // Whether the value is converted differently than by the defaults for its type
+    let mut converted = false;
+    let mut bcd = false;
+    let mut bounds_message = None;
+    // Whether the field has its own bit or byte order
+    let mut permuted = false;
 
     // Find and parse the bits attribute
     for attr in attrs {
@@ -673,33 +6598,287 @@ fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::R
             let span = tokens.span();
             let BitsAttr {
                 bits,
+                count: _,
+                parts,
+                offset,
                 default,
                 into,
                 from,
+                encoding,
+                parity,
+                over,
+                redact,
+                access,
+                bounds_message: message,
+                overflow,
+                aliases,
+                doc_aliases: _,
+                views,
+                overlaps,
+                none: _,
+                scale,
+                bias,
+                get,
+                with,
+                set,
+                order: _,
+                reverse,
+                endian,
+                exhaustive,
+                checked,
+                validate,
+                computed: _,
+                fmt,
             } = syn::parse2(tokens.clone()).map_err(|e| malformed(e, attr))?;
 
-            if let Some(bits) = bits {
-                if bits == 0 {
-                    return Err(syn::Error::new(span, "bits cannot bit 0"));
+            if let Some(bits) = bits {
+                if bits == 0 {
+                    return Err(syn::Error::new(span, "bits cannot bit 0"));
+                }
+                if ty_bits != 0 && bits > ty_bits {
+                    return Err(syn::Error::new(span, "overflowing field type"));
+                }
+                ret.bits = bits;
+            }
+            if offset.is_some() {
+                ret.offset = offset;
+            }
+            if let Some(parts) = parts {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "split ranges are not supported on padding",
+                    ));
+                }
+                ret.parts = Some(parts);
+            }
+            if ignore && (into.is_some() || from.is_some()) {
+                return Err(syn::Error::new(
+                    default.span(),
+                    "'into' and 'from' are not supported on padding",
+                ));
+            }
+
+            if scale.is_some() || bias.is_some() {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'scale' and 'bias' are not supported on padding",
+                    ));
+                }
+                if into.is_some() || from.is_some() || encoding.is_some() || none.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "'scale' and 'bias' cannot be combined with other conversions",
+                    ));
+                }
+                converted = true;
+                let scale = scale.map_or_else(|| quote!(1.0), ToTokens::into_token_stream);
+                let bias = bias.map_or_else(|| quote!(0.0), ToTokens::into_token_stream);
+                // Quantize to the nearest step, values below the bias saturate to zero
+                ret.into = quote!(((this - #bias) / #scale + 0.5) as _);
+                ret.from = quote!(this as #ty * #scale + #bias);
+                ret.default = bias;
+            }
+
+            if let Some(encoding) = encoding {
+                if none.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "'encoding' cannot be combined with 'none'",
+                    ));
+                }
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "encodings are not supported on padding",
+                    ));
+                }
+                if into.is_some() || from.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "'encoding' cannot be combined with 'into' or 'from'",
+                    ));
+                }
+                converted = true;
+                match encoding {
+                    Encoding::Bcd => {
+                        bcd = true;
+                        if class != TypeClass::UInt {
+                            return Err(syn::Error::new(
+                                span,
+                                "bcd encoding is only supported for unsigned integers",
+                            ));
+                        }
+                    }
+                }
+            }
+
+            match (parity, over) {
+                (Some(parity), Some(over)) => {
+                    if ignore || class != TypeClass::Bool || ret.bits != 1 {
+                        return Err(syn::Error::new(
+                            span,
+                            "parity is only supported on 1 bit bool fields",
+                        ));
+                    }
+                    ret.parity = Some((parity, over));
+                }
+                (None, None) => {}
+                _ => {
+                    return Err(syn::Error::new(
+                        span,
+                        "'parity' and 'over' have to be specified together",
+                    ))
+                }
+            }
+
+            if let Some(message) = message {
+                bounds_message = Some(message.value());
+            }
+
+            if ignore && !aliases.is_empty() {
+                return Err(syn::Error::new(
+                    span,
+                    "aliases are not supported on padding",
+                ));
+            }
+            for alias in aliases {
+                ret.aliases
+                    .push(alias.parse().map_err(|e| malformed(e, attr))?);
+            }
+
+            if ignore && !views.is_empty() {
+                return Err(syn::Error::new(span, "views are not supported on padding"));
+            }
+            ret.views.extend(views);
+
+            if overlaps {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'overlaps' is not supported on padding",
+                    ));
+                }
+                if default.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "'overlaps' fields take their default from the fields they alias",
+                    ));
+                }
+                ret.overlaps = true;
+            }
+
+            if get.is_some() || with.is_some() || set.is_some() {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "accessor names are not supported on padding",
+                    ));
+                }
+                ret.get = get.or(ret.get);
+                ret.with = with.or(ret.with);
+                ret.set = set.or(ret.set);
+            }
+
+            if redact {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'redact' is not supported on padding",
+                    ));
+                }
+                ret.redact = true;
+            }
+
+            if exhaustive {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'exhaustive' is not supported on padding",
+                    ));
                 }
-                if ty_bits != 0 && bits > ty_bits {
-                    return Err(syn::Error::new(span, "overflowing field type"));
+                ret.exhaustive = true;
+            }
+
+            if let Some(validate) = validate {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'validate' is not supported on padding",
+                    ));
                 }
-                ret.bits = bits;
+                ret.validate = Some((validate, String::new()));
             }
-            if ignore && (into.is_some() || from.is_some()) {
-                return Err(syn::Error::new(
-                    default.span(),
-                    "'into' and 'from' are not supported on padding",
-                ));
+
+            if let Some(fmt) = fmt {
+                if ignore {
+                    return Err(syn::Error::new(span, "'fmt' is not supported on padding"));
+                }
+                ret.fmt = Some(fmt);
+            }
+
+            if checked {
+                if class != TypeClass::Other || none.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "'checked' requires a custom field type",
+                    ));
+                }
+                ret.checked = true;
+            }
+
+            if reverse || endian.is_some() {
+                permuted = true;
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'reverse' and 'endian' are not supported on padding",
+                    ));
+                }
+                ret.permutation = match (reverse, endian) {
+                    (true, Some(Permutation::SwapBytes)) => {
+                        return Err(syn::Error::new(
+                            span,
+                            "'reverse' cannot be combined with 'endian = big'",
+                        ))
+                    }
+                    (true, _) => Permutation::ReverseBits,
+                    (false, endian) => endian.unwrap_or(Permutation::Identity),
+                };
+            }
+
+            if let Some(overflow) = overflow {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'overflow' is not supported on padding",
+                    ));
+                }
+                ret.overflow = overflow;
+            }
+
+            if let Some(access) = access {
+                if ignore {
+                    return Err(syn::Error::new(
+                        span,
+                        "'access' is not supported on padding",
+                    ));
+                }
+                if parity.is_some() && !access.read() {
+                    return Err(syn::Error::new(span, "parity fields have to be readable"));
+                }
+                ret.access = access;
             }
 
             if let Some(into) = into {
+                converted = true;
                 ret.into = quote!(#into(this));
             }
             if let Some(from) = from {
+                converted = true;
                 // Auto-conversion from zero
-                if default.is_none() {
+                if default.is_none() && none.is_none() {
                     ret.default = quote!(#from(0));
                 }
 
@@ -707,6 +6886,7 @@ fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::R
             }
             if let Some(default) = default {
                 ret.default = default.into_token_stream();
+                ret.explicit_default = true;
             }
         }
     }
@@ -717,18 +6897,168 @@ fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::R
             "Custom types and isize/usize require the size in the #[bits] attribute",
         ));
     }
+    if ret.exhaustive && ret.bits > 16 {
+        return Err(syn::Error::new(
+            ty.span(),
+            "exhaustiveness checks are limited to 16 bits",
+        ));
+    }
+    // Multi-byte fields follow the byte order of the bitfield
+    if !permuted && !ignore && ret.bits > 8 && ret.bits % 8 == 0 {
+        ret.permutation = byte_order;
+    }
+    if ret.permutation == Permutation::SwapBytes && ret.bits % 8 != 0 {
+        return Err(syn::Error::new(
+            ty.span(),
+            "big endian fields have to consist of whole bytes",
+        ));
+    }
+
+    // The range of values that fit into the field
+    let mut bcd_max = 0u128;
+    if bcd {
+        // every nibble holds a decimal digit, the last one might be truncated
+        let mut scale = 1u128;
+        for nibble in (0..ret.bits).step_by(4) {
+            let digit = (1u128 << (ret.bits - nibble).min(4)) - 1;
+            bcd_max += digit.min(9) * scale;
+            scale *= 10;
+        }
+    }
+    if !ignore && !converted {
+        let inner = if none.is_some() {
+            option_inner(ty)
+        } else {
+            Some(ty)
+        };
+        ret.nested = inner.filter(|ty| bits_convertible(ty)).cloned();
+    }
+    if !ignore && (!converted || bcd) {
+        let full = ret.bits == ty_bits;
+        ret.range = match class {
+            TypeClass::UInt if bcd => {
+                let max = syn::LitInt::new(&bcd_max.to_string(), Span::mixed_site());
+                Some((None, max.into_token_stream()))
+            }
+            TypeClass::UInt if full => Some((None, quote!(#ty::MAX))),
+            TypeClass::UInt => Some((None, mask(ret.bits).into_token_stream())),
+            TypeClass::SInt if full => Some((Some(quote!(#ty::MIN)), quote!(#ty::MAX))),
+            TypeClass::SInt => {
+                let max = u128::MAX >> (u128::BITS - ret.bits as u32 + 1);
+                let min = syn::LitInt::new(&(max + 1).to_string(), Span::mixed_site());
+                let max = syn::LitInt::new(&max.to_string(), Span::mixed_site());
+                Some((Some(quote!(-#min)), max.into_token_stream()))
+            }
+            _ => None,
+        };
+    }
+
+    let raw_max = u128::MAX >> (u128::BITS - ret.bits as u32);
+    ret.bounds_message = bounds_message.unwrap_or_else(|| {
+        let bounds = match class {
+            TypeClass::UInt if bcd => format!("max {bcd_max}"),
+            TypeClass::SInt if !converted => {
+                let max = raw_max >> 1;
+                format!("range -{}..={max}", max + 1)
+            }
+            TypeClass::UInt | TypeClass::Bool if !converted => format!("max {raw_max}"),
+            _ => format!("max raw value {raw_max:#x}"),
+        };
+        format!("{path}: value out of bounds ({bounds})")
+    });
+    let message = &ret.bounds_message;
+    if let Some((validate, message)) = &mut ret.validate {
+        let name = validate.to_token_stream().to_string().replace(' ', "");
+        *message = format!("{path}: value rejected by `{name}`");
+    }
+
+    // Types with their own conversion reject unknown patterns without panicking
+    if let Some(nested) = &ret.nested {
+        ret.try_from = quote!(<#nested>::try_from_bits(this as _));
+    }
+
+    if let Some(none) = none {
+        let collision = format!("{path}: value collides with the `None` pattern");
+        let check = ret.overflow.assert(quote!(raw != #none), &collision);
+        let (into, from) = (&ret.into, &ret.from);
+        ret.into = quote! {
+            match this {
+                Some(this) => {
+                    let raw = { #into };
+                    #check
+                    raw
+                }
+                None => #none,
+            }
+        };
+        ret.from = quote! {
+            if this == #none {
+                None
+            } else {
+                Some({ #from })
+            }
+        };
+        if !ret.try_from.is_empty() {
+            let try_from = &ret.try_from;
+            ret.try_from = quote! {
+                if this == #none {
+                    Some(None)
+                } else {
+                    match #try_from {
+                        Some(value) => Some(Some(value)),
+                        None => None,
+                    }
+                }
+            };
+        }
+    }
+
+    if bcd {
+        let check_shift = ret.overflow.assert(quote!(shift < #ty::BITS), message);
+        // Split the binary value into decimal digits, one per nibble
+        ret.into = quote! {{
+            let mut bin = this;
+            let mut bcd: u128 = 0;
+            let mut shift = 0;
+            while bin != 0 {
+                #check_shift
+                bcd |= ((bin % 10) as u128) << shift;
+                bin /= 10;
+                shift += 4;
+            }
+            bcd as _
+        }};
+        // Join the nibbles back into a binary value
+        ret.from = quote! {{
+            let mut bcd = this;
+            let mut bin = 0;
+            let mut scale = 1;
+            while bcd != 0 {
+                bin += (bcd & 0xf) * scale;
+                bcd >>= 4;
+                scale *= 10;
+            }
+            bin as _
+        }};
+    }
 
     // Signed integers need some special handling...
     if !ignore && class == TypeClass::SInt {
         let bits = ret.bits as u32;
-        let mask = u128::MAX >> (u128::BITS - ret.bits as u32);
-        let mask = syn::LitInt::new(&format!("0x{mask:x}"), Span::mixed_site());
+        let mask = mask(ret.bits);
+        // Excluding the sign bit
+        let value_mask = syn::LitInt::new(&format!("{:#x}", raw_max >> 1), Span::mixed_site());
         if ret.into.is_empty() {
             // Bounds check and remove leading ones from negative values
+            let check = ret.overflow.assert(
+                quote!(if this >= 0 { this & !#value_mask == 0 } else { !this & !#value_mask == 0 }),
+                message,
+            );
+            // The unsigned counterpart, as the mask does not fit the signed type
+            let unsigned = format_ident!("u{}", &ty.to_token_stream().to_string()[1..]);
             ret.into = quote! {{
-                #[allow(unused_comparisons)]
-                debug_assert!(if this >= 0 { this & !#mask == 0 } else { !this & !#mask == 0 }, "value out of bounds");
-                (this & #mask) as _
+                #check
+                (this as #unsigned & #mask) as _
             }};
         }
         if ret.from.is_empty() {
@@ -739,6 +7069,10 @@ fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::R
             }};
         }
     }
+    if ret.try_from.is_empty() {
+        let from = &ret.from;
+        ret.try_from = quote!(Some({ #from }));
+    }
 
     Ok(ret)
 }
@@ -746,30 +7080,364 @@ fn parse_field(attrs: &[syn::Attribute], ty: &syn::Type, ignore: bool) -> syn::R
 /// The bits attribute of the fields of a bitfield struct
 struct BitsAttr {
     bits: Option<usize>,
+    /// Number of elements of field arrays, given as `bits * count`
+    count: Option<usize>,
+    /// The bit ranges of a field that is split over non-contiguous bits
+    parts: Option<Vec<Range<usize>>>,
+    /// Fixed position of the field, independent of the other fields
+    offset: Option<usize>,
     default: Option<syn::Expr>,
     into: Option<syn::Path>,
     from: Option<syn::Path>,
+    encoding: Option<Encoding>,
+    parity: Option<Parity>,
+    over: Option<Range<usize>>,
+    redact: bool,
+    access: Option<Access>,
+    bounds_message: Option<syn::LitStr>,
+    overflow: Option<Overflow>,
+    aliases: Vec<syn::LitStr>,
+    /// Names for the `#[doc(alias)]` of the accessors, like in a datasheet
+    doc_aliases: Vec<syn::LitStr>,
+    views: Vec<View>,
+    /// The field intentionally shares its bits with other fields
+    overlaps: bool,
+    /// The raw pattern that `Option` fields decode to `None`
+    none: Option<syn::Expr>,
+    /// The weight of the least significant bit of floating point fields
+    scale: Option<syn::Expr>,
+    /// The value of floating point fields for the raw zero
+    bias: Option<syn::Expr>,
+    /// Custom names of the getter, `with_` and `set_` functions
+    get: Option<syn::Ident>,
+    with: Option<syn::Ident>,
+    set: Option<syn::Ident>,
+    /// Places the field from the other end than the bitfield `order`
+    order: Option<Order>,
+    /// Stores the bits of the field in reverse order
+    reverse: bool,
+    /// Stores the bytes of the field in reverse order if big endian
+    endian: Option<Permutation>,
+    /// Checks at compile time that every bit pattern can be converted
+    exhaustive: bool,
+    /// Checks at compile time that the `BITS` of the field type fit into the field
+    checked: bool,
+    /// A `const fn(&T) -> bool` that has to accept the values of the field
+    validate: Option<syn::Path>,
+    /// The expression of a field without bits, which only has a getter
+    computed: Option<syn::Expr>,
+    /// The format string in `Debug`, also given as `hex`, `binary`, or `octal`
+    fmt: Option<syn::LitStr>,
+}
+
+/// A view of the whole bitfield as another type, selected by the value of a field
+#[derive(Clone)]
+struct View {
+    pat: syn::Pat,
+    ident: syn::Ident,
+    ty: syn::Type,
+}
+
+impl Parse for View {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pat = syn::Pat::parse_single(input)?;
+        <Token![=>]>::parse(input)?;
+        let ident = input.parse()?;
+        <Token![:]>::parse(input)?;
+        let ty = input.parse()?;
+        Ok(Self { pat, ident, ty })
+    }
+}
+
+/// Alternative representations of a field value in its bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// Packed binary-coded decimal, one decimal digit per nibble
+    Bcd,
+}
+
+/// The error type of the fallible functions
+#[derive(Clone)]
+struct ErrorTy {
+    /// The generated error enum
+    name: Ident,
+    /// A custom error type, which is converted from the generated one
+    custom: Option<syn::Path>,
+}
+
+impl ErrorTy {
+    /// The type in the signatures.
+    fn ty(&self) -> TokenStream {
+        match &self.custom {
+            Some(custom) => custom.to_token_stream(),
+            None => self.name.to_token_stream(),
+        }
+    }
+    /// Creates the error `variant`, converted into the custom type.
+    fn variant(&self, variant: &str) -> TokenStream {
+        let name = &self.name;
+        let variant = format_ident!("{variant}");
+        match &self.custom {
+            Some(custom) => quote!(<#custom as From<#name>>::from(#name::#variant)),
+            None => quote!(#name::#variant),
+        }
+    }
+    /// The conversion into a custom type cannot be `const`.
+    fn constness(&self) -> Option<TokenStream> {
+        self.custom.is_none().then(|| quote!(const))
+    }
+    /// The definition of the generated error enum.
+    fn definition(&self, vis: &syn::Visibility, bitfield: &Ident) -> TokenStream {
+        let name = &self.name;
+        let doc = format!("The errors of the fallible functions of [`{bitfield}`].");
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[allow(dead_code)]
+            #vis enum #name {
+                /// A value does not fit into its field or is rejected by its validation.
+                OutOfBounds,
+                /// The field cannot be written.
+                ReadOnly,
+                /// The resulting bitfield is not valid, like a zero `NonZero` bitfield.
+                Invalid,
+                /// The text does not match the `Display` format.
+                Parse,
+            }
+
+            impl core::fmt::Display for #name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(match self {
+                        Self::OutOfBounds => "value out of bounds",
+                        Self::ReadOnly => "field is read-only",
+                        Self::Invalid => "invalid bitfield",
+                        Self::Parse => "invalid format",
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// The order in which the raw bits of a field are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Permutation {
+    /// Unchanged
+    Identity,
+    /// With the bits in reverse order
+    ReverseBits,
+    /// With the bytes in reverse order, for big endian fields
+    SwapBytes,
+}
+
+impl Permutation {
+    /// The integer method that applies the permutation, which is its own inverse
+    fn method(self) -> Option<Ident> {
+        match self {
+            Permutation::Identity => None,
+            Permutation::ReverseBits => Some(format_ident!("reverse_bits")),
+            Permutation::SwapBytes => Some(format_ident!("swap_bytes")),
+        }
+    }
+}
+
+/// The kind of parity computed by a parity field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parity {
+    /// The number of ones, including the parity bit, is even
+    Even,
+    /// The number of ones, including the parity bit, is odd
+    Odd,
+}
+
+/// What the setters do with values that do not fit into the field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overflow {
+    /// Panic in debug builds and truncate in release builds (default)
+    DebugPanic,
+    /// Always panic
+    Panic,
+    /// Discard the bits that do not fit
+    Truncate,
+    /// Clamp to the range of the field
+    Saturate,
+}
+
+impl Overflow {
+    /// Asserts the `cond`ition, as far as this overflow behavior panics.
+    fn assert(self, cond: TokenStream, message: &str) -> TokenStream {
+        match self {
+            Overflow::DebugPanic => quote! {
+                #[allow(unused_comparisons)]
+                debug_assert!(#cond, #message);
+            },
+            Overflow::Panic => quote! {
+                #[allow(unused_comparisons)]
+                assert!(#cond, #message);
+            },
+            Overflow::Truncate | Overflow::Saturate => TokenStream::new(),
+        }
+    }
+}
+
+impl Parse for Overflow {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let value = syn::Ident::parse(input)?;
+        Ok(match value.to_string().as_str() {
+            "panic" => Overflow::Panic,
+            "truncate" => Overflow::Truncate,
+            "saturate" => Overflow::Saturate,
+            _ => return Err(syn::Error::new(value.span(), "unknown overflow behavior")),
+        })
+    }
+}
+
+/// Which accessors are generated for a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    /// Getter, setter and builder (`RW`)
+    ReadWrite,
+    /// Only the getter (`RO`)
+    ReadOnly,
+    /// Only the setter and builder (`WO`)
+    WriteOnly,
+    /// The getter and functions that write ones to clear the field (`W1C`)
+    WriteOneToClear,
+    /// Only `take_<field>`, which clears the field when reading it (`RC`)
+    ReadToClear,
+}
+
+impl Access {
+    /// The name of the access mode in the bits attribute.
+    fn name(self) -> &'static str {
+        match self {
+            Access::ReadWrite => "RW",
+            Access::ReadOnly => "RO",
+            Access::WriteOnly => "WO",
+            Access::WriteOneToClear => "W1C",
+            Access::ReadToClear => "RC",
+        }
+    }
+    fn read(self) -> bool {
+        matches!(
+            self,
+            Access::ReadWrite | Access::ReadOnly | Access::WriteOneToClear
+        )
+    }
+    fn write(self) -> bool {
+        matches!(self, Access::ReadWrite | Access::WriteOnly)
+    }
 }
 
 impl Parse for BitsAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut attr = Self {
             bits: None,
+            count: None,
+            parts: None,
+            offset: None,
             default: None,
             into: None,
             from: None,
+            encoding: None,
+            parity: None,
+            over: None,
+            redact: false,
+            access: None,
+            bounds_message: None,
+            overflow: None,
+            aliases: Vec::new(),
+            doc_aliases: Vec::new(),
+            views: Vec::new(),
+            overlaps: false,
+            none: None,
+            scale: None,
+            bias: None,
+            get: None,
+            with: None,
+            set: None,
+            order: None,
+            reverse: false,
+            endian: None,
+            exhaustive: false,
+            checked: false,
+            validate: None,
+            computed: None,
+            fmt: None,
         };
-        if let Ok(bits) = syn::LitInt::parse(input) {
+        if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let mut parts = Vec::new();
+            while !content.is_empty() {
+                parts.push(parse_range(&content)?);
+                if content.is_empty() {
+                    break;
+                }
+                <Token![,]>::parse(&content)?;
+            }
+            let Some(first) = parts.first() else {
+                return Err(syn::Error::new(input.span(), "empty list of bit ranges"));
+            };
+            attr.offset = Some(first.start);
+            attr.bits = Some(parts.iter().map(ExactSizeIterator::len).sum());
+            attr.parts = Some(parts);
+            if !input.is_empty() {
+                <Token![,]>::parse(input)?;
+            }
+        } else if input.peek(syn::LitInt) && input.peek2(Token![..]) {
+            let range = parse_range(input)?;
+            attr.offset = Some(range.start);
+            attr.bits = Some(range.len());
+            if !input.is_empty() {
+                <Token![,]>::parse(input)?;
+            }
+        } else if let Ok(bits) = syn::LitInt::parse(input) {
             attr.bits = Some(bits.base10_parse()?);
+            if input.peek(Token![*]) {
+                <Token![*]>::parse(input)?;
+                attr.count = Some(syn::LitInt::parse(input)?.base10_parse()?);
+            }
             if !input.is_empty() {
                 <Token![,]>::parse(input)?;
             }
+        } else if !input.is_empty()
+            && !(input.peek(syn::Ident) && (input.peek2(Token![=]) || is_flag(input)))
+        {
+            // The layout is computed during expansion, when constants are not known yet
+            let width = syn::Expr::parse(input)?;
+            return Err(syn::Error::new(
+                width.span(),
+                "the bit width has to be an integer literal, which can be compared to \
+                constants with `const _: () = assert!(Struct::FIELD_BITS == WIDTH);`",
+            ));
         }
         // parse remainder
         if !input.is_empty() {
             loop {
                 let ident = syn::Ident::parse(input)?;
 
+                // flags without value
+                if FLAGS.iter().any(|f| ident == f) {
+                    let value = parse_flag(input)?;
+                    if ident == "redact" {
+                        attr.redact = value;
+                    } else if ident == "overlaps" {
+                        attr.overlaps = value;
+                    } else if ident == "reverse" {
+                        attr.reverse = value;
+                    } else if ident == "checked" {
+                        attr.checked = value;
+                    } else {
+                        attr.exhaustive = value;
+                    }
+                    if input.is_empty() {
+                        break;
+                    }
+                    <Token![,]>::parse(input)?;
+                    continue;
+                }
+
                 <Token![=]>::parse(input)?;
 
                 if ident == "default" {
@@ -778,6 +7446,81 @@ impl Parse for BitsAttr {
                     attr.into = Some(input.parse()?);
                 } else if ident == "from" {
                     attr.from = Some(input.parse()?);
+                } else if ident == "endian" {
+                    let value = syn::Ident::parse(input)?;
+                    attr.endian = Some(match value.to_string().as_str() {
+                        "big" | "Big" => Permutation::SwapBytes,
+                        "little" | "Little" => Permutation::Identity,
+                        _ => return Err(syn::Error::new(value.span(), "unknown endianness")),
+                    });
+                } else if ident == "encoding" {
+                    let value = syn::Ident::parse(input)?;
+                    attr.encoding = Some(match value.to_string().as_str() {
+                        "bcd" | "Bcd" => Encoding::Bcd,
+                        _ => return Err(syn::Error::new(value.span(), "unknown encoding")),
+                    });
+                } else if ident == "parity" {
+                    let value = syn::Ident::parse(input)?;
+                    attr.parity = Some(match value.to_string().as_str() {
+                        "even" | "Even" => Parity::Even,
+                        "odd" | "Odd" => Parity::Odd,
+                        _ => return Err(syn::Error::new(value.span(), "unknown parity")),
+                    });
+                } else if ident == "access" {
+                    let value = syn::Ident::parse(input)?;
+                    attr.access = Some(match value.to_string().as_str() {
+                        "RW" => Access::ReadWrite,
+                        "RO" => Access::ReadOnly,
+                        "WO" => Access::WriteOnly,
+                        "W1C" => Access::WriteOneToClear,
+                        "RC" => Access::ReadToClear,
+                        _ => return Err(syn::Error::new(value.span(), "unknown access mode")),
+                    });
+                } else if ident == "over" {
+                    attr.over = Some(parse_range(input)?);
+                } else if ident == "offset" {
+                    attr.offset = Some(syn::LitInt::parse(input)?.base10_parse()?);
+                } else if ident == "validate" {
+                    attr.validate = Some(input.parse()?);
+                } else if ident == "computed" {
+                    attr.computed = Some(input.parse()?);
+                } else if ident == "fmt" {
+                    attr.fmt = Some(if input.peek(syn::LitStr) {
+                        input.parse()?
+                    } else {
+                        let value = syn::Ident::parse(input)?;
+                        let fmt = match value.to_string().as_str() {
+                            "hex" => "{:#x}",
+                            "binary" => "{:#b}",
+                            "octal" => "{:#o}",
+                            _ => return Err(syn::Error::new(value.span(), "unknown format")),
+                        };
+                        syn::LitStr::new(fmt, value.span())
+                    });
+                } else if ident == "bounds_message" {
+                    attr.bounds_message = Some(input.parse()?);
+                } else if ident == "overflow" {
+                    attr.overflow = Some(input.parse()?);
+                } else if ident == "alias" {
+                    attr.aliases.push(input.parse()?);
+                } else if ident == "doc_alias" {
+                    attr.doc_aliases.push(input.parse()?);
+                } else if ident == "view" {
+                    attr.views.push(input.parse()?);
+                } else if ident == "none" {
+                    attr.none = Some(input.parse()?);
+                } else if ident == "scale" {
+                    attr.scale = Some(input.parse()?);
+                } else if ident == "bias" {
+                    attr.bias = Some(input.parse()?);
+                } else if ident == "get" {
+                    attr.get = Some(input.parse()?);
+                } else if ident == "with" {
+                    attr.with = Some(input.parse()?);
+                } else if ident == "set" {
+                    attr.set = Some(input.parse()?);
+                } else if ident == "order" {
+                    attr.order = Some(input.parse()?);
                 }
 
                 if input.is_empty() {
@@ -791,19 +7534,482 @@ impl Parse for BitsAttr {
     }
 }
 
+/// Whether the input starts with a flag without value, like `redact`
+fn is_flag(input: ParseStream) -> bool {
+    let fork = input.fork();
+    syn::Ident::parse(&fork).is_ok_and(|ident| {
+        FLAGS.iter().any(|flag| ident == flag) && (fork.is_empty() || fork.peek(Token![,]))
+    })
+}
+
+/// Field options that can be given without value
+const FLAGS: [&str; 5] = ["redact", "overlaps", "reverse", "exhaustive", "checked"];
+
+/// Parses an optional `= <bool>` after a flag, which is `true` if omitted
+fn parse_flag(input: ParseStream) -> syn::Result<bool> {
+    if input.peek(Token![=]) {
+        <Token![=]>::parse(input)?;
+        Ok(syn::LitBool::parse(input)?.value)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Returns the expression of a `computed` field
+fn field_computed(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Expr>> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("bits")) {
+        // Malformed attributes are reported when the field is parsed
+        let Ok(bits) = attr.parse_args::<BitsAttr>() else {
+            continue;
+        };
+        let BitsAttr {
+            bits,
+            count,
+            parts,
+            offset,
+            default,
+            into,
+            from,
+            encoding,
+            parity,
+            over,
+            redact,
+            access,
+            bounds_message,
+            overflow,
+            aliases,
+            doc_aliases,
+            views,
+            overlaps,
+            none,
+            scale,
+            bias,
+            get,
+            with,
+            set,
+            order,
+            reverse,
+            endian,
+            exhaustive,
+            checked,
+            validate,
+            computed,
+            fmt,
+        } = bits;
+        let Some(expr) = computed else {
+            continue;
+        };
+        if bits.is_some()
+            || count.is_some()
+            || parts.is_some()
+            || offset.is_some()
+            || default.is_some()
+        {
+            return Err(syn::Error::new(
+                attr.span(),
+                "computed fields cannot have bits of their own",
+            ));
+        }
+        // Only the getter is generated, so the other options would have no effect
+        let flags = [redact, overlaps, reverse, exhaustive, checked];
+        let options = [
+            into.is_some() || from.is_some() || encoding.is_some() || parity.is_some(),
+            over.is_some() || access.is_some() || bounds_message.is_some() || overflow.is_some(),
+            !aliases.is_empty() || !doc_aliases.is_empty() || !views.is_empty(),
+            none.is_some() || scale.is_some() || bias.is_some(),
+            get.is_some() || with.is_some() || set.is_some() || order.is_some(),
+            endian.is_some() || validate.is_some() || fmt.is_some(),
+        ];
+        if flags.into_iter().chain(options).any(|o| o) {
+            return Err(syn::Error::new(
+                attr.span(),
+                "computed fields cannot have other options",
+            ));
+        }
+        return Ok(Some(expr));
+    }
+    Ok(None)
+}
+
+/// Returns the `order` of the `bits` attribute, which overrides the bitfield order
+///
+/// Malformed attributes are reported when the field is parsed.
+fn field_order(attrs: &[syn::Attribute]) -> Option<Order> {
+    attrs
+        .iter()
+        .rev()
+        .filter(|a| a.path().is_ident("bits"))
+        .find_map(|a| a.parse_args::<BitsAttr>().ok()?.order)
+}
+
+/// Parses a literal bit range (`start..end` or `start..=end`)
+fn parse_range(input: ParseStream) -> syn::Result<Range<usize>> {
+    let start = syn::LitInt::parse(input)?;
+    let span = start.span();
+    let start = start.base10_parse()?;
+    let end = if input.peek(Token![..=]) {
+        <Token![..=]>::parse(input)?;
+        syn::LitInt::parse(input)?.base10_parse::<usize>()? + 1
+    } else {
+        <Token![..]>::parse(input)?;
+        syn::LitInt::parse(input)?.base10_parse()?
+    };
+    if start >= end {
+        return Err(syn::Error::new(span, "empty bit range"));
+    }
+    Ok(start..end)
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum Order {
     Lsb,
     Msb,
 }
 
+impl Parse for Order {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let value = syn::Ident::parse(input)?;
+        Ok(match value.to_string().as_str() {
+            "Msb" | "msb" => Order::Msb,
+            "Lsb" | "lsb" => Order::Lsb,
+            _ => return Err(syn::Error::new(value.span(), "unknown value for order")),
+        })
+    }
+}
+
+/// The order of the fields in the generated `Debug` output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DebugOrder {
+    /// As declared in the struct
+    Declaration,
+    /// Sorted by bit offset, starting with the least significant bits
+    Offset,
+}
+
+/// How the generated struct stores its bits
+#[derive(Clone)]
+enum Storage {
+    /// An unsigned integer
+    Int,
+    /// A `NonZero` unsigned integer, which must never become zero
+    NonZero,
+    /// The bits of a float, which is only converted with `From`
+    Float(Box<syn::Type>),
+    /// A signed integer, whose bits are handled as the unsigned counterpart
+    Signed {
+        signed: Box<syn::Type>,
+        unsigned: Box<syn::Type>,
+    },
+    /// An array of unsigned integers, the first element holds the least significant bits
+    Array {
+        elem: Box<syn::Type>,
+        elem_bits: usize,
+        len: usize,
+    },
+}
+
+impl Storage {
+    /// Converts the stored `value` into the underlying integer (or array).
+    fn get(&self, value: TokenStream) -> TokenStream {
+        match self {
+            Storage::Int | Storage::Float(_) | Storage::Array { .. } => value,
+            Storage::NonZero => quote!(#value.get()),
+            Storage::Signed { unsigned, .. } => quote!((#value as #unsigned)),
+        }
+    }
+    /// Converts the stored `value` into the type of `into_bits`.
+    fn bits(&self, value: TokenStream) -> TokenStream {
+        match self {
+            Storage::Signed { .. } => value,
+            _ => self.get(value),
+        }
+    }
+    /// Converts the underlying integer `value` into the type of `from_bits`.
+    fn cast_bits(&self, value: TokenStream) -> TokenStream {
+        match self {
+            Storage::Signed { signed, .. } => quote!(((#value) as #signed)),
+            _ => value,
+        }
+    }
+    /// The type of `from_bits` and `into_bits`, the signed integer of signed storages.
+    fn bits_ty(&self, int: &syn::Type) -> syn::Type {
+        match self {
+            Storage::Signed { signed, .. } => (**signed).clone(),
+            _ => int.clone(),
+        }
+    }
+    /// Creates `Self` from the underlying integer (or array) `value`.
+    fn wrap(&self, value: TokenStream) -> TokenStream {
+        match self {
+            Storage::Int | Storage::Float(_) | Storage::Array { .. } => quote!(Self(#value)),
+            Storage::Signed { .. } => quote!(Self(#value as _)),
+            Storage::NonZero => quote! {
+                match Self::new_checked(#value) {
+                    Some(this) => this,
+                    None => panic!("NonZero bitfields must not be zero"),
+                }
+            },
+        }
+    }
+    /// Creates `Ok(Self)` from the underlying `value`, or `Err(error)` if it is not valid.
+    fn try_wrap(&self, value: TokenStream, error: TokenStream) -> TokenStream {
+        match self {
+            Storage::Int | Storage::Float(_) | Storage::Array { .. } => quote!(Ok(Self(#value))),
+            Storage::Signed { .. } => quote!(Ok(Self(#value as _))),
+            Storage::NonZero => quote! {
+                match Self::new_checked(#value) {
+                    Some(this) => Ok(this),
+                    None => Err(#error),
+                }
+            },
+        }
+    }
+    /// The underlying value with all bits cleared.
+    fn zero(&self) -> TokenStream {
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                quote!(0)
+            }
+            Storage::Array { len, .. } => quote!([0; #len]),
+        }
+    }
+    /// The unsigned integer in which the values of a member with `bits` are handled.
+    fn member_ty(&self, int: &syn::Type, bits: usize) -> syn::Type {
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                int.clone()
+            }
+            Storage::Array { .. } => {
+                let bits = bits.max(8).next_power_of_two();
+                syn::Type::Verbatim(format_ident!("u{bits}").into_token_stream())
+            }
+        }
+    }
+    /// The type of the underlying integer (or array), given the type of a member.
+    fn raw_ty(&self, member_ty: &syn::Type) -> TokenStream {
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                member_ty.to_token_stream()
+            }
+            Storage::Array { elem, len, .. } => quote!([#elem; #len]),
+        }
+    }
+    /// The underlying integer (or array) with the bits in `ranges` set.
+    fn mask(&self, ranges: &[Range<usize>]) -> TokenStream {
+        let lit = |value: u128| syn::LitInt::new(&format!("0x{value:x}"), Span::mixed_site());
+        let elem_bits = match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => 128,
+            Storage::Array { elem_bits, .. } => *elem_bits,
+        };
+        let mut elems = vec![0u128; self.len()];
+        for range in ranges.iter().filter(|r| !r.is_empty()) {
+            for (i, shift, bits, _) in array_parts(elem_bits, range.start, range.len()) {
+                elems[i] |= (u128::MAX >> (u128::BITS - bits as u32)) << shift;
+            }
+        }
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                lit(elems[0]).into_token_stream()
+            }
+            Storage::Array { .. } => {
+                let elems = elems.into_iter().map(lit);
+                quote!([#( #elems ),*])
+            }
+        }
+    }
+    /// The number of elements, which is one for integers.
+    fn len(&self) -> usize {
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => 1,
+            Storage::Array { len, .. } => *len,
+        }
+    }
+    /// Extracts `bits` at `offset` of the underlying `value`, as the member type `ty`.
+    fn extract(
+        &self,
+        value: &TokenStream,
+        offset: usize,
+        bits: usize,
+        ty: &syn::Type,
+    ) -> TokenStream {
+        let mask = mask(bits);
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                quote!(((#value >> #offset) & #mask))
+            }
+            Storage::Array { elem_bits, .. } => {
+                let parts = array_parts(*elem_bits, offset, bits).map(|(i, shift, bits, pos)| {
+                    let mask = self::mask(bits);
+                    quote!(((((#value[#i] >> #shift) as #ty) & #mask) << #pos))
+                });
+                quote!((0 #( | #parts )*))
+            }
+        }
+    }
+    /// Replaces `bits` at `offset` of the underlying `this` variable with `value`.
+    fn insert(&self, offset: usize, bits: usize) -> TokenStream {
+        let mask = mask(bits);
+        match self {
+            Storage::Int | Storage::NonZero | Storage::Float(_) | Storage::Signed { .. } => {
+                quote!(this = this & !(#mask << #offset) | (value & #mask) << #offset;)
+            }
+            Storage::Array {
+                elem, elem_bits, ..
+            } => {
+                let parts = array_parts(*elem_bits, offset, bits).map(|(i, shift, bits, pos)| {
+                    let mask = self::mask(bits);
+                    quote! {
+                        this[#i] = this[#i] & !(#mask << #shift)
+                            | (((value >> #pos) as #elem) & #mask) << #shift;
+                    }
+                });
+                quote!(#( #parts )*)
+            }
+        }
+    }
+}
+
+/// Splits the `bits` at `offset` into the parts stored in the array elements.
+///
+/// Returns the element index, the offset and size within the element,
+/// and the position within the member value.
+fn array_parts(
+    elem_bits: usize,
+    offset: usize,
+    bits: usize,
+) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+    let end = offset + bits;
+    (offset / elem_bits..end.div_ceil(elem_bits)).map(move |i| {
+        let start = offset.max(i * elem_bits);
+        let stop = end.min((i + 1) * elem_bits);
+        (i, start - i * elem_bits, stop - start, start - offset)
+    })
+}
+
+/// Whether an optional trait implementation should be generated
+#[derive(Clone)]
+enum Generate {
+    No,
+    Yes,
+    /// Only if the given `cfg` predicate holds
+    Cfg(TokenStream),
+}
+
+impl Generate {
+    fn is_no(&self) -> bool {
+        matches!(self, Generate::No)
+    }
+    /// Returns the code as configured, or nothing.
+    fn wrap(&self, code: TokenStream) -> TokenStream {
+        match self {
+            Generate::No => TokenStream::new(),
+            Generate::Yes => code,
+            Generate::Cfg(cfg) => quote! {
+                #[cfg(#cfg)]
+                #code
+            },
+        }
+    }
+}
+
+impl Parse for Generate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitBool) {
+            let value = syn::LitBool::parse(input)?.value;
+            Ok(if value { Generate::Yes } else { Generate::No })
+        } else {
+            let ident = Ident::parse(input)?;
+            if ident != "cfg" {
+                return Err(syn::Error::new(ident.span(), "expected bool or cfg(...)"));
+            }
+            let content;
+            syn::parenthesized!(content in input);
+            Ok(Generate::Cfg(content.parse()?))
+        }
+    }
+}
+
 /// The bitfield macro parameters
 struct Params {
+    /// The type of the generated struct's field
     ty: syn::Type,
+    /// The unsigned integer that holds the bits
+    int: syn::Type,
+    storage: Storage,
     bits: usize,
     debug: bool,
+    debug_order: DebugOrder,
     default: bool,
     order: Order,
+    zeroize: Generate,
+    slices: bool,
+    alloc: Generate,
+    /// Implement the `bytemuck` traits `Zeroable` and `Pod`
+    bytemuck: Generate,
+    /// Implement `arbitrary::Arbitrary` with valid field values
+    arbitrary: Generate,
+    /// Generate `kani::Arbitrary` and a round trip proof harness
+    kani: bool,
+    /// Implement the SCALE `Encode`, `Decode`, and `MaxEncodedLen` traits
+    scale_codec: Generate,
+    /// Implement `ufmt::uDebug` and `ufmt::uDisplay`
+    ufmt: Generate,
+    /// Generate the svd2rust-style `read`, `write`, and `modify` API
+    svd: bool,
+    /// Generate a trait containing the getters, with an optional custom name
+    fields_trait: Option<Option<Ident>>,
+    /// Generate an enum for dynamic field access, with an optional custom name
+    field_enum: Option<Option<Ident>>,
+    /// Generate an atomic wrapper type
+    atomic: Generate,
+    /// Generate `Display` and `FromStr` implementations
+    display: bool,
+    /// Generate `Binary`, `Octal`, `LowerHex`, and `UpperHex` implementations
+    radix: bool,
+    /// Generate a validating `TryFrom` instead of `From` for the raw type
+    validate: bool,
+    /// Generate `new`, with an optional custom visibility
+    new: Option<Option<syn::Visibility>>,
+    /// Generate a typestate builder, with an optional custom name
+    builder: Option<Option<Ident>>,
+    /// Generate `PartialEq`, `Eq`, and `Hash` that ignore padding
+    eq: bool,
+    /// Fill the bits that are not covered by members with padding
+    pad_auto: bool,
+    /// Generate flag constants and set operations for bitfields of bools
+    flags: bool,
+    /// Generate bitwise operators on the raw value
+    ops: bool,
+    /// Generate the `&mut self` setters, otherwise only `with_` functions
+    setters: bool,
+    /// Add `#[must_use]` to the getters
+    must_use_getters: bool,
+    /// Generate the fallible `try_with_` and `try_set_` functions
+    try_setters: bool,
+    /// Generate the fallible `try_` getters for custom types
+    try_getters: bool,
+    /// The `#[inline]` attribute of the accessors
+    inline: TokenStream,
+    /// Implement `From` (or `TryFrom`) between the bitfield and its raw type
+    conversions: bool,
+    /// The `#[repr]` of the struct instead of `transparent`
+    repr: Vec<syn::Meta>,
+    /// Derive `Copy` in addition to `Clone`
+    copy: bool,
+    /// Append a table of the fields to the documentation
+    doc_table: bool,
+    /// Number the bits from the most significant bit in the constants and documentation
+    msb0: bool,
+    /// The default overflow behavior of the setters
+    overflow: Overflow,
+    /// The byte order of multi-byte fields, which can be overridden per field
+    endian: Permutation,
+    /// A custom error type of the fallible functions, converted from the generated one
+    error: Option<syn::Path>,
+    /// A `const fn(&Self) -> bool` that is checked after modifications
+    invariant: Option<syn::Path>,
+    /// A custom struct for `LAYOUT`, instead of a tuple
+    layout: Option<syn::Path>,
 }
 
 impl Parse for Params {
@@ -811,14 +8017,101 @@ impl Parse for Params {
         let Ok(ty) = syn::Type::parse(input) else {
             return Err(syn::Error::new(input.span(), "unknown type"));
         };
-        let (class, bits) = type_bits(&ty);
-        if class != TypeClass::UInt {
-            return Err(syn::Error::new(input.span(), "unsupported type"));
-        }
+        let (int, storage, bits) = if let Some(bits) = nonzero_bits(&ty) {
+            let int = syn::parse_str(&format!("u{bits}"))?;
+            (int, Storage::NonZero, bits)
+        } else if let Some(bits) = float_bits(&ty) {
+            let int = syn::parse_str(&format!("u{bits}"))?;
+            (int, Storage::Float(Box::new(ty.clone())), bits)
+        } else if let syn::Type::Array(array) = &ty {
+            let (class, elem_bits) = type_bits(&array.elem);
+            if class != TypeClass::UInt || elem_bits == 0 {
+                return Err(syn::Error::new(
+                    array.elem.span(),
+                    "unsupported element type",
+                ));
+            }
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(len),
+                ..
+            }) = &array.len
+            else {
+                return Err(syn::Error::new(
+                    array.len.span(),
+                    "the array length has to be a literal",
+                ));
+            };
+            let len: usize = len.base10_parse()?;
+            let storage = Storage::Array {
+                elem: array.elem.clone(),
+                elem_bits,
+                len,
+            };
+            (ty.clone(), storage, elem_bits * len)
+        } else {
+            let (class, bits) = type_bits(&ty);
+            if bits == 0 {
+                return Err(syn::Error::new(input.span(), "unsupported type"));
+            }
+            match class {
+                TypeClass::UInt => (ty.clone(), Storage::Int, bits),
+                TypeClass::SInt => {
+                    let int: syn::Type = syn::parse_str(&format!("u{bits}"))?;
+                    let storage = Storage::Signed {
+                        signed: Box::new(ty.clone()),
+                        unsigned: Box::new(int.clone()),
+                    };
+                    (int, storage, bits)
+                }
+                _ => return Err(syn::Error::new(input.span(), "unsupported type")),
+            }
+        };
+        // Floats are stored as their bits, so that the accessors can stay `const`
+        let ty = match &storage {
+            Storage::Float(_) => int.clone(),
+            _ => ty,
+        };
 
         let mut debug = true;
+        let mut debug_order = DebugOrder::Declaration;
         let mut default = true;
         let mut order = Order::Lsb;
+        let mut zeroize = Generate::No;
+        let mut bytemuck = Generate::No;
+        let mut arbitrary = Generate::No;
+        let mut kani = false;
+        let mut scale_codec = Generate::No;
+        let mut ufmt = Generate::No;
+        let mut svd = false;
+        let mut slices = false;
+        let mut alloc = Generate::No;
+        let mut fields_trait = None;
+        let mut field_enum = None;
+        let mut atomic = Generate::No;
+        let mut display = false;
+        let mut radix = false;
+        let mut validate = false;
+        let mut new = Some(None);
+        let mut builder = None;
+        let mut eq = false;
+        let mut pad_auto = false;
+        let mut flags = false;
+        let mut ops = false;
+        let mut setters = true;
+        let mut must_use_getters = false;
+        let mut try_setters = false;
+        let mut try_getters = false;
+        let mut inline = TokenStream::new();
+        let mut conversions = true;
+        let mut repr = Vec::new();
+        let mut copy = true;
+        let mut doc_table = false;
+        let mut msb0 = false;
+        let mut overflow = Overflow::DebugPanic;
+        let mut endian = Permutation::Identity;
+        let mut error = None;
+        let mut invariant = None;
+        let mut layout = None;
 
         // try parse additional args
         while <Token![,]>::parse(input).is_ok() {
@@ -829,17 +8122,197 @@ impl Parse for Params {
                     let value = syn::LitBool::parse(input)?.value;
                     debug = value;
                 }
+                "debug_order" => {
+                    let value = match syn::Ident::parse(input)?.to_string().as_str() {
+                        "declaration" => DebugOrder::Declaration,
+                        "offset" => DebugOrder::Offset,
+                        _ => {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                "unknown value for debug_order",
+                            ))
+                        }
+                    };
+                    debug_order = value;
+                }
                 "default" => {
                     let value = syn::LitBool::parse(input)?.value;
                     default = value;
                 }
                 "order" => {
-                    let value = match syn::Ident::parse(input)?.to_string().as_str() {
-                        "Msb" | "msb" => Order::Msb,
-                        "Lsb" | "lsb" => Order::Lsb,
-                        _ => return Err(syn::Error::new(ident.span(), "unknown value for order")),
+                    order = input.parse()?;
+                }
+                "zeroize" => {
+                    zeroize = input.parse()?;
+                }
+                "bytemuck" => {
+                    bytemuck = input.parse()?;
+                }
+                "arbitrary" => {
+                    arbitrary = input.parse()?;
+                }
+                "kani" => {
+                    kani = syn::LitBool::parse(input)?.value;
+                }
+                "svd" => {
+                    svd = syn::LitBool::parse(input)?.value;
+                }
+                "scale_codec" => {
+                    scale_codec = input.parse()?;
+                }
+                "ufmt" => {
+                    ufmt = input.parse()?;
+                }
+                "slices" => {
+                    slices = syn::LitBool::parse(input)?.value;
+                }
+                "alloc" => {
+                    alloc = input.parse()?;
+                }
+                "fields_trait" => {
+                    fields_trait = if input.peek(syn::LitBool) {
+                        syn::LitBool::parse(input)?.value.then_some(None)
+                    } else {
+                        Some(Some(Ident::parse(input)?))
+                    };
+                }
+                "field_enum" => {
+                    field_enum = if input.peek(syn::LitBool) {
+                        syn::LitBool::parse(input)?.value.then_some(None)
+                    } else {
+                        Some(Some(Ident::parse(input)?))
+                    };
+                }
+                "overflow" => {
+                    overflow = input.parse()?;
+                }
+                "error" => {
+                    error = Some(input.parse()?);
+                }
+                "endian" => {
+                    let value = syn::Ident::parse(input)?;
+                    endian = match value.to_string().as_str() {
+                        "big" | "Big" => Permutation::SwapBytes,
+                        "little" | "Little" => Permutation::Identity,
+                        _ => return Err(syn::Error::new(value.span(), "unknown endianness")),
+                    };
+                }
+                "invariant" => {
+                    invariant = Some(input.parse()?);
+                }
+                "layout" => {
+                    layout = Some(input.parse()?);
+                }
+                "atomic" => {
+                    atomic = input.parse()?;
+                }
+                "display" => {
+                    display = syn::LitBool::parse(input)?.value;
+                }
+                "radix" => {
+                    radix = syn::LitBool::parse(input)?.value;
+                }
+                "validate" => {
+                    validate = syn::LitBool::parse(input)?.value;
+                }
+                "pad" => {
+                    pad_auto = match syn::Ident::parse(input)?.to_string().as_str() {
+                        "auto" => true,
+                        "none" => false,
+                        _ => return Err(syn::Error::new(ident.span(), "unknown value for pad")),
+                    };
+                }
+                "ops" => {
+                    ops = syn::LitBool::parse(input)?.value;
+                }
+                "setters" => {
+                    setters = syn::LitBool::parse(input)?.value;
+                }
+                "must_use_getters" => {
+                    must_use_getters = syn::LitBool::parse(input)?.value;
+                }
+                "try_setters" => {
+                    try_setters = syn::LitBool::parse(input)?.value;
+                }
+                "try_getters" => {
+                    try_getters = syn::LitBool::parse(input)?.value;
+                }
+                "conversions" => {
+                    conversions = syn::LitBool::parse(input)?.value;
+                }
+                "copy" => {
+                    copy = syn::LitBool::parse(input)?.value;
+                }
+                "doc_table" => {
+                    doc_table = syn::LitBool::parse(input)?.value;
+                }
+                "msb0" => {
+                    msb0 = syn::LitBool::parse(input)?.value;
+                }
+                "repr" => {
+                    repr = if input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in input);
+                        content
+                            .parse_terminated(syn::Meta::parse, Token![,])?
+                            .into_iter()
+                            .collect()
+                    } else {
+                        vec![input.parse()?]
+                    };
+                    for meta in &repr {
+                        let path = meta.path();
+                        if !(path.is_ident("C")
+                            || path.is_ident("transparent")
+                            || path.is_ident("align"))
+                        {
+                            return Err(syn::Error::new(
+                                meta.span(),
+                                "expected `C`, `transparent`, or `align(N)`",
+                            ));
+                        }
+                    }
+                }
+                "inline" => {
+                    inline = if input.peek(syn::LitBool) {
+                        if syn::LitBool::parse(input)?.value {
+                            quote!(#[inline])
+                        } else {
+                            TokenStream::new()
+                        }
+                    } else {
+                        let value = Ident::parse(input)?;
+                        match value.to_string().as_str() {
+                            "always" => quote!(#[inline(always)]),
+                            "never" => quote!(#[inline(never)]),
+                            _ => {
+                                return Err(syn::Error::new(
+                                    value.span(),
+                                    "expected `true`, `false`, `always`, or `never`",
+                                ))
+                            }
+                        }
+                    };
+                }
+                "flags" => {
+                    flags = syn::LitBool::parse(input)?.value;
+                }
+                "eq" => {
+                    eq = syn::LitBool::parse(input)?.value;
+                }
+                "builder" => {
+                    builder = if input.peek(syn::LitBool) {
+                        syn::LitBool::parse(input)?.value.then_some(None)
+                    } else {
+                        Some(Some(Ident::parse(input)?))
+                    };
+                }
+                "new" => {
+                    new = if input.peek(syn::LitBool) {
+                        syn::LitBool::parse(input)?.value.then_some(None)
+                    } else {
+                        Some(Some(syn::Visibility::parse(input)?))
                     };
-                    order = value;
                 }
                 _ => return Err(syn::Error::new(ident.span(), "unknown argument")),
             };
@@ -847,10 +8320,49 @@ impl Parse for Params {
 
         Ok(Params {
             ty,
+            int,
+            storage,
             bits,
             debug,
+            debug_order,
             default,
             order,
+            zeroize,
+            bytemuck,
+            arbitrary,
+            kani,
+            scale_codec,
+            ufmt,
+            svd,
+            slices,
+            alloc,
+            fields_trait,
+            field_enum,
+            atomic,
+            display,
+            radix,
+            validate,
+            new,
+            builder,
+            eq,
+            pad_auto,
+            flags,
+            ops,
+            setters,
+            must_use_getters,
+            try_setters,
+            try_getters,
+            inline,
+            conversions,
+            repr,
+            copy,
+            doc_table,
+            msb0,
+            overflow,
+            endian,
+            error,
+            invariant,
+            layout,
         })
     }
 }
@@ -881,13 +8393,168 @@ fn type_bits(ty: &syn::Type) -> (TypeClass, usize) {
     integer!(ident => u8, u16, u32, u64, u128 ; i8, i16, i32, i64, i128)
 }
 
+/// Returns if the type is converted with its own `into_bits` and `from_bits`, like nested bitfields
+fn bits_convertible(ty: &syn::Type) -> bool {
+    type_bits(ty).0 == TypeClass::Other
+        && nonzero_field(ty).is_none()
+        && arbitrary_uint_bits(ty).is_none()
+        && !matches!(ty, syn::Type::Path(p) if p.path.is_ident("char"))
+}
+
+/// Returns the class, size, default value, and conversions of a field type
+fn type_defaults(ty: &syn::Type) -> (TypeClass, usize, TokenStream, TokenStream, TokenStream) {
+    let (class, mut ty_bits) = type_bits(ty);
+    let (default, into, from) = if let Some((inner, optional, bits)) = nonzero_field(ty) {
+        ty_bits = bits;
+        let uint = format_ident!("u{bits}");
+        if optional {
+            // Zero is `None`
+            (
+                quote!(None),
+                quote!(match this {
+                    Some(v) => v.get() as _,
+                    None => 0,
+                }),
+                quote!(#inner::new(this as #uint)),
+            )
+        } else {
+            // Stored as `value - 1`, the all-ones pattern of full sized fields saturates
+            (
+                quote!(#inner::MIN),
+                quote!((this.get() - 1) as _),
+                quote!(match #inner::new((this as #uint).wrapping_add(1)) {
+                    Some(v) => v,
+                    None => #inner::MAX,
+                }),
+            )
+        }
+    } else if let Some(bits) = arbitrary_uint_bits(ty) {
+        // Arbitrary width integers, like `u4` of `arbitrary-int`, occupy their own width
+        ty_bits = bits;
+        (
+            quote!(#ty::new(0)),
+            quote!(this.value() as _),
+            quote!(#ty::new(this as _)),
+        )
+    } else if matches!(ty, syn::Type::Path(p) if p.path.is_ident("char")) {
+        // Unicode scalar values have up to 21 bits, invalid ones are replaced
+        ty_bits = 21;
+        (
+            quote!('\0'),
+            quote!(this as u32 as _),
+            quote!(match char::from_u32(this as u32) {
+                Some(c) => c,
+                None => char::REPLACEMENT_CHARACTER,
+            }),
+        )
+    } else {
+        match class {
+            TypeClass::Bool => (quote!(false), quote!(this as _), quote!(this != 0)),
+            TypeClass::SInt => (quote!(0), TokenStream::new(), TokenStream::new()),
+            TypeClass::UInt => (quote!(0), quote!(this as _), quote!(this as _)),
+            TypeClass::Other => (
+                TokenStream::new(),
+                // Signed bits, like those of bitfields with signed storage, must not be sign-extended
+                quote!({
+                    const fn zero_extend<T, R>(_: fn(T) -> R) -> u128 {
+                        u128::MAX >> (128 - core::mem::size_of::<R>() * 8)
+                    }
+                    ((#ty::into_bits(this) as u128) & zero_extend(#ty::into_bits)) as _
+                }),
+                quote!(#ty::from_bits(this as _)),
+            ),
+        }
+    };
+    (class, ty_bits, default, into, from)
+}
+
+/// Returns `T` of `Option<T>`
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Returns the `NonZero` type, whether it is wrapped in an `Option`, and its number of bits
+fn nonzero_field(ty: &syn::Type) -> Option<(syn::Type, bool, usize)> {
+    if let Some(bits) = nonzero_bits(ty) {
+        return Some((ty.clone(), false, bits));
+    }
+    let inner = option_inner(ty)?;
+    Some((inner.clone(), true, nonzero_bits(inner)?))
+}
+
+/// Returns the number of bits for `NonZero` unsigned integers
+fn nonzero_bits(ty: &syn::Type) -> Option<usize> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    if !last.arguments.is_none() {
+        return None;
+    }
+    match last.ident.to_string().as_str() {
+        "NonZeroU8" => Some(8),
+        "NonZeroU16" => Some(16),
+        "NonZeroU32" => Some(32),
+        "NonZeroU64" => Some(64),
+        "NonZeroU128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Returns the number of bits for arbitrary width unsigned integers, like `u4` or `u12`
+fn arbitrary_uint_bits(ty: &syn::Type) -> Option<usize> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    if !last.arguments.is_none() {
+        return None;
+    }
+    let bits: usize = last.ident.to_string().strip_prefix('u')?.parse().ok()?;
+    (bits > 0 && bits < 128 && !matches!(bits, 8 | 16 | 32 | 64)).then_some(bits)
+}
+
+/// Returns the number of bits for floats, which are stored with `to_bits` and `from_bits`
+fn float_bits(ty: &syn::Type) -> Option<usize> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    if path.is_ident("f32") {
+        Some(32)
+    } else if path.is_ident("f64") {
+        Some(64)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use quote::quote;
+    #![allow(clippy::unwrap_used)]
 
-    use crate::{BitsAttr, Order, Params};
+    use quote::{quote, ToTokens};
+
+    use crate::{
+        array_parts, bitfield_inner, doc_default, field_computed, hex_mask, interpolate_doc,
+        Access, BitsAttr, DebugOrder, Encoding, Generate, Order, Overflow, Params, Parity,
+        Permutation,
+    };
 
     #[test]
+    #[allow(clippy::bool_comparison)]
     fn parse_args() {
         let args = quote!(u64);
         let params = syn::parse2::<Params>(args).unwrap();
@@ -900,6 +8567,331 @@ mod test {
         let args = quote!(u32, order = Msb);
         let params = syn::parse2::<Params>(args).unwrap();
         assert!(params.bits == u32::BITS as usize && params.order == Order::Msb);
+
+        let args = quote!(u32, zeroize = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.zeroize, Generate::Yes));
+
+        let args = quote!(u32, zeroize = cfg(feature = "zeroize"));
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.zeroize, Generate::Cfg(_)));
+
+        let args = quote!(u32, bytemuck = cfg(feature = "bytemuck"));
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.bytemuck, Generate::Cfg(_)));
+        let input = quote! {
+            struct Raw {
+                value: u8,
+            }
+        };
+        let output = bitfield_inner(quote!(NonZeroU8, bytemuck = true), input);
+        assert!(output.is_err());
+        let input = quote! {
+            struct Raw {
+                value: u8,
+            }
+        };
+        let output = bitfield_inner(quote!(u8, bytemuck = true, copy = false), input);
+        assert!(output.is_err());
+
+        let args = quote!(u32, arbitrary = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.arbitrary, Generate::Yes));
+
+        let args = quote!(u32, kani = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.kani);
+        for ty in [quote!(u32), quote!([u8; 4])] {
+            let input = quote! {
+                struct Control {
+                    #[bits(4)]
+                    kind: u8,
+                    #[bits(28)]
+                    addr: u32,
+                }
+            };
+            let output = bitfield_inner(quote!(#ty, kani = true), input).unwrap();
+            // The harness is only compiled by kani, so at least check that it parses
+            let items = syn::parse2::<syn::File>(output).unwrap().items;
+            let harness = items.iter().find_map(|item| match item {
+                syn::Item::Fn(f) if f.sig.ident == "kani_control_round_trip" => Some(f),
+                _ => None,
+            });
+            let harness = harness.expect("missing proof harness");
+            assert!(harness.attrs.iter().any(|a| a.path().is_ident("cfg")));
+            assert_eq!(harness.block.stmts.len(), 2);
+            assert!(items.iter().any(|item| matches!(item,
+                syn::Item::Impl(i) if quote!(#i).to_string().contains("kani :: Arbitrary"))));
+        }
+
+        let args = quote!(u32, ufmt = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.ufmt, Generate::Yes));
+
+        let args = quote!(u32, svd = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.svd);
+
+        let args = quote!(u32, scale_codec = cfg(feature = "std"));
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.scale_codec, Generate::Cfg(_)));
+
+        let args = quote!(u32, fields_trait = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.fields_trait, Some(None)));
+
+        let args = quote!(u32, fields_trait = RegFields);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.fields_trait.unwrap().unwrap() == "RegFields");
+
+        let args = quote!(u32, field_enum = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(params.field_enum, Some(None)));
+
+        let args = quote!(u32, field_enum = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.field_enum.is_none());
+
+        let args = quote!(
+            u32,
+            display = true,
+            radix = true,
+            validate = true,
+            eq = true
+        );
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.display && params.radix && params.validate && params.eq);
+
+        let args = quote!(u32, pad = auto);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.pad_auto);
+
+        let args = quote!(u8, flags = true, ops = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.flags && params.ops);
+        for flag in [quote!(default), quote!(bits)] {
+            let input = quote! {
+                struct Perm {
+                    #flag: bool,
+                    #[bits(7)]
+                    __: u8,
+                }
+            };
+            let output = bitfield_inner(quote!(u8, flags = true), input);
+            assert!(output.is_err());
+        }
+
+        let args = quote!(u8, setters = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(!params.setters);
+        assert!(!params.must_use_getters);
+
+        let args = quote!(u8, must_use_getters = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.must_use_getters);
+        assert!(!params.try_setters);
+
+        let args = quote!(u8, try_setters = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.try_setters && !params.try_getters);
+
+        let args = quote!(u8, try_getters = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.try_getters);
+
+        let args = quote!(u8, inline = always);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert_eq!(params.inline.to_string(), "# [inline (always)]");
+        let args = quote!(u8, inline = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.inline.is_empty());
+        assert!(syn::parse2::<Params>(quote!(u8, inline = sometimes)).is_err());
+
+        let args = quote!(u8, conversions = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(!params.conversions);
+
+        let args = quote!(u32, repr = (C, align(16)));
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert_eq!(params.repr.len(), 2);
+        let args = quote!(u32, repr = C);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.repr[0].path().is_ident("C"));
+        assert!(syn::parse2::<Params>(quote!(u32, repr = packed)).is_err());
+
+        let args = quote!(u32, copy = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(!params.copy);
+
+        let args = quote!(u32, doc_table = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.doc_table);
+        let input = quote! {
+            struct Status {
+                #[bits(4, default = 3, access = RO)]
+                kind: u8,
+                #[bits(4)]
+                __: u8,
+            }
+        };
+        let output = bitfield_inner(quote!(u8, doc_table = true), input).unwrap();
+        assert!(output.to_string().contains("| `kind` | 0..4 | RO | `3` |"));
+
+        let args = quote!(u32, msb0 = true);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.msb0);
+
+        let args = quote!(u8, invariant = checks::divider_set);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.invariant.is_some());
+
+        let args = quote!(u32, debug_order = offset);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.debug && params.debug_order == DebugOrder::Offset);
+        let args = quote!(u32, new = false);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.new.is_none());
+
+        let args = quote!(u32, new = pub(crate));
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(matches!(
+            params.new,
+            Some(Some(syn::Visibility::Restricted(_)))
+        ));
+
+        let args = quote!(u32, builder = MyBuilder);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.builder.unwrap().unwrap() == "MyBuilder");
+
+        let args = quote!([u8; 4], endian = big);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert_eq!(params.endian, Permutation::SwapBytes);
+
+        let args = quote!(u32, error = crate::Error);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.error.is_some_and(|p| p.segments.len() == 2));
+
+        let args = quote!(u32, layout = crate::Layout);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert!(params.layout.is_some());
+    }
+
+    #[test]
+    fn doc_placeholders() {
+        let attr: syn::Attribute = syn::parse_quote!(#[doc = " {bits} bits at {offset}, {bits}"]);
+        let placeholders = [("{bits}", "4".to_string()), ("{offset}", "2".to_string())];
+        let doc = interpolate_doc(&attr, &placeholders);
+        assert_eq!(
+            doc.to_string(),
+            quote!(#[doc = " 4 bits at 2, 4"]).to_string()
+        );
+
+        let attr: syn::Attribute = syn::parse_quote!(#[allow(unused)]);
+        let doc = interpolate_doc(&attr, &placeholders);
+        assert_eq!(doc.to_string(), attr.to_token_stream().to_string());
+
+        assert_eq!(doc_default(&quote!(0x10u8)), "16");
+        assert_eq!(doc_default(&quote!(-1)), "-1");
+        assert_eq!(doc_default(&quote!(true)), "true");
+        assert_eq!(doc_default(&quote!(Mode::On)), "Mode::On");
+
+        let input = quote! {
+            struct Status {
+                /// Defaults to {default}
+                #[bits(4, default = 0xa)]
+                kind: u8,
+                /// Defaults to {default}
+                #[bits(4, default = Mode::On)]
+                mode: Mode,
+            }
+        };
+        let output = bitfield_inner(quote!(u8), input).unwrap().to_string();
+        let kind = quote!(#[doc = " Defaults to 10"]).to_string();
+        let mode = quote!(#[doc = " Defaults to Mode::On"]).to_string();
+        assert!(output.contains(&kind) && output.contains(&mode));
+    }
+
+    #[test]
+    fn atomic() {
+        let input = quote! {
+            struct Wide {
+                value: u128,
+            }
+        };
+        let error = bitfield_inner(quote!(u128, atomic = true), input)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "atomic requires an unsigned integer up to 64 bits"
+        );
+    }
+
+    #[test]
+    fn placement() {
+        let input = quote! {
+            struct Overlapping {
+                #[bits(0..4)]
+                a: u8,
+                #[bits(2..6)]
+                b: u8,
+            }
+        };
+        let error = bitfield_inner(quote!(u8), input).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The members at bits 0..4 and 2..6 overlap!"
+        );
+
+        let input = quote! {
+            struct Entry<T: PhysAddr> {
+                #[bits(52)]
+                addr: T,
+                #[bits(12)]
+                flags: u16,
+            }
+        };
+        let error = bitfield_inner(quote!(u64), input).err().unwrap();
+        assert!(error.to_string().starts_with("bitfields cannot be generic"));
+
+        let input = quote! {
+            struct Gaps {
+                #[bits(4..8)]
+                a: u8,
+            }
+        };
+        assert!(bitfield_inner(quote!(u8), input).is_ok());
+
+        let input = quote! {
+            struct Alias {
+                #[bits(0..8, overlaps)]
+                raw: u8,
+                #[bits(4)]
+                a: u8,
+                #[bits(4)]
+                b: u8,
+            }
+        };
+        assert!(bitfield_inner(quote!(u8), input).is_ok());
+    }
+
+    #[test]
+    fn array_masks() {
+        assert_eq!(hex_mask(std::slice::from_ref(&(4..12))), "0xff0");
+        assert_eq!(
+            hex_mask(std::slice::from_ref(&(126..130))),
+            "0x3c0000000000000000000000000000000"
+        );
+        assert_eq!(hex_mask(&[0..4, 8..12]), "0xf0f");
+        assert_eq!(
+            array_parts(32, 20, 50).collect::<Vec<_>>(),
+            [(0, 20, 12, 0), (1, 0, 32, 12), (2, 0, 6, 44)]
+        );
+
+        let args = quote!([u32; 8]);
+        let params = syn::parse2::<Params>(args).unwrap();
+        assert_eq!(params.bits, 256);
+        assert!(syn::parse2::<Params>(quote!([i32; 8])).is_err());
     }
 
     #[test]
@@ -931,5 +8923,139 @@ mod test {
         assert!(attr.default.is_some());
         assert!(attr.into.is_some());
         assert!(attr.from.is_some());
+
+        let args = quote!(8, encoding = bcd);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.bits, Some(8));
+        assert_eq!(attr.encoding, Some(Encoding::Bcd));
+
+        let args = quote!(32, redact);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.bits, Some(32));
+        assert!(attr.redact);
+
+        let args = quote!(redact = false, default = 1);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(!attr.redact);
+        assert!(attr.default.is_some());
+
+        let args = quote!(4, bounds_message = "kind too large");
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.bits, Some(4));
+        assert_eq!(attr.bounds_message.unwrap().value(), "kind too large");
+
+        let args = quote!(4, alias = "old", alias = "older");
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.aliases.len(), 2);
+
+        let args = quote!(4, doc_alias = "TXFE");
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.doc_aliases[0].value(), "TXFE");
+
+        let args = quote!(1, view = true => huge: Huge, view = false => normal: Normal);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.views.len(), 2);
+        assert_eq!(attr.views[1].ident, "normal");
+
+        let args = quote!(6, none = 0x3f);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.none.to_token_stream().to_string(), "0x3f");
+
+        let args = quote!(10, scale = 0.25, bias = -40.0);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.scale.to_token_stream().to_string(), "0.25");
+        assert_eq!(attr.bias.to_token_stream().to_string(), "- 40.0");
+
+        let args = quote!(8, get = read_kind, set = write_kind);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.get.unwrap(), "read_kind");
+        assert!(attr.with.is_none() && attr.set.is_some());
+
+        let args = quote!(4, order = Msb);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(attr.order == Some(Order::Msb));
+
+        let args = quote!(8, reverse = true);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(attr.reverse);
+
+        let args = quote!(16, endian = big);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.endian, Some(Permutation::SwapBytes));
+
+        let args = quote!(2, exhaustive);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(attr.exhaustive);
+
+        let args = quote!(8, checked);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(attr.checked && !attr.exhaustive);
+
+        let args = quote!(computed = self.a() + self.b());
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert!(attr.computed.is_some() && !attr.redact);
+        let attrs: Vec<syn::Attribute> =
+            vec![syn::parse_quote!(#[bits(computed = self.a(), redact)])];
+        assert!(field_computed(&attrs).is_err());
+
+        let args = quote!(12, fmt = hex);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.fmt.unwrap().value(), "{:#x}");
+        let args = quote!(12, fmt = "{:03}");
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.fmt.unwrap().value(), "{:03}");
+        assert!(syn::parse2::<BitsAttr>(quote!(12, fmt = decimal)).is_err());
+
+        let args = quote!(7, validate = asid::is_valid);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(
+            attr.validate.to_token_stream().to_string(),
+            "asid :: is_valid"
+        );
+
+        let args = quote!(1, parity = odd, over = 0..=30);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.bits, Some(1));
+        assert_eq!(attr.parity, Some(Parity::Odd));
+        assert_eq!(attr.over, Some(0..31));
+
+        let args = quote!(8, access = RO);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.access, Some(Access::ReadOnly));
+        let args = quote!(1, access = W1C);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.access, Some(Access::WriteOneToClear));
+        let args = quote!(4, access = RC);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.access, Some(Access::ReadToClear));
+        assert!(syn::parse2::<BitsAttr>(quote!(access = RX)).is_err());
+
+        let args = quote!(4, overflow = saturate);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!(attr.overflow, Some(Overflow::Saturate));
+        assert!(syn::parse2::<BitsAttr>(quote!(overflow = wrap)).is_err());
+
+        let args = quote!(4..8, default = 1);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!((attr.offset, attr.bits), (Some(4), Some(4)));
+
+        let args = quote!(offset = 3);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!((attr.offset, attr.bits), (Some(3), None));
+
+        let args = quote!([16..40, 56..64], default = 1);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!((attr.offset, attr.bits), (Some(16), Some(32)));
+        assert_eq!(attr.parts, Some(vec![16..40, 56..64]));
+        assert!(syn::parse2::<BitsAttr>(quote!([])).is_err());
+
+        let args = quote!(2 * 8);
+        let attr = syn::parse2::<BitsAttr>(args).unwrap();
+        assert_eq!((attr.bits, attr.count), (Some(2), Some(8)));
+
+        let err = syn::parse2::<BitsAttr>(quote!(ADDR_BITS)).err().unwrap();
+        assert!(err.to_string().contains("integer literal"));
+        assert!(syn::parse2::<BitsAttr>(quote!(size_of::<u16>() * 8, default = 1)).is_err());
+        assert!(syn::parse2::<BitsAttr>(quote!(exhaustive, redact)).is_ok());
     }
 }
@@ -1,8 +1,9 @@
 use std::fmt;
 
-use bitfield_struct::bitfield;
+use bitfield_struct::{bitfield, FromBits};
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn members() {
     /// A test bitfield with documentation
     #[bitfield(u64)]
@@ -77,6 +78,8 @@ fn members() {
     // const members
     assert_eq!(MyBitfield::FLAG_BITS, 1);
     assert_eq!(MyBitfield::FLAG_OFFSET, 16);
+    assert_eq!(MyBitfield::FLAG_MASK, 1 << 16);
+    assert_eq!(MyBitfield::INT_MASK, 0xffff);
 
     val.set_negative(1);
     assert_eq!(val.negative(), 1);
@@ -93,6 +96,7 @@ fn attrs() {
     struct Full {
         data: u64,
     }
+    #[allow(clippy::derivable_impls)]
     impl Default for Full {
         fn default() -> Self {
             Self(0)
@@ -183,6 +187,7 @@ fn entirely_negative() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn defaults() {
     #[bitfield(u16)]
     #[derive(PartialEq, Eq)]
@@ -227,6 +232,18 @@ fn defaults() {
     assert_eq!(val.flag(), true);
     assert_eq!(val.custom(), CustomEnum::B);
     assert_eq!(val.0 >> 14, 0b10); // padding
+
+    // Usable in statics
+    static TABLE: [MyBitfield; 2] = [MyBitfield::DEFAULT; 2];
+    assert!(TABLE[1] == val);
+
+    // Ignores defaults
+    let zero = MyBitfield::new_zeroed();
+    assert_eq!(zero.0, 0);
+    assert!(!zero.flag());
+    assert_eq!(zero.custom(), CustomEnum::A);
+    assert!(zero.is_zero() && !zero.is_default());
+    assert!(val.is_default() && !val.is_zero());
 }
 
 #[test]
@@ -245,6 +262,7 @@ fn default_padding() {
 }
 
 #[test]
+#[allow(clippy::unusual_byte_groupings)]
 fn lsb_order() {
     #[bitfield(u32, order=lsb)]
     struct MyBitfield {
@@ -260,6 +278,7 @@ fn lsb_order() {
 }
 
 #[test]
+#[allow(clippy::unusual_byte_groupings)]
 fn msb_order() {
     #[bitfield(u32, order=msb)]
     struct MyBitfield {
@@ -273,3 +292,3321 @@ fn msb_order() {
 
     assert_eq!(v.0, 0xe11e_00_f0);
 }
+
+#[test]
+fn bcd() {
+    #[bitfield(u16)]
+    struct MyBitfield {
+        #[bits(7, encoding = bcd)]
+        minutes: u8,
+        #[bits(6, encoding = bcd)]
+        hours: u8,
+        #[bits(3)]
+        __: (),
+    }
+
+    let v = MyBitfield::new().with_minutes(59).with_hours(23);
+    assert_eq!(v.0, 0x23 << 7 | 0x59);
+    assert_eq!(v.minutes(), 59);
+    assert_eq!(v.hours(), 23);
+
+    let v = MyBitfield::new().with_minutes(0).with_hours(9);
+    assert_eq!(v.minutes(), 0);
+    assert_eq!(v.hours(), 9);
+}
+
+#[test]
+fn parity() {
+    #[bitfield(u32)]
+    struct Frame {
+        #[bits(31)]
+        data: u32,
+        #[bits(1, parity = even, over = 0..31)]
+        parity: bool,
+    }
+
+    let v = Frame::new().with_data(0b1011).with_valid_parity();
+    assert!(v.parity());
+    assert!(v.is_parity_ok());
+    assert!(!v.with_data(0b1001).is_parity_ok());
+
+    let v = Frame::new().with_data(0b11).with_valid_parity();
+    assert!(!v.parity());
+    assert!(v.is_parity_ok());
+
+    #[bitfield(u8, order = Msb)]
+    struct OddFrame {
+        #[bits(1, parity = odd, over = 0..7)]
+        parity: bool,
+        #[bits(7)]
+        data: u8,
+    }
+
+    let v = OddFrame::new().with_valid_parity();
+    assert_eq!(v.0, 0x80);
+    assert!(v.is_parity_ok());
+    let v = OddFrame::new().with_data(3);
+    assert!(!v.is_parity_ok());
+    assert!(v.with_valid_parity().is_parity_ok());
+}
+
+#[test]
+fn debug_order() {
+    #[bitfield(u8, order = Msb, debug_order = offset)]
+    struct MyBitfield {
+        #[bits(4)]
+        high: u8,
+        #[bits(4)]
+        low: u8,
+    }
+
+    let v = MyBitfield::new().with_high(1).with_low(2);
+    assert_eq!(format!("{v:?}"), "MyBitfield { low: 2, high: 1 }");
+}
+
+#[test]
+fn nonzero() {
+    use std::num::NonZeroU64;
+
+    #[bitfield(NonZeroU64)]
+    struct Handle {
+        #[bits(default = true)]
+        valid: bool,
+        #[bits(63)]
+        index: u64,
+    }
+
+    assert_eq!(std::mem::size_of::<Option<Handle>>(), 8);
+
+    let v = Handle::new().with_index(42);
+    assert!(v.valid());
+    assert_eq!(v.index(), 42);
+    assert_eq!(u64::from(v), 42 << 1 | 1);
+    assert_eq!(NonZeroU64::from(v).get(), 42 << 1 | 1);
+
+    assert!(Handle::new_checked(0).is_none());
+    let v = Handle::new_checked(2).unwrap();
+    assert!(!v.valid());
+    assert_eq!(v.index(), 1);
+}
+
+#[test]
+#[should_panic]
+fn nonzero_zeroed() {
+    #[bitfield(core::num::NonZeroU8, new = false, default = false)]
+    struct Handle {
+        value: u8,
+    }
+
+    let v = Handle::new_checked(1).unwrap();
+    let _ = v.with_value(0);
+}
+
+#[test]
+fn float_storage() {
+    #[bitfield(f32)]
+    struct Single {
+        #[bits(23)]
+        mantissa: u32,
+        #[bits(8)]
+        exponent: u8,
+        sign: bool,
+    }
+
+    let v = Single::from(-1.5f32);
+    assert!(v.sign());
+    assert_eq!(v.exponent(), 127);
+    assert_eq!(v.mantissa(), 1 << 22);
+    assert_eq!(f32::from(v.with_sign(false)), 1.5);
+    assert_eq!(v.into_bits(), (-1.5f32).to_bits());
+    assert_eq!(u32::from(v), (-1.5f32).to_bits());
+    assert_eq!(f32::from(Single::from_bits(0x4000_0000)), 2.0);
+    assert_eq!(f32::from(Single::new()), 0.0);
+    const EXPONENT: u8 = Single::from_bits(0x3fc0_0000).exponent();
+    assert_eq!(EXPONENT, 127);
+
+    #[bitfield(f64)]
+    struct Double {
+        #[bits(52)]
+        mantissa: u64,
+        #[bits(11)]
+        exponent: u16,
+        sign: bool,
+    }
+
+    let v = Double::new().with_exponent(1023 + 3).with_mantissa(1 << 51);
+    assert_eq!(f64::from(v), 12.0);
+}
+
+#[test]
+fn float_storage_traits() {
+    #[bitfield(f32, eq = true, ops = true, display = true)]
+    struct Single {
+        #[bits(23)]
+        mantissa: u32,
+        #[bits(8)]
+        exponent: u8,
+        sign: bool,
+    }
+
+    // The bits are compared, so NaN equals itself and the zeros differ
+    let nan = Single::from(f32::NAN);
+    assert_eq!(nan, nan);
+    assert_ne!(Single::from(0.0), Single::from(-0.0));
+    assert_eq!(
+        Single::from(1.5),
+        Single::new().with_exponent(127).with_mantissa(1 << 22)
+    );
+
+    let v = Single::from(-1.5);
+    assert_eq!(f32::from(v & Single::new().with_exponent(0xff)), 1.0);
+    assert_eq!(f32::from(v ^ Single::new().with_sign(true)), 1.5);
+    assert_eq!((!Single::from(0.0)).into_bits(), u32::MAX);
+    assert_eq!(f32::from(Single::from(1.0) | Single::from(-0.0)), -1.0);
+
+    assert_eq!(v.to_string(), "mantissa=4194304 exponent=127 sign");
+    let parsed: Single = "exponent=128".parse().unwrap();
+    assert_eq!(f32::from(parsed), 2.0);
+}
+
+#[test]
+fn signed_storage() {
+    #[bitfield(i32)]
+    struct Status {
+        #[bits(16)]
+        code: u16,
+        #[bits(15)]
+        count: u16,
+        error: bool,
+    }
+
+    let v = Status::new().with_code(0xbeef).with_error(true);
+    assert_eq!(v.0, 0xbeef | i32::MIN);
+    // The signatures keep the signed integer
+    let bits: i32 = v.into_bits();
+    assert_eq!(bits, 0xbeef | i32::MIN);
+    assert_eq!(v.raw(), bits);
+    assert_eq!(i32::from(v), 0xbeef | i32::MIN);
+    assert_eq!(u32::from(v), 0x8000_beef);
+    assert_eq!(Status::from(-1).count(), 0x7fff);
+    assert_eq!(Status::from_bits(i32::MIN).0, i32::MIN);
+    assert_eq!(Status::from_bits(-1).with_raw(1).code(), 1);
+
+    #[bitfield(i16, ops = true, validate = true, builder = true, svd = true)]
+    struct Flags {
+        #[bits(8)]
+        low: u8,
+        #[bits(7)]
+        __: u8,
+        #[bits(default = true)]
+        high: bool,
+    }
+
+    assert_eq!(Flags::new().into_bits(), i16::MIN);
+    assert_eq!(Flags::DEFAULT.into_bits(), i16::MIN);
+    assert_eq!(Flags::builder().low(3).build().into_bits(), i16::MIN | 3);
+    assert_eq!((!Flags::new()).into_bits(), i16::MAX);
+    assert_eq!(
+        (Flags::new() | Flags::from_bits(5)).into_bits(),
+        i16::MIN | 5
+    );
+    assert_eq!(Flags::try_from_bits(-1).err(), Some(-1));
+    assert_eq!(Flags::try_from_bits(i16::MIN | 7).map(|f| f.low()), Ok(7));
+    assert_eq!(Flags::from_be_bytes([0x80, 0x02]).low(), 2);
+    let mut flags = Flags::new();
+    flags.write(|w| w.bits(1));
+    assert_eq!(flags.read().bits(), 1);
+
+    #[bitfield(i64)]
+    struct Wide {
+        #[bits(63)]
+        value: u64,
+        sign: bool,
+    }
+
+    let v = Wide::new().with_sign(true).with_value(1);
+    assert_eq!(v.0, i64::MIN + 1);
+
+    #[bitfield(u32)]
+    struct Outer {
+        #[bits(16)]
+        flags: Flags,
+        #[bits(16)]
+        __: u16,
+    }
+
+    // Nested, the signed bits are not sign-extended
+    let v = Outer::new().with_flags(Flags::from_bits(-1));
+    assert_eq!(v.0, 0xffff);
+    assert_eq!(v.flags().into_bits(), -1);
+}
+
+#[test]
+fn arbitrary_width_ints() {
+    use arbitrary_int::{u12, u4};
+
+    #[bitfield(u32, try_setters = true)]
+    struct Entry {
+        kind: u4,
+        #[bits(3)]
+        small: u4,
+        addr: u12,
+        #[bits(13)]
+        __: u16,
+    }
+
+    assert_eq!(Entry::KIND_BITS, 4);
+    assert_eq!(Entry::ADDR_BITS, 12);
+    assert_eq!(Entry::ADDR_OFFSET, 7);
+
+    let v = Entry::new()
+        .with_kind(u4::new(9))
+        .with_small(u4::new(5))
+        .with_addr(u12::new(0xabc));
+    assert_eq!(v.kind(), u4::new(9));
+    assert_eq!(v.small().value(), 5);
+    assert_eq!(v.addr(), u12::new(0xabc));
+    assert_eq!(v.into_bits(), 9 | 5 << 4 | 0xabc << 7);
+    assert!(v.try_with_small(u4::new(8)).is_err());
+    assert_eq!(Entry::new().kind(), u4::new(0));
+
+    // With custom conversions, types named like them are regular custom types
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, PartialEq)]
+    struct u24(u32);
+    const fn u24_from(bits: u32) -> u24 {
+        u24(bits & 0xff_ffff)
+    }
+    const fn u24_into(value: u24) -> u32 {
+        value.0 | 0xff << 24
+    }
+
+    #[bitfield(u32)]
+    struct Rgb {
+        #[bits(32, from = u24_from, into = u24_into)]
+        color: u24,
+    }
+    let rgb = Rgb::new().with_color(u24(0xabcdef));
+    assert_eq!(rgb.color(), u24(0xabcdef));
+    assert_eq!(rgb.into_bits(), 0xffab_cdef);
+}
+
+#[test]
+fn redact() {
+    #[bitfield(u64)]
+    struct KeySlot {
+        #[bits(32, redact)]
+        key: u32,
+        #[bits(31)]
+        index: u32,
+        valid: bool,
+    }
+
+    let v = KeySlot::new()
+        .with_key(0xdead_beef)
+        .with_index(3)
+        .with_valid(true);
+    assert_eq!(v.key(), 0xdead_beef);
+    assert_eq!(
+        format!("{v:?}"),
+        "KeySlot { key: ***, index: 3, valid: true }"
+    );
+}
+
+#[test]
+fn zeroize() {
+    use zeroize::{Zeroize, Zeroizing};
+
+    #[bitfield(u32, zeroize = true)]
+    struct Secret {
+        #[bits(default = 0xff)]
+        key: u16,
+        #[bits(16)]
+        __: (),
+    }
+
+    let mut v = Secret::new();
+    assert_eq!(v.key(), 0xff);
+    v.zeroize();
+    assert_eq!(v.0, 0);
+
+    let v = Zeroizing::new(Secret::new().with_key(0x1234));
+    assert_eq!(v.key(), 0x1234);
+
+    #[bitfield(u8, zeroize = cfg(test))]
+    struct CfgSecret {
+        key: u8,
+    }
+    let mut v = CfgSecret::new().with_key(1);
+    v.zeroize();
+    assert_eq!(v.key(), 0);
+}
+
+#[test]
+fn bytemuck() {
+    #[bitfield(u16, bytemuck = true)]
+    struct Entry {
+        #[bits(4)]
+        kind: u8,
+        #[bits(12)]
+        len: u16,
+    }
+
+    let entries = [Entry::new().with_kind(1).with_len(0x123), Entry::new()];
+    let raw: &[u16] = bytemuck::cast_slice(&entries);
+    assert_eq!(raw, &[0x1231, 0]);
+
+    let raw = [0x4562u16, 0xfff0];
+    let entries: &[Entry] = bytemuck::cast_slice(&raw);
+    assert_eq!(entries[0].kind(), 2);
+    assert_eq!(entries[0].len(), 0x456);
+    assert_eq!(entries[1].len(), 0xfff);
+    assert_eq!(bytemuck::cast::<Entry, u16>(entries[1]), 0xfff0);
+
+    let zeroed: Entry = bytemuck::Zeroable::zeroed();
+    assert_eq!(zeroed.0, 0);
+
+    #[bitfield([u8; 3], bytemuck = cfg(test))]
+    struct Triple {
+        #[bits(24)]
+        value: u32,
+    }
+    let triple: Triple = bytemuck::cast([0x01u8, 0x02, 0x03]);
+    assert_eq!(bytemuck::bytes_of(&triple), &[0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[derive(FromBits, Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Mode {
+        #[fallback]
+        Off = 0,
+        On = 1,
+        Auto = 2,
+    }
+
+    #[bitfield(u8, arbitrary = true)]
+    struct Control {
+        #[bits(2)]
+        mode: Mode,
+        #[bits(4)]
+        value: u8,
+        #[bits(2, default = 0b10)]
+        __: u8,
+    }
+
+    for byte in 0..=u8::MAX {
+        let data = [byte];
+        let mut u = Unstructured::new(&data);
+        let control = Control::arbitrary(&mut u).unwrap();
+        assert!(u.is_empty());
+        // The value 3 does not round trip and keeps the default
+        let mode = if byte & 0b11 == 3 { 0 } else { byte & 0b11 };
+        assert_eq!(control.mode().into_bits(), mode);
+        assert_eq!(control.value(), (byte >> 2) & 0xf);
+        assert_eq!(control.0 >> 6, 0b10);
+    }
+
+    #[bitfield(u16, arbitrary = cfg(test))]
+    struct Pair {
+        a: u8,
+        b: u8,
+    }
+    let mut u = Unstructured::new(&[0x12, 0x34, 0x56]);
+    let pair = Pair::arbitrary(&mut u).unwrap();
+    assert_eq!(pair.0, u16::from_le_bytes([0x12, 0x34]));
+    assert_eq!(u.len(), 1);
+}
+
+#[test]
+fn scale_codec() {
+    use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+
+    #[bitfield(u16, scale_codec = true, validate = true)]
+    struct Header {
+        #[bits(4)]
+        version: u8,
+        #[bits(10)]
+        len: u16,
+        #[bits(2, default = 0b01)]
+        __: u8,
+    }
+
+    let header = Header::new().with_version(3).with_len(0x155);
+    let bytes = header.encode();
+    assert_eq!(bytes, header.into_bits().to_le_bytes());
+    assert_eq!(Header::max_encoded_len(), 2);
+    let decoded = Header::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded.into_bits(), header.into_bits());
+    // reserved bits differ from their default
+    assert!(Header::decode(&mut &[0u8, 0][..]).is_err());
+    // not enough input
+    assert!(Header::decode(&mut &[0u8][..]).is_err());
+
+    #[bitfield(core::num::NonZeroU8, scale_codec = cfg(test), new = false, default = false)]
+    struct Id {
+        #[bits(default = true)]
+        valid: bool,
+        #[bits(7)]
+        index: u8,
+    }
+
+    let id = Id::new_checked(0b1011).unwrap();
+    assert_eq!(id.encode(), [0b1011]);
+    assert_eq!(Id::decode(&mut &[0b1011u8][..]).unwrap().index(), 0b101);
+    assert!(Id::decode(&mut &[0u8][..]).is_err());
+
+    #[bitfield([u8; 3], scale_codec = true)]
+    struct Triple {
+        #[bits(12)]
+        a: u16,
+        #[bits(12)]
+        b: u16,
+    }
+
+    let triple = Triple::new().with_a(0xabc).with_b(0x123);
+    let bytes = triple.encode();
+    assert_eq!(bytes, triple.into_bits());
+    assert_eq!(Triple::max_encoded_len(), 3);
+    let decoded = Triple::decode(&mut &bytes[..]).unwrap();
+    assert_eq!((decoded.a(), decoded.b()), (0xabc, 0x123));
+}
+
+#[test]
+fn ufmt() {
+    use ufmt::uwrite;
+
+    #[bitfield(u16, ufmt = true)]
+    struct Entry {
+        #[bits(4)]
+        kind: u8,
+        #[bits(4)]
+        level: i8,
+        present: bool,
+        writable: bool,
+        #[bits(4, redact)]
+        key: u8,
+        #[bits(2)]
+        __: u8,
+    }
+
+    let entry = Entry::new().with_kind(3).with_level(-2).with_present(true);
+    let mut s = String::new();
+    uwrite!(s, "{:?}", entry).unwrap();
+    assert_eq!(
+        s,
+        "Entry { kind: 3, level: -2, present: true, writable: false, key: *** }"
+    );
+    let mut s = String::new();
+    uwrite!(s, "{}", entry).unwrap();
+    assert_eq!(s, "kind=3 level=-2 present key=***");
+
+    #[bitfield(u8, ufmt = cfg(test))]
+    struct Lanes {
+        #[bits(2)]
+        lanes: [u8; 4],
+    }
+    let mut s = String::new();
+    uwrite!(s, "{:?}", Lanes::new().with_lanes(1, 3)).unwrap();
+    assert_eq!(s, "Lanes { lanes: [0, 3, 0, 0] }");
+}
+
+#[test]
+fn min_max() {
+    #[bitfield(u64)]
+    struct MyBitfield {
+        #[bits(4)]
+        kind: u8,
+        #[bits(13)]
+        negative: i16,
+        full: i8,
+        #[bits(7, encoding = bcd)]
+        minutes: u8,
+        #[bits(12)]
+        size: usize,
+        byte: u8,
+        flag: bool,
+        #[bits(11)]
+        __: (),
+    }
+
+    assert_eq!(MyBitfield::KIND_MAX, 15);
+    assert_eq!(MyBitfield::NEGATIVE_MIN, -4096);
+    assert_eq!(MyBitfield::NEGATIVE_MAX, 4095);
+    assert_eq!(MyBitfield::FULL_MIN, i8::MIN);
+    assert_eq!(MyBitfield::FULL_MAX, i8::MAX);
+    assert_eq!(MyBitfield::MINUTES_MAX, 79);
+    assert_eq!(MyBitfield::SIZE_MAX, 0xfff);
+    assert_eq!(MyBitfield::BYTE_MAX, u8::MAX);
+
+    let v = MyBitfield::new()
+        .with_kind(MyBitfield::KIND_MAX)
+        .with_negative(MyBitfield::NEGATIVE_MIN)
+        .with_minutes(MyBitfield::MINUTES_MAX);
+    assert_eq!(v.kind(), 15);
+    assert_eq!(v.negative(), -4096);
+    assert_eq!(v.minutes(), 79);
+}
+
+#[test]
+fn slices() {
+    #[bitfield(u16, slices = true)]
+    struct Descriptor {
+        #[bits(15)]
+        len: u16,
+        valid: bool,
+    }
+
+    let mut raw = [0x8001u16, 2, 0x8003];
+    let descs = Descriptor::from_raw_slice(&raw);
+    assert_eq!(descs.len(), 3);
+    assert!(descs[0].valid() && !descs[1].valid());
+    assert_eq!(descs[2].len(), 3);
+
+    let descs = Descriptor::from_raw_slice_mut(&mut raw);
+    descs[1].set_valid(true);
+    assert_eq!(Descriptor::as_raw_slice(descs), &[0x8001, 0x8002, 0x8003]);
+
+    Descriptor::as_raw_slice_mut(descs)[0] = 5;
+    assert_eq!(raw, [5, 0x8002, 0x8003]);
+}
+
+#[test]
+fn slice_codec() {
+    #[bitfield(u16, slices = true)]
+    struct Descriptor {
+        #[bits(15)]
+        len: u16,
+        valid: bool,
+    }
+
+    let descs = [
+        Descriptor::new().with_len(1),
+        Descriptor::new().with_valid(true),
+    ];
+    let mut buf = [0u8; 5];
+    assert_eq!(Descriptor::encode_slice(&descs, &mut buf), Some(4));
+    assert_eq!(
+        &buf[..4],
+        [0x0001u16.to_ne_bytes(), 0x8000u16.to_ne_bytes()].concat()
+    );
+    assert_eq!(Descriptor::encode_slice(&descs, &mut buf[..3]), None);
+
+    let decoded: Vec<_> = Descriptor::decode_slice(&buf).collect();
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].len(), 1);
+    assert!(decoded[1].valid());
+
+    #[bitfield(core::num::NonZeroU8, slices = true, new = false, default = false)]
+    struct Id {
+        value: u8,
+    }
+    let ids: Vec<_> = Id::decode_slice(&[1, 0])
+        .map(|id| id.map(|id| id.value()))
+        .collect();
+    assert_eq!(ids, [Some(1), None]);
+
+    #[bitfield([u16; 2], slices = true)]
+    struct Wide {
+        value: u32,
+    }
+    let mut buf = [0u8; 8];
+    let wides = [
+        Wide::new().with_value(0x1234_5678),
+        Wide::new().with_value(9),
+    ];
+    Wide::encode_slice(&wides, &mut buf).unwrap();
+    let values: Vec<_> = Wide::decode_slice(&buf).map(|w| w.value()).collect();
+    assert_eq!(values, [0x1234_5678, 9]);
+}
+
+#[test]
+fn alloc() {
+    #[bitfield(u16, alloc = true)]
+    #[derive(PartialEq, Eq)]
+    struct Command {
+        opcode: u8,
+        arg: u8,
+    }
+
+    let commands = [
+        Command::new().with_opcode(1).with_arg(2),
+        Command::new().with_opcode(3).with_arg(4),
+    ];
+    let bytes = Command::to_bytes_vec(&commands);
+    assert_eq!(bytes.len(), 4);
+    assert_eq!(bytes[..2], 0x0201u16.to_ne_bytes());
+
+    let parsed = Command::from_bytes_vec(&bytes).unwrap();
+    assert!(parsed == commands);
+    assert!(Command::from_bytes_vec(&bytes[..3]).is_none());
+
+    let raw = Command::into_raw_vec(parsed);
+    assert_eq!(raw, vec![0x0201, 0x0403]);
+    assert!(Command::from_raw_vec(raw) == commands);
+
+    #[bitfield(std::num::NonZeroU8, alloc = cfg(test), new = false, default = false)]
+    struct Handle {
+        value: u8,
+    }
+    assert_eq!(Handle::from_bytes_vec(&[1, 2]).unwrap().len(), 2);
+    assert!(Handle::from_bytes_vec(&[1, 0]).is_none());
+}
+
+#[test]
+#[should_panic(expected = "MyBitfield::kind: value out of bounds (max 15)")]
+fn bounds_message() {
+    #[bitfield(u8)]
+    struct MyBitfield {
+        #[bits(4)]
+        kind: u8,
+        #[bits(4)]
+        __: (),
+    }
+
+    let _ = MyBitfield::new().with_kind(16);
+}
+
+#[test]
+#[should_panic(expected = "MyBitfield::negative: value out of bounds (range -4..=3)")]
+fn bounds_message_signed() {
+    #[bitfield(u8)]
+    struct MyBitfield {
+        #[bits(3)]
+        negative: i8,
+        #[bits(5)]
+        __: (),
+    }
+
+    let _ = MyBitfield::new().with_negative(-5);
+}
+
+#[test]
+fn full_width_signed_negative() {
+    // The mask of full-width signed fields does not fit the signed type,
+    // which rejected negative values in the bounds check
+    #[bitfield(u16)]
+    struct MyBitfield {
+        #[bits(8)]
+        low: i8,
+        #[bits(8)]
+        high: i8,
+    }
+
+    let v = MyBitfield::new().with_low(-1).with_high(i8::MIN);
+    assert_eq!(v.low(), -1);
+    assert_eq!(v.high(), i8::MIN);
+    assert_eq!(v.0, 0x80ff);
+}
+
+#[test]
+#[should_panic(expected = "the level is limited to 0..4")]
+fn bounds_message_custom() {
+    #[bitfield(u8)]
+    struct MyBitfield {
+        #[bits(2, bounds_message = "the level is limited to 0..4")]
+        level: u8,
+        #[bits(6)]
+        __: (),
+    }
+
+    MyBitfield::new().set_level(4);
+}
+
+#[test]
+#[should_panic(expected = "value out of bounds")]
+fn negative_overflow() {
+    #[bitfield(u8)]
+    struct MyBitfield {
+        #[bits(3)]
+        negative: i8,
+        #[bits(5)]
+        __: (),
+    }
+
+    let _ = MyBitfield::new().with_negative(4);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_alias() {
+    #[bitfield(u8)]
+    struct MyBitfield {
+        #[bits(4, alias = "old_kind", alias = "kind")]
+        category: u8,
+        #[bits(4)]
+        __: (),
+    }
+
+    let mut v = MyBitfield::new().with_old_kind(3);
+    assert_eq!(v.category(), 3);
+    v.set_kind(5);
+    assert_eq!(v.old_kind(), 5);
+    assert_eq!(MyBitfield::OLD_KIND_BITS, MyBitfield::CATEGORY_BITS);
+    assert_eq!(MyBitfield::KIND_OFFSET, MyBitfield::CATEGORY_OFFSET);
+}
+
+#[test]
+fn doc_placeholders() {
+    /// Checks that placeholders in the field docs compile
+    #[bitfield(u16)]
+    struct MyBitfield {
+        /// Occupies {bits} bits at {offset} (mask `{mask}`), defaults to {default}
+        #[bits(4, default = 3)]
+        kind: u8,
+        #[bits(12)]
+        __: (),
+    }
+
+    assert_eq!(MyBitfield::new().kind(), 3);
+}
+
+#[test]
+fn fields_trait() {
+    #[bitfield(u8, fields_trait = true)]
+    struct MyReg {
+        #[bits(4)]
+        kind: u8,
+        enabled: bool,
+        #[bits(3)]
+        __: (),
+    }
+
+    struct FakeReg;
+    impl MyRegFields for FakeReg {
+        fn kind(&self) -> u8 {
+            7
+        }
+        fn enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn describe(reg: &impl MyRegFields) -> (u8, bool) {
+        (reg.kind(), reg.enabled())
+    }
+
+    let reg = MyReg::new().with_kind(3);
+    assert_eq!(describe(&reg), (3, false));
+    assert_eq!(describe(&FakeReg), (7, true));
+
+    #[bitfield(u8, fields_trait = Named)]
+    struct Other {
+        value: u8,
+    }
+    assert_eq!(Named::value(&Other::new().with_value(4)), 4);
+}
+
+#[test]
+fn array() {
+    #[bitfield([u32; 8])]
+    struct Descriptor {
+        #[bits(100)]
+        address: u128,
+        #[bits(40, default = -3)]
+        delta: i64,
+        valid: bool,
+        #[bits(7)]
+        kind: u8,
+        #[bits(108)]
+        __: (),
+    }
+
+    const _: () = assert!(Descriptor::new().delta() == -3);
+    const _: () = assert!(Descriptor::new().is_default() && Descriptor::new_zeroed().is_zero());
+
+    let d = Descriptor::new()
+        .with_address(0xf_1234_5678_9abc_def0_1234_5678)
+        .with_valid(true)
+        .with_kind(0x55);
+    assert_eq!(d.address(), 0xf_1234_5678_9abc_def0_1234_5678);
+    assert_eq!(d.delta(), -3);
+    assert!(d.valid());
+    assert_eq!(d.kind(), 0x55);
+    assert!(!d.is_default() && !d.is_zero());
+
+    let raw: [u32; 8] = d.into();
+    assert_eq!(raw[0], 0x1234_5678);
+    assert_eq!(raw[3] & 0xf, 0xf);
+    assert_eq!(raw[3] >> 4, 0x0fff_fffd);
+    assert_eq!(raw[4], 0xa_bfff);
+    assert_eq!(raw[5..], [0; 3]);
+    assert_eq!(Descriptor::from(raw).address(), d.address());
+
+    #[bitfield([u8; 16], order = Msb)]
+    struct Header {
+        #[bits(4)]
+        version: u8,
+        #[bits(20)]
+        flow: u32,
+        #[bits(104)]
+        __: (),
+    }
+
+    let h = Header::new().with_version(6).with_flow(0xabcde);
+    let raw: [u8; 16] = h.into();
+    assert_eq!(raw[15], 0x6a);
+    assert_eq!(raw[14], 0xbc);
+    assert_eq!(raw[13], 0xde);
+    assert_eq!(Header::VERSION_OFFSET, 124);
+    assert_eq!(Header::FLOW_MASK[15], 0x0f);
+    assert_eq!(Header::FLOW_MASK[13], 0xff);
+    assert_eq!(Header::FLOW_MASK[..13], [0; 13]);
+    assert_eq!(
+        Descriptor::DELTA_MASK,
+        [0, 0, 0, 0xffff_fff0, 0xfff, 0, 0, 0]
+    );
+    assert_eq!(h.version(), 6);
+    assert_eq!(h.flow(), 0xabcde);
+}
+
+#[test]
+fn nested() {
+    #[bitfield(u8)]
+    #[derive(PartialEq, Eq)]
+    struct Status {
+        ready: bool,
+        #[bits(3)]
+        code: u8,
+        #[bits(4)]
+        __: u8,
+    }
+
+    #[bitfield(u32)]
+    struct Register {
+        #[bits(8)]
+        status: Status,
+        #[bits(4, default = Status::new().with_code(5))]
+        defaulted: Status,
+        #[bits(20)]
+        value: u32,
+    }
+
+    const _: () = assert!(Register::new().defaulted().code() == 5);
+
+    let reg = Register::new()
+        .with_status(Status::new().with_ready(true).with_code(3))
+        .with_value(0xabcde);
+    assert_eq!(reg.status(), Status::from_bits(0b0111));
+    assert_eq!(reg.status().into_bits(), 0b0111);
+    assert_eq!(reg.defaulted().code(), 5);
+    assert_eq!(reg.value(), 0xabcde);
+    assert_eq!(Register::from_bits(reg.into_bits()).status().code(), 3);
+}
+
+#[test]
+fn from_bits_derive() {
+    #[derive(FromBits, Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Mode {
+        Off = 0,
+        On = 1,
+        Auto = 3,
+        #[fallback]
+        Invalid = 0xff,
+    }
+
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Strict {
+        A,
+        B,
+    }
+
+    #[bitfield(u16)]
+    struct Control {
+        #[bits(2)]
+        mode: Mode,
+        #[bits(1)]
+        strict: Strict,
+        #[bits(13)]
+        __: u16,
+    }
+
+    assert_eq!(Mode::from_bits(3), Mode::Auto);
+    assert_eq!(Mode::from_bits(2), Mode::Invalid);
+    assert_eq!(Mode::On.into_bits(), 1);
+    assert_eq!(Strict::from_bits(1), Strict::B);
+
+    let c = Control::new().with_mode(Mode::Auto).with_strict(Strict::B);
+    assert_eq!(c.into_bits(), 0b111);
+    assert_eq!(c.mode(), Mode::Auto);
+    assert_eq!(c.strict(), Strict::B);
+    assert_eq!(Control::from_bits(0b010).mode(), Mode::Invalid);
+
+    // Without a repr, the bits are the smallest unsigned integer for all discriminants
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    enum Small {
+        A,
+        B = 0x7f,
+        C,
+    }
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    enum Wide {
+        A = 0xff,
+        B,
+    }
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    enum Negative {
+        A = -1,
+        B,
+    }
+    let small: u8 = Small::C.into_bits();
+    assert_eq!(small, 0x80);
+    let wide: u16 = Wide::B.into_bits();
+    assert_eq!(wide, 0x100);
+    let negative: isize = Negative::A.into_bits();
+    assert_eq!(negative, -1);
+    assert_eq!(Negative::from_bits(0), Negative::B);
+    assert_eq!((Small::BITS, Wide::BITS), (8, 9));
+
+    #[bitfield(u8)]
+    struct Packed {
+        #[bits(8)]
+        small: Small,
+    }
+    assert_eq!(Packed::new().with_small(Small::B).into_bits(), 0x7f);
+}
+
+#[test]
+fn access() {
+    #[bitfield(u16)]
+    struct Status {
+        #[bits(access = RO)]
+        busy: bool,
+        #[bits(7, access = WO)]
+        command: u8,
+        #[bits(8, default = 3, access = RO)]
+        revision: u8,
+    }
+
+    let s = Status::from_bits(0x0201).with_command(0x7f);
+    assert!(s.busy());
+    assert_eq!(s.revision(), 2);
+    assert_eq!(s.into_bits(), 0x02ff);
+    assert_eq!(Status::new().revision(), 3);
+    assert_eq!(format!("{s:?}"), "Status { busy: true, revision: 2 }");
+}
+
+#[test]
+fn try_setters() {
+    #[bitfield(u32, try_setters = true)]
+    struct MyBitfield {
+        #[bits(4)]
+        small: u8,
+        #[bits(5)]
+        signed: i8,
+        #[bits(8, encoding = bcd)]
+        decimal: u8,
+        full: u8,
+        #[bits(7)]
+        __: u8,
+    }
+
+    let mut val = MyBitfield::new();
+    assert_eq!(val.try_with_small(15).map(|v| v.small()), Ok(15));
+    assert_eq!(
+        val.try_with_small(16).err(),
+        Some(MyBitfieldError::OutOfBounds)
+    );
+    assert_eq!(val.try_with_signed(-16).map(|v| v.signed()), Ok(-16));
+    assert!(val.try_with_signed(-17).is_err());
+    assert!(val.try_with_signed(16).is_err());
+    assert_eq!(val.try_with_decimal(99).map(|v| v.decimal()), Ok(99));
+    assert!(val.try_with_decimal(100).is_err());
+    assert_eq!(val.try_with_full(255).map(|v| v.full()), Ok(255));
+
+    assert_eq!(val.try_set_small(3), Ok(()));
+    assert_eq!(
+        val.try_set_small(32).err(),
+        Some(MyBitfieldError::OutOfBounds)
+    );
+    assert_eq!(val.small(), 3);
+    assert_eq!(val.into_bits(), 3);
+
+    #[bitfield(core::num::NonZeroU8, try_setters = true)]
+    struct Id {
+        #[bits(4, default = 1)]
+        low: u8,
+        #[bits(4)]
+        high: u8,
+    }
+    assert_eq!(Id::new().try_with_low(0).err(), Some(IdError::Invalid));
+    assert_eq!(Id::new().try_with_high(2).map(|v| v.high()), Ok(2));
+}
+
+#[test]
+fn custom_error() {
+    #[derive(Debug, PartialEq)]
+    enum DriverError {
+        Reg(RegError),
+    }
+    impl From<RegError> for DriverError {
+        fn from(e: RegError) -> Self {
+            Self::Reg(e)
+        }
+    }
+
+    #[bitfield(u16, error = DriverError, display = true, try_setters = true)]
+    struct Reg {
+        #[bits(4)]
+        kind: u8,
+        #[bits(12)]
+        __: u16,
+    }
+
+    assert_eq!(Reg::new().try_with_kind(3).map(|v| v.kind()), Ok(3));
+    assert_eq!(
+        Reg::new().try_with_kind(16).err(),
+        Some(DriverError::Reg(RegError::OutOfBounds))
+    );
+    assert_eq!(
+        "kind=x".parse::<Reg>().err(),
+        Some(DriverError::Reg(RegError::Parse))
+    );
+    assert_eq!(RegError::ReadOnly.to_string(), "field is read-only");
+}
+
+#[test]
+fn overflow() {
+    #[bitfield(u32, overflow = saturate)]
+    struct Saturating {
+        #[bits(4)]
+        small: u8,
+        #[bits(5)]
+        signed: i8,
+        #[bits(8, encoding = bcd)]
+        decimal: u8,
+        #[bits(4, overflow = truncate)]
+        wrapping: u8,
+        #[bits(3, into = to_raw, from = from_raw)]
+        converted: u8,
+        #[bits(8)]
+        __: u8,
+    }
+    const fn to_raw(value: u8) -> u32 {
+        value as _
+    }
+    const fn from_raw(value: u32) -> u8 {
+        value as _
+    }
+
+    let val = Saturating::new()
+        .with_small(100)
+        .with_signed(-100)
+        .with_decimal(123)
+        .with_wrapping(0x13)
+        .with_converted(9);
+    assert_eq!(val.small(), 15);
+    assert_eq!(val.signed(), -16);
+    assert_eq!(val.decimal(), 99);
+    assert_eq!(val.wrapping(), 3);
+    assert_eq!(val.converted(), 7);
+    assert_eq!(val.with_signed(100).signed(), 15);
+}
+
+#[test]
+#[should_panic = "Panicking::small: value out of bounds (max 15)"]
+fn overflow_panic() {
+    #[bitfield(u8)]
+    struct Panicking {
+        #[bits(4, overflow = panic)]
+        small: u8,
+        #[bits(4)]
+        __: u8,
+    }
+    let _ = Panicking::new().with_small(16);
+}
+
+#[test]
+fn placement() {
+    #[bitfield(u32)]
+    struct Placed {
+        #[bits(24..32)]
+        high: u8,
+        #[bits(0..4)]
+        low: u8,
+        #[bits(offset = 8)]
+        mid: u8,
+        /// continues after `mid`
+        #[bits(4)]
+        nibble: u8,
+    }
+
+    assert_eq!(Placed::HIGH_OFFSET, 24);
+    assert_eq!(Placed::LOW_OFFSET, 0);
+    assert_eq!(Placed::MID_OFFSET, 8);
+    assert_eq!(Placed::NIBBLE_OFFSET, 16);
+
+    let val = Placed::new()
+        .with_high(0xab)
+        .with_low(0xc)
+        .with_mid(0xde)
+        .with_nibble(0xf);
+    assert_eq!(val.into_bits(), 0xab0f_de0c);
+
+    #[bitfield(u16, order = Msb)]
+    struct Msb {
+        #[bits(4)]
+        first: u8,
+        #[bits(4..=7)]
+        mid: u8,
+        #[bits(4)]
+        after: u8,
+    }
+
+    assert_eq!(Msb::FIRST_OFFSET, 12);
+    assert_eq!(Msb::MID_OFFSET, 4);
+    assert_eq!(Msb::AFTER_OFFSET, 0);
+    let val = Msb::from_bits(0xa0bc);
+    assert_eq!(val.first(), 0xa);
+    assert_eq!(val.mid(), 0xb);
+    assert_eq!(val.after(), 0xc);
+}
+
+#[test]
+fn fields_metadata() {
+    #[bitfield(u16)]
+    struct MyBitfield {
+        #[bits(4)]
+        kind: u8,
+        #[bits(3)]
+        __: u8,
+        #[bits(5)]
+        delta: i8,
+        #[bits(4, access = WO)]
+        command: u8,
+    }
+
+    assert_eq!(
+        MyBitfield::FIELDS,
+        [
+            ("kind", 0, 4, false),
+            ("delta", 7, 5, true),
+            ("command", 12, 4, false)
+        ]
+    );
+    let covered: usize = MyBitfield::FIELDS.iter().map(|f| f.2).sum();
+    assert_eq!(covered, 13);
+
+    let (name, bits, fields) = MyBitfield::LAYOUT;
+    assert_eq!((name, bits, fields.len()), ("MyBitfield", 16, 3));
+
+    mod regmap {
+        pub struct Layout {
+            pub name: &'static str,
+            pub bits: usize,
+            pub fields: &'static [(&'static str, usize, usize, bool)],
+        }
+    }
+
+    #[bitfield(u16, layout = regmap::Layout)]
+    struct Named {
+        #[bits(4)]
+        kind: u8,
+        #[bits(12)]
+        __: u16,
+    }
+
+    let regmap::Layout { name, bits, fields } = Named::LAYOUT;
+    assert_eq!((name, bits), ("Named", 16));
+    assert_eq!(fields, [("kind", 0, 4, false)]);
+
+    // Offsets count from the least significant bit, and custom types are not signed
+    #[derive(Debug)]
+    struct Level(i8);
+    impl Level {
+        const fn into_bits(self) -> u8 {
+            self.0 as u8 & 0xf
+        }
+        const fn from_bits(bits: u8) -> Self {
+            Self((bits << 4) as i8 >> 4)
+        }
+    }
+    #[bitfield(u8, order = Msb)]
+    struct Msb {
+        #[bits(4)]
+        level: Level,
+        #[bits(4)]
+        delta: i8,
+    }
+    assert_eq!(Msb::FIELDS, [("level", 4, 4, false), ("delta", 0, 4, true)]);
+}
+
+#[test]
+fn field_enum() {
+    #[bitfield(u32, field_enum = true)]
+    struct MyReg {
+        #[bits(4)]
+        irq_mask: u8,
+        #[bits(4)]
+        __: u8,
+        #[bits(8)]
+        level: i8,
+        #[bits(access = RO)]
+        busy: bool,
+        #[bits(15)]
+        kind: u16,
+    }
+
+    assert_eq!(
+        MyRegField::ALL,
+        [
+            MyRegField::IrqMask,
+            MyRegField::Level,
+            MyRegField::Busy,
+            MyRegField::Kind
+        ]
+    );
+    assert_eq!(MyRegField::from_name("level"), Some(MyRegField::Level));
+    assert_eq!(MyRegField::from_name("unknown"), None);
+    assert_eq!(MyRegField::Kind.name(), "kind");
+
+    let mut reg = MyReg::new().with_level(-1).with_kind(7);
+    assert_eq!(reg.get(MyRegField::Level), 0xff);
+    assert_eq!(reg.get(MyRegField::Kind), 7);
+
+    assert_eq!(reg.set(MyRegField::IrqMask, 0xa), Ok(()));
+    assert_eq!(reg.irq_mask(), 0xa);
+    assert_eq!(
+        reg.set(MyRegField::IrqMask, 0x10),
+        Err(MyRegError::OutOfBounds)
+    );
+    assert_eq!(
+        reg.set(MyRegField::Busy, 1).err(),
+        Some(MyRegError::ReadOnly)
+    );
+    assert!(!reg.busy());
+
+    let other = reg.with_level(2).with_kind(7).with_irq_mask(0);
+    let changes: Vec<_> = reg.diff(&other).collect();
+    assert_eq!(
+        changes,
+        [(MyRegField::IrqMask, 0xa, 0), (MyRegField::Level, 0xff, 2)]
+    );
+    assert_eq!(reg.diff(&reg).count(), 0);
+
+    #[bitfield(u8, field_enum = Selector)]
+    struct Small {
+        #[bits(8)]
+        value: u8,
+    }
+    assert_eq!(Small::new().get(Selector::Value), 0);
+
+    // Fields named like the dynamic accessors have to be renamed
+    #[bitfield(u8, field_enum = true)]
+    struct Mux {
+        #[bits(4, get = input)]
+        get: u8,
+        #[bits(4, get = output)]
+        set: u8,
+    }
+    let mut mux = Mux::new();
+    assert_eq!(mux.set(MuxField::Set, 3), Ok(()));
+    assert_eq!((mux.input(), mux.output()), (0, 3));
+}
+
+#[test]
+fn atomic() {
+    use std::sync::atomic::Ordering;
+
+    #[bitfield(u32, atomic = true)]
+    struct Lock {
+        locked: bool,
+        #[bits(15)]
+        waiters: u16,
+        #[bits(16, access = RO)]
+        owner: u16,
+    }
+
+    let lock = AtomicLock::new(Lock::new().with_waiters(2));
+    let prev = lock
+        .fetch_update_locked(Ordering::AcqRel, Ordering::Acquire, |locked| {
+            (!locked).then_some(true)
+        })
+        .unwrap();
+    assert!(!prev.locked());
+    assert!(lock.load(Ordering::Relaxed).locked());
+    assert!(lock
+        .fetch_update_locked(Ordering::AcqRel, Ordering::Acquire, |locked| {
+            (!locked).then_some(true)
+        })
+        .is_err());
+
+    lock.fetch_update_waiters(Ordering::Relaxed, Ordering::Relaxed, |w| Some(w + 1))
+        .unwrap();
+    assert_eq!(lock.load(Ordering::Relaxed).waiters(), 3);
+
+    let old = lock.swap(Lock::new(), Ordering::Relaxed);
+    assert_eq!(old.into_bits(), 0b111);
+    lock.store(Lock::from_bits(1 << 16), Ordering::Relaxed);
+    assert_eq!(lock.into_inner().owner(), 1);
+    assert_eq!(AtomicLock::default().load(Ordering::Relaxed).into_bits(), 0);
+
+    // Field types may use the names of generic parameters
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum F {
+        A,
+        B,
+    }
+    #[bitfield(u8, atomic = true)]
+    struct Flags {
+        #[bits(1)]
+        f: F,
+        #[bits(7)]
+        __: u8,
+    }
+    let flags = AtomicFlags::new(Flags::new());
+    flags
+        .fetch_update_f(Ordering::Relaxed, Ordering::Relaxed, |_| Some(F::B))
+        .unwrap();
+    assert_eq!(flags.load(Ordering::Relaxed).f(), F::B);
+}
+
+#[test]
+fn atomic_setters() {
+    use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
+
+    #[bitfield(u64, atomic = true)]
+    struct Entry {
+        present: bool,
+        #[bits(access = RO)]
+        dirty: bool,
+        #[bits(62, access = WO)]
+        frame: u64,
+    }
+
+    let raw = AtomicU64::new(0b10);
+    let prev = Entry::set_present_atomic(&raw, true, Ordering::AcqRel);
+    assert_eq!(prev.into_bits(), 0b10);
+    Entry::set_frame_atomic(&raw, 0x1234, Ordering::Release);
+    assert_eq!(raw.load(Ordering::Relaxed), 0x1234 << 2 | 0b11);
+    Entry::set_present_atomic(&raw, false, Ordering::SeqCst);
+    assert!(Entry::from_bits(raw.load(Ordering::Relaxed)).dirty());
+    assert_eq!(raw.load(Ordering::Relaxed), 0x1234 << 2 | 0b10);
+
+    // Multiple fields with a single update
+    #[bitfield(u16, atomic = true)]
+    struct Status {
+        ready: bool,
+        #[bits(15)]
+        count: u16,
+    }
+    let raw = AtomicU16::new(0);
+    Status::set_count_atomic(&raw, 3, Ordering::Relaxed);
+    Status::modify_atomic(&raw, |s| s.with_ready(true), Ordering::Relaxed);
+    assert_eq!(raw.load(Ordering::Relaxed), 3 << 1 | 1);
+}
+
+#[test]
+fn byte_conversions() {
+    #[bitfield(u32)]
+    struct Header {
+        #[bits(16)]
+        length: u16,
+        #[bits(16)]
+        kind: u16,
+    }
+
+    let h = Header::from_be_bytes([0xab, 0xcd, 0x01, 0x02]);
+    assert_eq!(h.kind(), 0xabcd);
+    assert_eq!(h.length(), 0x0102);
+    assert_eq!(h.to_le_bytes(), [0x02, 0x01, 0xcd, 0xab]);
+    assert_eq!(
+        Header::from_le_bytes(h.to_le_bytes()).into_bits(),
+        h.into_bits()
+    );
+    assert_eq!(h.to_ne_bytes(), h.into_bits().to_ne_bytes());
+
+    #[bitfield([u16; 3])]
+    struct Wide {
+        #[bits(24)]
+        low: u32,
+        #[bits(24)]
+        high: u32,
+    }
+
+    let bytes = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc];
+    let w = Wide::from_be_bytes(bytes);
+    assert_eq!(w.high(), 0x12_3456);
+    assert_eq!(w.low(), 0x78_9abc);
+    assert_eq!(w.to_be_bytes(), bytes);
+    assert_eq!(w.to_le_bytes(), [0xbc, 0x9a, 0x78, 0x56, 0x34, 0x12]);
+    assert_eq!(Wide::from_le_bytes(w.to_le_bytes()).low(), 0x78_9abc);
+    assert_eq!(Wide::from_ne_bytes(w.to_ne_bytes()).high(), 0x12_3456);
+}
+
+#[test]
+fn display() {
+    #[bitfield(u16, display = true)]
+    struct Entry {
+        #[bits(4)]
+        kind: u8,
+        #[bits(4)]
+        level: i8,
+        present: bool,
+        #[bits(default = true)]
+        writable: bool,
+        #[bits(4, redact)]
+        key: u8,
+        #[bits(2)]
+        __: u8,
+    }
+
+    let entry = Entry::new().with_kind(3).with_level(-2).with_present(true);
+    assert_eq!(
+        entry.to_string(),
+        "kind=3 level=-2 present writable key=***"
+    );
+    let entry = entry.with_writable(false);
+    assert_eq!(entry.to_string(), "kind=3 level=-2 present key=***");
+
+    let parsed: Entry = "kind=3 level=-2 present".parse().unwrap();
+    assert_eq!(parsed.into_bits(), entry.into_bits());
+    let parsed: Entry = "kind=1 writable=true key=5".parse().unwrap();
+    assert_eq!(
+        (parsed.kind(), parsed.writable(), parsed.key()),
+        (1, true, 5)
+    );
+
+    assert_eq!(
+        "kind=16".parse::<Entry>().err(),
+        Some(EntryError::OutOfBounds)
+    );
+    assert_eq!("kind".parse::<Entry>().err(), Some(EntryError::Parse));
+    assert!("unknown=1".parse::<Entry>().is_err());
+    // Redacted values keep their defaults
+    let parsed: Entry = "key=***".parse().unwrap();
+    assert_eq!(
+        parsed.into_bits(),
+        Entry::new().with_writable(false).into_bits()
+    );
+}
+
+#[test]
+fn display_round_trip() {
+    #[bitfield(u16, display = true)]
+    struct Status {
+        #[bits(4, access = RO)]
+        state: u8,
+        #[bits(4, redact, default = 9)]
+        token: u8,
+        #[bits(access = RO)]
+        busy: bool,
+        #[bits(access = RO, default = true)]
+        idle: bool,
+        #[bits(6)]
+        count: u8,
+    }
+
+    let status = Status::from_bits(0x15a7);
+    assert_eq!(status.to_string(), "state=7 token=*** busy count=5");
+    let parsed: Status = status.to_string().parse().unwrap();
+    assert_eq!(
+        (parsed.state(), parsed.busy(), parsed.idle()),
+        (7, true, false)
+    );
+    assert_eq!((parsed.token(), parsed.count()), (9, 5));
+    assert_eq!(parsed.to_string(), status.to_string());
+}
+
+#[test]
+fn radix() {
+    #[bitfield(u32, radix = true)]
+    struct Reg {
+        #[bits(16)]
+        low: u16,
+        #[bits(16)]
+        high: u16,
+    }
+
+    let reg = Reg::new().with_low(0xbeef).with_high(0xa);
+    assert_eq!(format!("{reg:#010x}"), "0x000abeef");
+    assert_eq!(format!("{reg:X}"), "ABEEF");
+    assert_eq!(format!("{:b}", Reg::from_bits(5)), "101");
+    assert_eq!(format!("{:o}", Reg::from_bits(8)), "10");
+
+    #[bitfield(core::num::NonZeroU8, radix = true, new = false, default = false)]
+    struct Id {
+        value: u8,
+    }
+    assert_eq!(format!("{:x}", Id::from_bits(255)), "ff");
+}
+
+#[test]
+fn validate() {
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Mode {
+        Off = 0,
+        On = 1,
+        #[fallback]
+        Invalid = 3,
+    }
+
+    #[bitfield(u16, validate = true)]
+    struct Frame {
+        #[bits(2)]
+        mode: Mode,
+        #[bits(2)]
+        __: u8,
+        #[bits(8, encoding = bcd)]
+        count: u8,
+        #[bits(2, default = 0b10)]
+        __: u8,
+        #[bits(2)]
+        level: i8,
+    }
+
+    let valid = Frame::new().with_mode(Mode::On).with_count(42).into_bits();
+    assert_eq!(Frame::try_from(valid).map(|f| f.count()), Ok(42));
+    assert_eq!(Frame::try_from_bits(valid).map(|f| f.mode()), Ok(Mode::On));
+    // `Mode` has no variant for 2
+    assert_eq!(
+        Frame::try_from(valid & !0b11 | 0b10).err(),
+        Some(valid & !0b11 | 0b10)
+    );
+    // 0xa is no decimal digit
+    assert!(Frame::try_from(valid & !0xff0 | 0xa00).is_err());
+    // reserved bits differ from their defaults
+    assert!(Frame::try_from(valid | 0b100).is_err());
+    assert!(Frame::try_from(valid & !(0b10 << 12)).is_err());
+    assert_eq!(u16::from(Frame::try_from(valid).unwrap()), valid);
+
+    #[bitfield(core::num::NonZeroU8, validate = true, new = false, default = false)]
+    struct Id {
+        #[bits(4, from = id_from, into = id_into)]
+        value: u8,
+        #[bits(4)]
+        __: u8,
+    }
+    const fn id_from(raw: u8) -> u8 {
+        if raw > 9 {
+            9
+        } else {
+            raw
+        }
+    }
+    const fn id_into(value: u8) -> u8 {
+        value
+    }
+    assert_eq!(Id::try_from_bits(0).err(), Some(0));
+    assert!(Id::try_from_bits(10).is_err());
+    let id = Id::try_from(core::num::NonZeroU8::new(9).unwrap()).unwrap();
+    assert_eq!(id.value(), 9);
+}
+
+#[test]
+fn validate_without_fallback() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    // Unknown patterns panic in `from_bits`, but not in the checks
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Mode {
+        Off = 0,
+        On = 1,
+        Auto = 3,
+    }
+    assert_eq!(Mode::try_from_bits(1), Some(Mode::On));
+    assert_eq!(Mode::try_from_bits(2), None);
+
+    #[bitfield(u8, validate = true, try_getters = true, arbitrary = true)]
+    struct Reg {
+        #[bits(2)]
+        mode: Mode,
+        #[bits(6)]
+        level: u8,
+    }
+
+    assert!(Reg::try_from(2u8).is_err());
+    assert_eq!(Reg::try_from(3u8).map(|r| r.mode()), Ok(Mode::Auto));
+    assert_eq!(Reg::from_bits(2).try_mode(), Err(2));
+    for byte in 0..=u8::MAX {
+        let data = [byte];
+        let reg = Reg::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert_ne!(reg.into_bits() & 0b11, 0b10);
+    }
+}
+
+#[test]
+fn new_visibility() {
+    mod inner {
+        use bitfield_struct::bitfield;
+
+        #[bitfield(u16, new = false, display = true)]
+        pub struct Port {
+            #[bits(15, default = 80)]
+            pub number: u16,
+            pub open: bool,
+        }
+
+        impl Port {
+            /// Custom constructor that rejects port 0
+            pub fn new(number: u16) -> Option<Self> {
+                (number != 0).then(|| Self::default().with_number(number))
+            }
+        }
+
+        #[bitfield(u8, new = pub(self))]
+        pub struct Flags {
+            #[bits(8, default = 3)]
+            pub value: u8,
+        }
+
+        pub fn flags() -> Flags {
+            Flags::new()
+        }
+    }
+
+    assert!(inner::Port::new(0).is_none());
+    assert_eq!(inner::Port::new(443).unwrap().number(), 443);
+    assert_eq!(inner::Port::default().number(), 80);
+    let port: inner::Port = "open".parse().unwrap();
+    assert_eq!(port.number(), 80);
+    assert_eq!(inner::flags().value(), 3);
+}
+
+#[test]
+fn new_with() {
+    #[bitfield(u16)]
+    struct Entry {
+        #[bits(4)]
+        kind: u8,
+        system: bool,
+        #[bits(2, default = 0b11)]
+        __: u8,
+        #[bits(3, access = RO)]
+        level: u8,
+        present: bool,
+        #[bits(5)]
+        offset: i8,
+    }
+
+    const TABLE: [Entry; 2] = [
+        Entry::new_with(3, true, 2, false, -1),
+        Entry::new_with(0xf, false, 7, true, 0),
+    ];
+    assert_eq!(TABLE[0].kind(), 3);
+    assert!(TABLE[0].system());
+    assert_eq!(TABLE[0].level(), 2);
+    assert!(!TABLE[0].present());
+    assert_eq!(TABLE[0].offset(), -1);
+    assert_eq!(TABLE[1].into_bits(), 0xf | 0b11 << 5 | 7 << 7 | 1 << 10);
+}
+
+#[test]
+fn builder() {
+    #[bitfield(u32, builder = true)]
+    struct Descriptor {
+        #[bits(20)]
+        base: u32,
+        #[bits(4, default = 0xa)]
+        kind: u8,
+        user: bool,
+        #[bits(7, default = 0)]
+        __: u8,
+    }
+
+    let desc = Descriptor::builder()
+        .kind(3)
+        .base(0x1234)
+        .user(true)
+        .build();
+    assert_eq!(desc.base(), 0x1234);
+    assert_eq!(desc.kind(), 3);
+    assert!(desc.user());
+
+    // optional fields keep their defaults
+    const DESC: Descriptor = Descriptor::builder().user(false).base(1).build();
+    assert_eq!(DESC.kind(), 0xa);
+    assert_eq!(DESC.into_bits(), 1 | 0xa << 20);
+
+    #[bitfield(u8, builder = FlagsBuilder)]
+    struct Flags {
+        #[bits(8, default = 1)]
+        value: u8,
+    }
+    let _: FlagsBuilder = Flags::builder();
+    assert_eq!(Flags::builder().build().value(), 1);
+
+    // the type state does not shadow other names
+    #[bitfield(u8, builder = true)]
+    struct B {
+        #[bits(8)]
+        b: u8,
+    }
+    assert_eq!(B::builder().b(3).build().b(), 3);
+}
+
+#[test]
+fn update_and_map() {
+    #[bitfield(u16)]
+    struct Counter {
+        #[bits(8)]
+        count: u8,
+        #[bits(7)]
+        level: i8,
+        flag: bool,
+    }
+
+    let mut counter = Counter::new().with_count(41).with_level(-3);
+    counter.update_count(|c| c + 1);
+    counter.update_flag(|f| !f);
+    assert_eq!(counter.count(), 42);
+    assert!(counter.flag());
+
+    let counter = counter.map_level(|l| l * 2).map_count(|c| c / 2);
+    assert_eq!(counter.level(), -6);
+    assert_eq!(counter.count(), 21);
+}
+
+#[test]
+fn replace() {
+    #[bitfield(u16)]
+    struct State {
+        #[bits(8)]
+        phase: u8,
+        #[bits(7)]
+        level: i8,
+        ready: bool,
+    }
+
+    let mut state = State::new().with_phase(1).with_level(-2);
+    assert_eq!(state.replace_phase(2), 1);
+    assert_eq!(state.replace_level(5), -2);
+    assert!(!state.replace_ready(true));
+    assert_eq!((state.phase(), state.level(), state.ready()), (2, 5, true));
+}
+
+#[test]
+fn toggle() {
+    #[bitfield(u8)]
+    struct Status {
+        ready: bool,
+        #[bits(access = RO)]
+        busy: bool,
+        #[bits(6)]
+        __: u8,
+    }
+
+    let mut status = Status::new();
+    status.toggle_ready();
+    assert!(status.ready());
+    status.toggle_ready();
+    assert!(!status.ready());
+
+    const READY: Status = Status::new().with_ready_toggled();
+    assert!(READY.ready());
+    assert_eq!(READY.with_ready_toggled().into_bits(), 0);
+}
+
+#[test]
+fn modify() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[bitfield(u32, atomic = true)]
+    struct Reg {
+        #[bits(8)]
+        a: u8,
+        #[bits(8)]
+        b: u8,
+        enable: bool,
+        #[bits(15)]
+        rest: u16,
+    }
+
+    let mut reg = Reg::new().with_rest(7);
+    reg.modify(|r| r.with_a(1).with_b(2));
+    assert_eq!((reg.a(), reg.b(), reg.rest()), (1, 2, 7));
+
+    let atomic = AtomicReg::new(reg);
+    let prev = atomic.modify(|r| r.with_b(0xff).with_enable(true), Ordering::AcqRel);
+    assert_eq!(prev.into_bits(), reg.into_bits());
+    let reg = atomic.load(Ordering::Relaxed);
+    assert_eq!(
+        (reg.a(), reg.b(), reg.enable(), reg.rest()),
+        (1, 0xff, true, 7)
+    );
+
+    let raw = AtomicU32::new(u32::MAX);
+    Reg::modify_atomic(&raw, |r| r.with_a(0).with_enable(false), Ordering::Relaxed);
+    assert_eq!(raw.load(Ordering::Relaxed), !0xff & !(1 << 16));
+
+    // The closure gets the current value
+    let raw = AtomicU32::new(Reg::new().with_a(1).with_b(2).into_bits());
+    Reg::modify_atomic(&raw, |r| r.with_a(r.a() + r.b()), Ordering::Relaxed);
+    let reg = Reg::from_bits(raw.load(Ordering::Relaxed));
+    assert_eq!((reg.a(), reg.b()), (3, 2));
+}
+
+#[test]
+fn modify_field() {
+    use std::sync::atomic::Ordering;
+
+    // Fields named `modify` take precedence over the `modify` functions
+    #[bitfield(u8, atomic = true)]
+    struct Cmd {
+        #[bits(4)]
+        modify: u8,
+        #[bits(4)]
+        modify_atomic: u8,
+    }
+
+    let cmd = Cmd::new().with_modify(3).with_modify_atomic(5);
+    assert_eq!((cmd.modify(), cmd.modify_atomic()), (3, 5));
+    let atomic = AtomicCmd::new(cmd);
+    atomic
+        .fetch_update_modify(Ordering::Relaxed, Ordering::Relaxed, |m| Some(m + 1))
+        .unwrap();
+    assert_eq!(atomic.load(Ordering::Relaxed).modify(), 4);
+}
+
+#[test]
+fn occupied_mask() {
+    #[bitfield(u16)]
+    struct Reg {
+        #[bits(4)]
+        a: u8,
+        #[bits(4)]
+        __: u8,
+        #[bits(6)]
+        b: u8,
+        #[bits(2)]
+        __: u8,
+    }
+    assert_eq!(Reg::OCCUPIED_MASK, 0x3f0f);
+    assert_eq!(Reg::PADDING_MASK, 0xc0f0);
+
+    #[bitfield(u32)]
+    struct Placed {
+        #[bits(24..32)]
+        high: u8,
+        #[bits(4..8)]
+        mid: u8,
+    }
+    assert_eq!(Placed::OCCUPIED_MASK, 0xff00_00f0);
+    assert_eq!(Placed::PADDING_MASK, 0x00ff_ff0f);
+
+    #[bitfield([u8; 3])]
+    struct Wide {
+        #[bits(12)]
+        value: u16,
+        #[bits(12)]
+        __: u16,
+    }
+    assert_eq!(Wide::OCCUPIED_MASK, [0xff, 0x0f, 0]);
+    assert_eq!(Wide::PADDING_MASK, [0, 0xf0, 0xff]);
+}
+
+#[test]
+fn occupied_mask_field_names() {
+    // The masks of these fields take precedence over those of the bitfield
+    #[bitfield(u8, eq = true)]
+    struct Slot {
+        #[bits(2)]
+        occupied: u8,
+        #[bits(4)]
+        padding: u8,
+        #[bits(2)]
+        __: u8,
+    }
+    assert_eq!(Slot::OCCUPIED_MASK, 0x03);
+    assert_eq!(Slot::PADDING_MASK, 0x3c);
+    assert_eq!(Slot::from_bits(0x05), Slot::from_bits(0xc5));
+}
+
+#[test]
+fn eq_ignores_padding() {
+    use std::collections::HashSet;
+
+    #[bitfield(u16, eq = true)]
+    struct Reg {
+        #[bits(4)]
+        a: u8,
+        #[bits(4)]
+        __: u8,
+        #[bits(8)]
+        b: u8,
+    }
+
+    let reg = Reg::new().with_a(3).with_b(4);
+    let garbage = Reg::from_bits(reg.into_bits() | 0xf0);
+    assert_eq!(reg, garbage);
+    assert_ne!(reg, reg.with_b(5));
+    let set: HashSet<_> = [reg, garbage].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    #[bitfield([u8; 2], eq = true)]
+    struct Wide {
+        #[bits(12)]
+        value: u16,
+        #[bits(4)]
+        __: u8,
+    }
+    assert_eq!(Wide::from_bits([1, 0xf2]), Wide::from_bits([1, 0x02]));
+    assert_ne!(Wide::from_bits([1, 0xf2]), Wide::from_bits([1, 0x03]));
+}
+
+#[test]
+fn pad_auto() {
+    #[bitfield(u32, pad = auto, validate = true)]
+    struct Reg {
+        #[bits(4)]
+        kind: u8,
+        enable: bool,
+    }
+    let reg = Reg::new().with_kind(0xf).with_enable(true);
+    assert_eq!(reg.into_bits(), 0x1f);
+    assert_eq!(Reg::PADDING_MASK, !0x1f);
+    assert!(Reg::try_from(0x20).is_err());
+
+    #[bitfield(u16, pad = auto, order = Msb)]
+    struct Msb {
+        #[bits(4)]
+        kind: u8,
+    }
+    assert_eq!(Msb::new().with_kind(0xa).into_bits(), 0xa000);
+    assert_eq!(Msb::KIND_OFFSET, 12);
+}
+
+#[test]
+fn flags() {
+    #[bitfield(u8, flags = true, pad = auto)]
+    struct Perm {
+        read: bool,
+        write: bool,
+        exec: bool,
+    }
+
+    const RW: Perm = Perm::READ.union(Perm::WRITE);
+    assert!(RW.read() && RW.write() && !RW.exec());
+    assert!(RW.contains(Perm::READ));
+    assert!(!RW.contains(Perm::READ.union(Perm::EXEC)));
+    assert!(RW.intersects(Perm::WRITE.union(Perm::EXEC)));
+    assert!(!RW.intersects(Perm::EXEC));
+    assert_eq!(
+        RW.difference(Perm::READ).into_bits(),
+        Perm::WRITE.into_bits()
+    );
+    assert_eq!(Perm::EXEC.into_bits(), 0b100);
+}
+
+#[test]
+fn ops() {
+    #[bitfield(u16, ops = true)]
+    struct Irq {
+        timer: bool,
+        uart: bool,
+        #[bits(14)]
+        other: u16,
+    }
+
+    let timer = Irq::new().with_timer(true);
+    let uart = Irq::new().with_uart(true);
+    let both = timer | uart;
+    assert!(both.timer() && both.uart());
+    assert_eq!((both & uart).into_bits(), uart.into_bits());
+    assert_eq!((both ^ uart).into_bits(), timer.into_bits());
+    assert_eq!((!both).into_bits(), !0b11);
+
+    #[bitfield([u8; 2], ops = true)]
+    struct Wide {
+        #[bits(16)]
+        value: u16,
+    }
+    let wide = Wide::from_bits([0xf0, 0x0f]) | Wide::from_bits([0x0f, 0x00]);
+    assert_eq!(wide.into_bits(), [0xff, 0x0f]);
+    assert_eq!((!wide).into_bits(), [0x00, 0xf0]);
+
+    #[bitfield(core::num::NonZeroU8, ops = true, new = false, default = false)]
+    struct Mask {
+        a: bool,
+        b: bool,
+        #[bits(6)]
+        __: u8,
+    }
+    let mask = Mask::from_bits(0b01) | Mask::from_bits(0b10);
+    assert!(mask.a() && mask.b());
+}
+
+#[test]
+fn field_array() {
+    #[bitfield(u32, validate = true)]
+    struct Prio {
+        #[bits(2 * 8)]
+        prio: [u8; 8],
+        #[bits(3, default = 2)]
+        lanes: [i8; 4],
+        #[bits(4)]
+        __: u8,
+    }
+
+    let mut p = Prio::new().with_prio(0, 3).with_prio(7, 2);
+    p.set_lanes(1, -1);
+    assert_eq!(p.prio(0), 3);
+    assert_eq!(p.prio(1), 0);
+    assert_eq!(p.prio(7), 2);
+    assert_eq!(p.lanes(0), 2);
+    assert_eq!(p.lanes(1), -1);
+    assert_eq!(p.into_bits() & 0xffff, 0x8003);
+    assert_eq!(Prio::PRIO_LEN, 8);
+    assert_eq!(Prio::LANES_OFFSET, 16);
+    assert_eq!(Prio::LANES_BITS, 12);
+    assert_eq!(Prio::OCCUPIED_MASK, 0x0fff_ffff);
+    assert!(Prio::try_from(p.into_bits()).is_ok());
+    let q = Prio::new_with([3, 0, 0, 0, 0, 0, 0, 2], [2, -1, 2, 2]);
+    assert_eq!(q.into_bits(), p.into_bits());
+    assert_eq!(
+        format!("{p:?}"),
+        "Prio { prio: [3, 0, 0, 0, 0, 0, 0, 2], lanes: [2, -1, 2, 2] }"
+    );
+
+    #[bitfield(u8, order = Msb)]
+    struct Flags {
+        flags: [bool; 8],
+    }
+    let flags = Flags::new().with_flags(0, true);
+    assert_eq!(flags.into_bits(), 0x80);
+    assert!(flags.flags(0) && !flags.flags(7));
+}
+
+#[test]
+#[should_panic]
+fn field_array_out_of_bounds() {
+    #[bitfield(u8)]
+    struct Flags {
+        flags: [bool; 8],
+    }
+    let _ = Flags::new().flags(8);
+}
+
+#[test]
+fn split_field() {
+    #[bitfield(u64, validate = true)]
+    struct Segment {
+        #[bits(16)]
+        limit_low: u16,
+        #[bits([16..40, 56..64])]
+        base: u32,
+        #[bits(40..56)]
+        flags: u16,
+    }
+
+    let seg = Segment::new().with_base(0x1234_5678).with_flags(0xffff);
+    assert_eq!(seg.base(), 0x1234_5678);
+    assert_eq!(seg.into_bits(), 0x12ff_ff34_5678_0000);
+    assert_eq!(seg.with_base(0).into_bits(), 0x00ff_ff00_0000_0000);
+    assert_eq!(Segment::BASE_MASK, 0xff00_00ff_ffff_0000);
+    assert_eq!(Segment::BASE_BITS, 32);
+    assert_eq!(Segment::OCCUPIED_MASK, u64::MAX);
+    assert!(Segment::try_from(seg.into_bits()).is_ok());
+
+    #[bitfield(u16, pad = auto)]
+    struct Msb {
+        #[bits([0..2, 8..10])]
+        value: i8,
+        next: bool,
+    }
+    let msb = Msb::new().with_value(-2);
+    assert_eq!(msb.value(), -2);
+    assert_eq!(msb.into_bits(), 0b11_0000_0010);
+    assert_eq!(Msb::NEXT_OFFSET, 10);
+}
+
+#[test]
+fn views() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Kind {
+        Short,
+        Long,
+        Other(u8),
+    }
+    impl Kind {
+        const fn into_bits(self) -> u8 {
+            match self {
+                Self::Short => 0,
+                Self::Long => 1,
+                Self::Other(v) => v,
+            }
+        }
+        const fn from_bits(value: u8) -> Self {
+            match value {
+                0 => Self::Short,
+                1 => Self::Long,
+                v => Self::Other(v),
+            }
+        }
+    }
+
+    #[bitfield(u16)]
+    struct Packet {
+        #[bits(2, view = Kind::Short => short: Short, view = Kind::Long => long: Long)]
+        kind: Kind,
+        #[bits(14)]
+        __: u16,
+    }
+
+    #[bitfield(u16)]
+    struct Short {
+        #[bits(2)]
+        __: u8,
+        #[bits(14)]
+        value: u16,
+    }
+
+    #[bitfield(u16)]
+    struct Long {
+        #[bits(8)]
+        __: u8,
+        value: u8,
+    }
+
+    let short = Packet::from_bits(0x0010);
+    assert_eq!(short.as_short().unwrap().value(), 4);
+    assert!(short.as_long().is_none());
+
+    let long = Packet::from_bits(0x1201);
+    assert_eq!(long.as_long().unwrap().value(), 0x12);
+    assert!(long.as_short().is_none());
+
+    let other = Packet::from_bits(0x0003);
+    assert_eq!(other.kind(), Kind::Other(3));
+    assert!(other.as_short().is_none() && other.as_long().is_none());
+}
+
+#[test]
+fn overlapping_alias() {
+    #[bitfield(u32)]
+    struct Status {
+        #[bits(16, overlaps)]
+        raw_status: u16,
+        busy: bool,
+        error: bool,
+        #[bits(14, default = 0x5)]
+        code: u16,
+        #[bits(16)]
+        data: u16,
+    }
+
+    let status = Status::new();
+    assert_eq!(status.raw_status(), 0x14);
+    let status = status.with_busy(true).with_data(0xabcd);
+    assert_eq!(status.raw_status(), 0x15);
+    assert_eq!(status.with_raw_status(0xfffe).code(), 0x3fff);
+    assert!(!status.with_raw_status(0xfffe).busy());
+    assert_eq!(status.data(), 0xabcd);
+    assert_eq!(Status::RAW_STATUS_MASK, 0xffff);
+    assert_eq!(Status::BUSY_OFFSET, 0);
+
+    let status = Status::new_with(true, true, 1, 2);
+    assert_eq!(status.raw_status(), 0b111);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn nonzero_fields() {
+    use std::num::{NonZeroU16, NonZeroU8};
+
+    #[bitfield(u32)]
+    struct Queue {
+        #[bits(4)]
+        count: NonZeroU8,
+        #[bits(12)]
+        next: Option<NonZeroU16>,
+        full: NonZeroU8,
+        #[bits(8)]
+        __: u8,
+    }
+
+    let queue = Queue::new();
+    assert_eq!(queue.count().get(), 1);
+    assert_eq!(queue.next(), None);
+    assert_eq!(queue.full().get(), 1);
+    assert_eq!(queue.into_bits(), 0);
+
+    let queue = queue
+        .with_count(NonZeroU8::new(16).unwrap())
+        .with_next(NonZeroU16::new(0xabc))
+        .with_full(NonZeroU8::new(3).unwrap());
+    assert_eq!(queue.into_bits(), 0x02_abc_f);
+    assert_eq!(queue.count().get(), 16);
+    assert_eq!(queue.next().unwrap().get(), 0xabc);
+    assert_eq!(queue.full().get(), 3);
+    // the all-ones pattern of full sized fields saturates
+    assert_eq!(Queue::from_bits(0xff_0000).full(), NonZeroU8::MAX);
+}
+
+#[test]
+fn option_sentinel() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Mode {
+        Low,
+        High,
+    }
+    impl Mode {
+        const fn into_bits(self) -> u8 {
+            self as _
+        }
+        const fn from_bits(value: u8) -> Self {
+            match value {
+                0 => Self::Low,
+                _ => Self::High,
+            }
+        }
+    }
+
+    #[bitfield(u16)]
+    struct Entry {
+        #[bits(6, none = 0x3f)]
+        owner: Option<u8>,
+        #[bits(2, none = 3)]
+        mode: Option<Mode>,
+        #[bits(8, none = 0, default = Some(7))]
+        level: Option<u8>,
+    }
+
+    let entry = Entry::new();
+    assert_eq!(entry.owner(), None);
+    assert_eq!(entry.mode(), None);
+    assert_eq!(entry.level(), Some(7));
+    assert_eq!(entry.into_bits(), 0x07ff);
+
+    let entry = entry.with_owner(Some(5)).with_mode(Some(Mode::High));
+    assert_eq!(entry.owner(), Some(5));
+    assert_eq!(entry.mode(), Some(Mode::High));
+    assert_eq!(entry.into_bits(), 0x0745);
+    assert_eq!(entry.with_level(None).into_bits(), 0x0045);
+}
+
+#[test]
+#[should_panic(expected = "Entry::owner: value collides with the `None` pattern")]
+fn option_sentinel_collision() {
+    #[bitfield(u8)]
+    struct Entry {
+        #[bits(6, none = 0x3f)]
+        owner: Option<u8>,
+        #[bits(2)]
+        __: u8,
+    }
+    let _ = Entry::new().with_owner(Some(0x3f));
+}
+
+#[test]
+fn scaled_fields() {
+    #[bitfield(u32)]
+    struct Sensor {
+        #[bits(10, scale = 0.25, bias = -40.0)]
+        temperature: f32,
+        #[bits(12, scale = 0.001)]
+        voltage: f64,
+        #[bits(10)]
+        __: u16,
+    }
+
+    let sensor = Sensor::new();
+    assert_eq!(sensor.temperature(), -40.0);
+    assert_eq!(sensor.voltage(), 0.0);
+    assert_eq!(sensor.into_bits(), 0);
+
+    let sensor = sensor.with_temperature(21.3).with_voltage(3.3);
+    assert_eq!(sensor.temperature(), 21.25);
+    assert_eq!(sensor.into_bits() & 0x3ff, 245);
+    assert!((sensor.voltage() - 3.3).abs() < 1e-9);
+    // below the bias saturates to zero
+    assert_eq!(sensor.with_temperature(-50.0).temperature(), -40.0);
+}
+
+#[test]
+fn accessor_names() {
+    #[bitfield(u16, display = true, try_setters = true)]
+    struct Register {
+        #[bits(8, get = read_kind, set = write_kind, with = kind_with)]
+        kind: u8,
+        #[bits(get = is_busy)]
+        busy: bool,
+        #[bits(7)]
+        level: u8,
+    }
+
+    let mut reg = Register::new().kind_with(3);
+    reg.write_kind(reg.read_kind() + 1);
+    assert_eq!(reg.read_kind(), 4);
+    reg.toggle_busy();
+    assert!(reg.is_busy());
+    reg.update_kind(|k| k * 2);
+    assert_eq!(reg.try_with_kind(9).unwrap().read_kind(), 9);
+    assert_eq!(reg.into_bits(), 0x0108);
+    assert_eq!(
+        format!("{reg:?}"),
+        "Register { kind: 8, busy: true, level: 0 }"
+    );
+    assert_eq!(reg.to_string(), "kind=8 busy level=0");
+}
+
+#[test]
+fn without_setters() {
+    #[bitfield(u16, setters = false, display = true, field_enum = Field, try_setters = true)]
+    struct Frozen {
+        #[bits(8)]
+        kind: u8,
+        flag: bool,
+        #[bits(7)]
+        level: u8,
+    }
+
+    let frozen = Frozen::new().with_kind(3).with_flag_toggled();
+    assert!(frozen.try_with_level(0x80).is_err());
+    assert_eq!(frozen.map_kind(|k| k + 1).kind(), 4);
+    assert_eq!(frozen.get(Field::Kind), 3);
+    assert_eq!(
+        "kind=3 flag level=0".parse::<Frozen>().unwrap().into_bits(),
+        frozen.into_bits()
+    );
+}
+
+#[test]
+fn field_order() {
+    #[bitfield(u16)]
+    struct Header {
+        #[bits(4)]
+        low: u8,
+        #[bits(4, order = Msb)]
+        version: u8,
+        #[bits(3, order = Msb)]
+        kind: u8,
+        #[bits(5)]
+        next: u8,
+    }
+
+    assert_eq!(Header::LOW_OFFSET, 0);
+    assert_eq!(Header::VERSION_OFFSET, 12);
+    assert_eq!(Header::KIND_OFFSET, 9);
+    assert_eq!(Header::NEXT_OFFSET, 4);
+    let header = Header::new().with_version(0xa).with_kind(0b101).with_low(3);
+    assert_eq!(header.into_bits(), 0xaa03);
+
+    #[bitfield(u8, order = Msb)]
+    struct Mixed {
+        #[bits(2)]
+        top: u8,
+        #[bits(2, order = Lsb)]
+        bottom: u8,
+        #[bits(4)]
+        middle: u8,
+    }
+    assert_eq!(Mixed::TOP_OFFSET, 6);
+    assert_eq!(Mixed::BOTTOM_OFFSET, 0);
+    assert_eq!(Mixed::MIDDLE_OFFSET, 2);
+}
+
+#[test]
+fn reversed_fields() {
+    #[bitfield(u32, field_enum = true)]
+    struct Frame {
+        #[bits(8, reverse = true)]
+        data: u8,
+        #[bits(5, reverse = true, default = -3)]
+        delta: i8,
+        #[bits(3)]
+        __: u8,
+        #[bits(2 * 4, reverse = true)]
+        pairs: [u8; 4],
+        #[bits(8)]
+        plain: u8,
+    }
+
+    let frame = Frame::new();
+    assert_eq!(frame.delta(), -3);
+    // -3 is 0b11101, reversed 0b10111
+    assert_eq!(frame.into_bits(), 0b10111 << 8);
+
+    let frame = frame
+        .with_data(0b1100_0001)
+        .with_pairs(0, 0b01)
+        .with_plain(0x5a);
+    assert_eq!(frame.data(), 0b1100_0001);
+    assert_eq!(frame.into_bits() & 0xff, 0b1000_0011);
+    assert_eq!(frame.into_bits() >> 16, 0x5a02);
+    assert_eq!(frame.pairs(0), 0b01);
+    assert_eq!(frame.get(FrameField::Data), 0b1100_0001);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn big_endian_fields() {
+    #[bitfield(u64)]
+    struct Packet {
+        #[bits(16, endian = big)]
+        port: u16,
+        #[bits(24, endian = big)]
+        seq: u32,
+        #[bits(16, endian = little)]
+        len: u16,
+        #[bits(8)]
+        __: u8,
+    }
+
+    let packet = Packet::new()
+        .with_port(0x1234)
+        .with_seq(0xabcdef)
+        .with_len(0x5678);
+    assert_eq!(packet.port(), 0x1234);
+    assert_eq!(packet.seq(), 0xabcdef);
+    assert_eq!(packet.into_bits(), 0x5678_efcdab_3412);
+    assert_eq!(packet.to_le_bytes()[..5], [0x12, 0x34, 0xab, 0xcd, 0xef]);
+}
+
+#[test]
+fn mixed_endian_bytes() {
+    #[bitfield([u8; 8], endian = big)]
+    struct Header {
+        #[bits(16)]
+        kind: u16,
+        #[bits(16, endian = little)]
+        legacy: u16,
+        #[bits(24)]
+        len: u32,
+        flags: u8,
+    }
+
+    let header = Header::new()
+        .with_kind(0x1234)
+        .with_legacy(0x5678)
+        .with_len(0xabcdef)
+        .with_flags(0x42);
+    assert_eq!(header.0, [0x12, 0x34, 0x78, 0x56, 0xab, 0xcd, 0xef, 0x42]);
+    assert_eq!(header.kind(), 0x1234);
+    assert_eq!(header.legacy(), 0x5678);
+    assert_eq!(header.len(), 0xabcdef);
+
+    let header = Header::from_bits([0, 1, 2, 0, 0, 0, 3, 0]);
+    assert_eq!((header.kind(), header.legacy(), header.len()), (1, 2, 3));
+}
+
+#[test]
+fn exhaustive_conversion() {
+    use bitfield_struct::FromBits;
+
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Speed {
+        Off = 0,
+        Slow = 1,
+        Medium = 2,
+        Fast = 3,
+    }
+
+    #[bitfield(u8)]
+    struct Fan {
+        #[bits(2, exhaustive, default = Speed::Slow)]
+        speed: Speed,
+        #[bits(6, exhaustive)]
+        level: u8,
+    }
+
+    assert_eq!(Fan::new().speed(), Speed::Slow);
+    assert_eq!(Fan::from_bits(3).speed(), Speed::Fast);
+}
+
+#[test]
+fn fallible_getters() {
+    use bitfield_struct::FromBits;
+
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Mode {
+        Off = 0,
+        On = 1,
+        #[fallback]
+        Reserved = 3,
+    }
+
+    #[bitfield(u8, try_getters = true)]
+    struct Control {
+        #[bits(2)]
+        mode: Mode,
+        #[bits(6)]
+        level: u8,
+    }
+
+    assert_eq!(Control::from_bits(1).try_mode(), Ok(Mode::On));
+    assert_eq!(Control::from_bits(3).try_mode(), Ok(Mode::Reserved));
+    assert_eq!(Control::from_bits(2).mode(), Mode::Reserved);
+    assert_eq!(Control::from_bits(2).try_mode(), Err(2));
+}
+
+#[test]
+fn checked_widths() {
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(i8)]
+    enum Signed {
+        Low = -4,
+        High = 3,
+    }
+    #[derive(FromBits, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Level {
+        Off = 0,
+        Max = 5,
+    }
+    assert_eq!(Signed::BITS, 3);
+    assert_eq!(Level::BITS, 3);
+
+    #[bitfield(u8)]
+    struct Inner {
+        #[bits(3, checked)]
+        level: Level,
+        #[bits(5)]
+        __: u8,
+    }
+    assert_eq!(Inner::BITS, 8);
+
+    #[bitfield(u16)]
+    struct Outer {
+        #[bits(8, checked)]
+        inner: Inner,
+        #[bits(8)]
+        __: u8,
+    }
+    let outer = Outer::new().with_inner(Inner::new().with_level(Level::Max));
+    assert_eq!(outer.inner().level(), Level::Max);
+    assert_eq!(Outer::BITS, 16);
+}
+
+#[test]
+fn cfg_fields() {
+    #[bitfield(u8)]
+    struct Conditional {
+        #[cfg(test)]
+        #[bits(2)]
+        extra: u8,
+        #[cfg(not(test))]
+        #[bits(4)]
+        missing: u8,
+        #[bits(6)]
+        value: u8,
+    }
+    let val = Conditional::new().with_extra(3).with_value(0x15);
+    assert_eq!(val.into_bits(), 0x57);
+    assert_eq!(Conditional::VALUE_OFFSET, 2);
+    assert_eq!(Conditional::FIELDS.len(), 2);
+}
+
+#[test]
+fn validated_fields() {
+    const fn is_valid_asid(asid: &u8) -> bool {
+        *asid != 0x7f
+    }
+
+    #[bitfield(u16, validate = true, try_setters = true)]
+    struct Context {
+        #[bits(7, validate = is_valid_asid)]
+        asid: u8,
+        #[bits(9)]
+        __: u16,
+    }
+
+    let ctx = Context::new().with_asid(5);
+    assert_eq!(ctx.asid(), 5);
+    assert!(ctx.try_with_asid(0x7f).is_err());
+    assert!(Context::try_from_bits(0x7f).is_err());
+    assert!(Context::try_from_bits(0x7e).is_ok());
+}
+
+#[test]
+#[should_panic = "Context::asid: value rejected by `is_valid_asid`"]
+#[cfg(debug_assertions)]
+fn validated_fields_panic() {
+    const fn is_valid_asid(asid: &u8) -> bool {
+        *asid != 0x7f
+    }
+
+    #[bitfield(u16)]
+    struct Context {
+        #[bits(7, validate = is_valid_asid)]
+        asid: u8,
+        #[bits(9)]
+        __: u16,
+    }
+
+    let _ = Context::new().with_asid(0x7f);
+}
+
+#[test]
+fn invariants() {
+    const fn divider_set(clock: &Clock) -> bool {
+        !clock.enable() || clock.divider() != 0
+    }
+
+    #[bitfield(
+        u8,
+        invariant = divider_set,
+        validate = true,
+        builder = true,
+        display = true,
+        field_enum = true
+    )]
+    struct Clock {
+        enable: bool,
+        #[bits(7, default = 0)]
+        divider: u8,
+    }
+
+    let mut clock = Clock::new_with(true, 4);
+    clock.set_divider(2);
+    clock.modify(|c| c.with_divider(0).with_enable(false));
+    assert!(!clock.enable());
+    let clock = Clock::builder().enable(true).divider(1).build();
+    assert_eq!(clock.into_bits(), 0b11);
+    assert!(Clock::try_from_bits(0b01).is_err());
+    assert!(Clock::try_from_bits(0b10).is_ok());
+
+    // The fallible functions reject violations
+    assert_eq!(
+        "enable divider=1".parse::<Clock>().map(|c| c.divider()),
+        Ok(1)
+    );
+    assert_eq!("enable".parse::<Clock>().err(), Some(ClockError::Invalid));
+    let mut clock = Clock::new();
+    assert_eq!(clock.set(ClockField::Enable, 1), Err(ClockError::Invalid));
+    assert_eq!(clock.into_bits(), 0);
+}
+
+#[test]
+#[should_panic = "Clock: invariant `divider_set` violated"]
+#[cfg(debug_assertions)]
+fn invariants_atomic_panic() {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    const fn divider_set(clock: &Clock) -> bool {
+        !clock.enable() || clock.divider() != 0
+    }
+
+    #[bitfield(u8, invariant = divider_set, atomic = true)]
+    struct Clock {
+        enable: bool,
+        #[bits(7)]
+        divider: u8,
+    }
+
+    let raw = AtomicU8::new(0);
+    Clock::set_enable_atomic(&raw, true, Ordering::Relaxed);
+}
+
+#[test]
+#[should_panic = "Clock: invariant `divider_set` violated"]
+#[cfg(debug_assertions)]
+fn invariants_panic() {
+    const fn divider_set(clock: &Clock) -> bool {
+        !clock.enable() || clock.divider() != 0
+    }
+
+    #[bitfield(u8, invariant = divider_set)]
+    struct Clock {
+        enable: bool,
+        #[bits(7)]
+        divider: u8,
+    }
+
+    let mut clock = Clock::new().with_divider(1).with_enable(true);
+    clock.set_divider(0);
+}
+
+#[test]
+fn computed_fields() {
+    #[bitfield(u16)]
+    struct Packet {
+        #[bits(4)]
+        hdr_len: u8,
+        #[bits(12)]
+        payload_len: u16,
+        /// The length of the whole packet
+        #[bits(computed = self.hdr_len() as u16 * 4 + self.payload_len())]
+        total_len: u16,
+    }
+
+    let packet = Packet::new().with_hdr_len(5).with_payload_len(100);
+    assert_eq!(packet.total_len(), 120);
+    assert_eq!(Packet::FIELDS.len(), 2);
+    assert_eq!(
+        format!("{packet:?}"),
+        "Packet { hdr_len: 5, payload_len: 100 }"
+    );
+}
+
+#[test]
+fn debug_formats() {
+    #[bitfield(u32)]
+    struct Dump {
+        #[bits(12, fmt = hex)]
+        addr: u16,
+        #[bits(4, fmt = binary)]
+        mask: u8,
+        #[bits(4 * 2, fmt = "{:02}")]
+        slots: [u8; 2],
+        #[bits(8)]
+        count: u8,
+    }
+
+    let dump = Dump::new()
+        .with_addr(0xabc)
+        .with_mask(0b101)
+        .with_slots(1, 7)
+        .with_count(12);
+    assert_eq!(
+        format!("{dump:?}"),
+        "Dump { addr: 0xabc, mask: 0b101, slots: [00, 07], count: 12 }"
+    );
+}
+
+#[test]
+fn debug_alternate() {
+    #[bitfield(u8)]
+    struct Status {
+        #[bits(5, fmt = hex)]
+        code: u8,
+        #[bits(2)]
+        level: u8,
+        #[bits(1, redact)]
+        secret: bool,
+    }
+
+    let status = Status::new().with_code(0x11).with_level(2);
+    assert_eq!(
+        format!("{status:#?}"),
+        "Status {\n    code: 0x11 (bits 0..5 = 0b10001),\n    level: 2 (bits 5..7 = 0b10),\n    secret: ***,\n}"
+    );
+}
+
+#[test]
+fn register_block() {
+    use bitfield_struct::register_block;
+
+    #[bitfield(u32)]
+    struct Control {
+        enable: bool,
+        #[bits(31)]
+        divider: u32,
+    }
+
+    #[bitfield(u32)]
+    struct Status {
+        busy: bool,
+        #[bits(31)]
+        __: u32,
+    }
+
+    #[register_block]
+    struct Uart {
+        #[offset(0x00)]
+        control: Control,
+        /// The status register
+        #[offset(0x08)]
+        status: Status,
+    }
+
+    let mut memory = [0u32; 3];
+    let uart = unsafe { Uart::new(memory.as_mut_ptr().cast()) };
+    uart.write_control(Control::new().with_divider(4));
+    uart.modify_control(|c| c.with_enable(true));
+    uart.write_status(Status::new().with_busy(true));
+    assert_eq!(uart.read_control().divider(), 4);
+    assert!(uart.read_control().enable());
+    assert!(uart.read_status().busy());
+    assert_eq!(Uart::STATUS_OFFSET, 8);
+    assert_eq!(memory, [4 << 1 | 1, 0, 1]);
+
+    #[bitfield(u32)]
+    struct Interrupts {
+        enable: bool,
+        #[bits(access = W1C)]
+        rx: bool,
+        #[bits(30)]
+        __: u32,
+    }
+
+    #[register_block]
+    struct Irq {
+        #[offset(0x00)]
+        interrupts: Interrupts,
+        #[offset(0x04)]
+        counter: u32,
+    }
+
+    let mut memory = [0b10u32, 7];
+    let irq = unsafe { Irq::new(memory.as_mut_ptr().cast()) };
+    // Writing back the pending interrupt would clear it
+    irq.modify_interrupts(|i| i.with_enable(true));
+    irq.modify_counter(|c| c + 1);
+    assert_eq!(memory, [0b01, 8]);
+    irq.modify_interrupts(|i| i.with_rx_cleared());
+    assert_eq!(memory[0], 0b11);
+}
+
+#[test]
+fn svd_api() {
+    #[bitfield(u32, svd = true)]
+    struct Control {
+        enable: bool,
+        #[bits(7, default = 2)]
+        divider: u8,
+        #[bits(access = RO)]
+        busy: bool,
+        #[bits(23)]
+        __: u32,
+    }
+
+    let mut control = Control::new();
+    control.write(|w| w.enable().set_bit().divider().bits(4));
+    assert!(control.read().enable().bit_is_set());
+    assert_eq!(control.read().divider().bits(), 4);
+
+    control.modify(|r, w| w.divider().bits(r.divider().bits() + 1));
+    assert_eq!(control.divider(), 5);
+    assert!(control.enable());
+
+    control.modify(|_, w| w.enable().clear_bit());
+    assert!(control.read().enable().bit_is_clear());
+    assert!(!control.read().busy().bit());
+
+    // `write` starts from the defaults
+    control.write(|w| w.enable().bit(true));
+    assert_eq!(control.divider(), 2);
+    assert_eq!(control.read().bits(), 0b101);
+    control.write(|w| w.bits(1 << 8));
+    assert!(control.busy());
+}
+
+#[test]
+fn w1c_fields() {
+    #[bitfield(u8)]
+    struct Interrupts {
+        enable: bool,
+        #[bits(access = W1C)]
+        rx: bool,
+        #[bits(2, access = W1C)]
+        errors: u8,
+        #[bits(4)]
+        __: u8,
+    }
+
+    let pending = Interrupts::from_bits(0b1111);
+    assert!(pending.rx());
+    assert_eq!(pending.errors(), 3);
+    assert_eq!(pending.without_w1c().into_bits(), 0b1);
+
+    // Only the written ones clear interrupts
+    let ack = Interrupts::new().with_rx_cleared();
+    assert_eq!(ack.into_bits(), 0b10);
+    let mut ack = Interrupts::new();
+    ack.clear_errors();
+    assert_eq!(ack.into_bits(), 0b1100);
+
+    // Writing back does not clear the pending interrupts
+    let mut value = pending;
+    value.modify(|v| v.with_enable(false));
+    assert_eq!(value.into_bits(), 0);
+    let mut value = pending;
+    value.modify(|v| v.with_errors_cleared());
+    assert_eq!(value.into_bits(), 0b1101);
+}
+
+#[test]
+fn read_to_clear_fields() {
+    #[bitfield(u16)]
+    struct Counters {
+        #[bits(8, access = RC)]
+        dropped: u8,
+        #[bits(7)]
+        queued: u8,
+        #[bits(access = RC)]
+        overflow: bool,
+    }
+
+    let mut counters = Counters::from_bits(0x8305);
+    assert_eq!(counters.take_dropped(), 5);
+    assert_eq!(counters.take_dropped(), 0);
+    assert_eq!(counters.queued(), 3);
+    assert!(counters.take_overflow());
+    assert!(!counters.take_overflow());
+    assert_eq!(counters.into_bits(), 0x0300);
+    assert_eq!(format!("{counters:?}"), "Counters { queued: 3 }");
+
+    #[bitfield(u8, setters = false)]
+    struct Frozen {
+        #[bits(4, access = RC)]
+        dropped: u8,
+        #[bits(4)]
+        queued: u8,
+    }
+
+    let (frozen, dropped) = Frozen::from_bits(0x35).take_dropped();
+    assert_eq!(dropped, 5);
+    assert_eq!(frozen.into_bits(), 0x30);
+    const TAKEN: (Frozen, u8) = Frozen::from_bits(0x35).take_dropped();
+    assert_eq!(TAKEN.1, 5);
+}
+
+#[test]
+#[deny(unused_must_use)]
+fn must_use_getters() {
+    #[bitfield(u8, must_use_getters = true)]
+    struct Flags {
+        enable: bool,
+        #[bits(3)]
+        mode: u8,
+        #[bits(1 * 2)]
+        lanes: [bool; 2],
+        #[bits(2)]
+        __: u8,
+    }
+
+    let flags = Flags::new().with_enable(true).with_lanes(1, true);
+    assert!(flags.enable());
+    assert!(flags.lanes(1));
+    assert_eq!(flags.mode(), 0);
+}
+
+#[test]
+fn inline_accessors() {
+    #[bitfield(u16, inline = always)]
+    struct Always {
+        #[bits(4)]
+        level: u8,
+        #[bits(2 * 6)]
+        lanes: [u8; 6],
+    }
+
+    #[bitfield(u16, inline = never)]
+    struct Never {
+        #[bits(4)]
+        level: u8,
+        #[bits(12)]
+        count: u16,
+    }
+
+    let always = Always::new().with_level(3).with_lanes(5, 2);
+    assert_eq!(always.level(), 3);
+    assert_eq!(always.lanes(5), 2);
+    let mut never = Never::from_bits(0x0123);
+    never.set_level(4);
+    assert_eq!(never.count(), 0x012);
+    assert_eq!(never.into_bits(), 0x0124);
+}
+
+#[test]
+fn without_conversions() {
+    #[bitfield(u8, conversions = false)]
+    struct Level {
+        #[bits(4)]
+        value: u8,
+        #[bits(4)]
+        __: u8,
+    }
+
+    // Would conflict with the generated implementation
+    impl From<u8> for Level {
+        fn from(value: u8) -> Self {
+            Self::new().with_value(value.min(15))
+        }
+    }
+
+    let level = Level::from(20);
+    assert_eq!(level.value(), 15);
+    assert_eq!(level.into_bits(), 15);
+}
+
+#[test]
+fn raw_accessors() {
+    #[bitfield(u16)]
+    struct Timer {
+        #[bits(12)]
+        count: u16,
+        #[bits(4)]
+        prescaler: u8,
+    }
+
+    let timer = Timer::new().with_raw(0x3123);
+    assert_eq!(timer.raw(), 0x3123);
+    assert_eq!(timer.prescaler(), 3);
+    let mut timer = timer;
+    timer.set_raw(0x0456);
+    assert_eq!(timer.count(), 0x456);
+
+    // A field named `raw` keeps its accessors
+    #[bitfield(u8)]
+    struct Sample {
+        #[bits(7)]
+        raw: u8,
+        valid: bool,
+    }
+
+    let sample = Sample::new().with_raw(5);
+    assert_eq!(sample.raw(), 5);
+    assert_eq!(sample.into_bits(), 5);
+}
+
+mod derived {
+    use bitfield_struct::Bitfield;
+
+    /// A plain struct with a packed form
+    #[derive(Bitfield, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfield(u16)]
+    struct Entry {
+        #[bits(10)]
+        address: u16,
+        #[bits(get = is_valid)]
+        valid: bool,
+        #[bits(1 * 2)]
+        lanes: [bool; 2],
+        #[bits(3)]
+        _reserved: u8,
+    }
+
+    #[test]
+    fn derived_bitfield() {
+        let entry = Entry {
+            address: 0x123,
+            valid: true,
+            lanes: [false, true],
+            _reserved: 7,
+        };
+        let packed = PackedEntry::from(entry);
+        assert_eq!(packed.address(), 0x123);
+        assert!(packed.is_valid());
+        assert!(packed.lanes(1));
+        assert_eq!(packed.into_bits(), 0x123 | 1 << 10 | 1 << 12);
+        assert_eq!(
+            Entry::from(packed),
+            Entry {
+                _reserved: 0,
+                ..entry
+            }
+        );
+    }
+}
+
+#[test]
+fn derived_args() {
+    // Cannot be confused with the imported `bitfield` attribute macro
+    #[derive(bitfield_struct::Bitfield, Clone, Copy)]
+    #[bitfield_args(u8)]
+    struct Entry {
+        #[bits(7)]
+        address: u8,
+        valid: bool,
+    }
+
+    let packed = PackedEntry::from(Entry {
+        address: 3,
+        valid: true,
+    });
+    assert_eq!(packed.into_bits(), 0x83);
+}
+
+#[test]
+fn tuple_struct() {
+    #[bitfield(u16)]
+    struct Flags(#[bits(3)] u8, bool, #[bits(4)] (), #[bits(8)] pub i8);
+
+    let mut flags = Flags::new().with_0(5).with_1(true);
+    flags.set_3(-2);
+    assert_eq!(flags.get_0(), 5);
+    assert!(flags.get_1());
+    assert_eq!(flags.get_3(), -2);
+    assert_eq!(flags.into_bits(), 0xfe0d);
+    assert_eq!(Flags::F3_OFFSET, 8);
+    assert_eq!(format!("{flags:?}"), "Flags { f0: 5, f1: true, f3: -2 }");
+}
+
+#[test]
+fn repr_and_alignment() {
+    #[bitfield(u32, repr = C)]
+    struct Shared {
+        #[bits(16)]
+        low: u16,
+        #[bits(16)]
+        high: u16,
+    }
+
+    #[bitfield(u32, repr = (C, align(64)))]
+    struct Descriptor {
+        #[bits(31)]
+        address: u32,
+        valid: bool,
+    }
+
+    assert_eq!(core::mem::size_of::<Shared>(), 4);
+    assert_eq!(core::mem::align_of::<Descriptor>(), 64);
+    assert_eq!(core::mem::size_of::<Descriptor>(), 64);
+    let d = Descriptor::new().with_valid(true);
+    assert_eq!(d.into_bits(), 1 << 31);
+    assert_eq!(Shared::from_bits(0x1234_5678).high(), 0x1234);
+}
+
+#[test]
+fn without_copy() {
+    /// A doorbell value that is consumed when rung
+    #[bitfield(u32, copy = false)]
+    #[derive(PartialEq, Eq)]
+    struct Doorbell {
+        #[bits(16)]
+        queue: u16,
+        #[bits(15)]
+        tail: u16,
+        #[bits(access = W1C)]
+        pending: bool,
+    }
+
+    fn ring(doorbell: Doorbell) -> u32 {
+        doorbell.into_bits()
+    }
+
+    let mut doorbell = Doorbell::new().with_queue(3);
+    doorbell.set_tail(7);
+    doorbell.update_tail(|t| t + 1);
+    doorbell.modify(|d| d.with_queue(4));
+    assert_eq!(doorbell.tail(), 8);
+    assert_eq!(doorbell.raw(), 0x8_0004);
+    assert!(doorbell.clone() == doorbell && !doorbell.is_default());
+    assert_eq!(
+        format!("{doorbell:?}"),
+        "Doorbell { queue: 4, tail: 8, pending: false }"
+    );
+    assert_eq!(ring(doorbell), 0x8_0004);
+}
+
+#[test]
+fn doc_aliases() {
+    #[bitfield(u8, fields_trait = true, builder = true, svd = true, field_enum = true)]
+    struct Uart {
+        /// Transmit FIFO empty
+        #[bits(doc_alias = "TXFE")]
+        tx_empty: bool,
+        #[bits(2 * 2, doc_alias = "LN")]
+        lanes: [u8; 2],
+        #[bits(3, doc_alias = "RXLVL", doc_alias = "RX_LEVEL")]
+        rx_level: u8,
+    }
+
+    let uart = Uart::builder()
+        .tx_empty(true)
+        .rx_level(3)
+        .build()
+        .with_lanes(1, 2);
+    assert!(uart.tx_empty());
+    assert_eq!(uart.rx_level(), 3);
+    assert_eq!(uart.lanes(1), 2);
+}
+
+#[test]
+fn msb0_numbering() {
+    /// The first word of an IPv4 header
+    #[bitfield(u32, order = Msb, msb0 = true)]
+    struct Ipv4Word {
+        #[bits(4)]
+        version: u8,
+        #[bits(4)]
+        ihl: u8,
+        #[bits(2 * 4)]
+        service: [u8; 4],
+        #[bits(16)]
+        total_length: u16,
+    }
+
+    assert_eq!(Ipv4Word::VERSION_MSB_OFFSET, 0);
+    assert_eq!(Ipv4Word::IHL_MSB_OFFSET, 4);
+    assert_eq!(Ipv4Word::SERVICE_MSB_OFFSET, 8);
+    assert_eq!(Ipv4Word::TOTAL_LENGTH_MSB_OFFSET, 16);
+    assert_eq!(Ipv4Word::TOTAL_LENGTH_OFFSET, 0);
+    let word = Ipv4Word::new().with_version(4).with_ihl(5);
+    assert_eq!(word.into_bits() >> 24, 0x45);
+}
+
+#[test]
+fn char_fields() {
+    #[bitfield(u64, try_setters = true, try_getters = true)]
+    struct Tag {
+        #[bits(7)]
+        ascii: char,
+        #[bits(8, default = '?')]
+        latin1: char,
+        unicode: char,
+        #[bits(28)]
+        __: u32,
+    }
+
+    let tag = Tag::new().with_ascii('A').with_unicode('€');
+    assert_eq!(tag.ascii(), 'A');
+    assert_eq!(tag.latin1(), '?');
+    assert_eq!(tag.unicode(), '€');
+    assert_eq!(Tag::UNICODE_BITS, 21);
+    assert!(Tag::new().try_with_ascii('é').is_err());
+    assert!(Tag::new().try_with_latin1('é').is_ok());
+
+    // Surrogates are no valid scalar values
+    let invalid = Tag::from_bits(0xd800 << 15);
+    assert_eq!(invalid.unicode(), char::REPLACEMENT_CHARACTER);
+    assert_eq!(invalid.try_unicode(), Err(0xd800));
+}
+
+#[test]
+fn frame() {
+    use bitfield_struct::frame;
+
+    #[bitfield(u16)]
+    #[derive(PartialEq, Eq)]
+    struct Header {
+        #[bits(4)]
+        version: u8,
+        #[bits(12)]
+        len: u16,
+    }
+
+    #[bitfield(u32)]
+    #[derive(PartialEq, Eq)]
+    struct Body {
+        #[bits(24)]
+        addr: u32,
+        kind: u8,
+    }
+
+    #[frame]
+    #[derive(Debug, PartialEq, Eq)]
+    struct Message {
+        header: Header,
+        reserved: [u8; 2],
+        body: Body,
+    }
+
+    assert_eq!(Message::HEADER_OFFSET, 0);
+    assert_eq!(Message::RESERVED_OFFSET, 2);
+    assert_eq!(Message::BODY_OFFSET, 4);
+    assert_eq!(Message::SIZE, 8);
+
+    let message = Message {
+        header: Header::new().with_version(1).with_len(0x234),
+        reserved: [0xaa, 0xbb],
+        body: Body::new().with_addr(0x56789a).with_kind(0xbc),
+    };
+    let bytes = message.to_bytes();
+    assert_eq!(bytes, [0x23, 0x41, 0xaa, 0xbb, 0xbc, 0x56, 0x78, 0x9a]);
+    let message = Message::from_bytes(bytes);
+    assert_eq!(message.header.len(), 0x234);
+    assert_eq!(message.body.kind(), 0xbc);
+    assert_eq!(message.reserved, [0xaa, 0xbb]);
+
+    #[frame(endian = little)]
+    struct Little {
+        header: Header,
+    }
+    let little = Little {
+        header: Header::from_bits(0x1234),
+    };
+    assert_eq!(little.to_bytes(), [0x34, 0x12]);
+}